@@ -2,6 +2,25 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20220101_000001_create_table;
 mod m20220101_000002_add_nft_metadata;
+mod m20220101_000003_create_mint_jobs;
+mod m20220101_000004_create_auth_sessions;
+mod m20220101_000005_add_user_roles;
+mod m20220101_000006_create_collections;
+mod m20220101_000007_add_nft_search_indexes;
+mod m20220101_000008_nullable_mint_block_number;
+mod m20220101_000009_create_transactions;
+mod m20220101_000010_add_mint_failures;
+mod m20220101_000011_add_eip1559_fees;
+mod m20220101_000012_add_transaction_block_hash;
+mod m20220101_000013_add_account_nonces;
+mod m20220101_000014_wei_amounts_as_text;
+mod m20220101_000015_create_transfers;
+mod m20220101_000016_add_nft_burns;
+mod m20220101_000017_create_idempotency_keys;
+mod m20220101_000018_add_mint_job_batches;
+mod m20220101_000019_sequential_token_ids;
+mod m20220101_000020_unique_pending_transfers;
+mod m20220101_000021_unique_uncollected_token_ids;
 
 pub struct Migrator;
 
@@ -11,6 +30,25 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20220101_000001_create_table::Migration),
             Box::new(m20220101_000002_add_nft_metadata::Migration),
+            Box::new(m20220101_000003_create_mint_jobs::Migration),
+            Box::new(m20220101_000004_create_auth_sessions::Migration),
+            Box::new(m20220101_000005_add_user_roles::Migration),
+            Box::new(m20220101_000006_create_collections::Migration),
+            Box::new(m20220101_000007_add_nft_search_indexes::Migration),
+            Box::new(m20220101_000008_nullable_mint_block_number::Migration),
+            Box::new(m20220101_000009_create_transactions::Migration),
+            Box::new(m20220101_000010_add_mint_failures::Migration),
+            Box::new(m20220101_000011_add_eip1559_fees::Migration),
+            Box::new(m20220101_000012_add_transaction_block_hash::Migration),
+            Box::new(m20220101_000013_add_account_nonces::Migration),
+            Box::new(m20220101_000014_wei_amounts_as_text::Migration),
+            Box::new(m20220101_000015_create_transfers::Migration),
+            Box::new(m20220101_000016_add_nft_burns::Migration),
+            Box::new(m20220101_000017_create_idempotency_keys::Migration),
+            Box::new(m20220101_000018_add_mint_job_batches::Migration),
+            Box::new(m20220101_000019_sequential_token_ids::Migration),
+            Box::new(m20220101_000020_unique_pending_transfers::Migration),
+            Box::new(m20220101_000021_unique_uncollected_token_ids::Migration),
         ]
     }
 }
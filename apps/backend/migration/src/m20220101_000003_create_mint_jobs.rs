@@ -0,0 +1,86 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create mint_jobs table so in-flight mints survive a restart
+        manager
+            .create_table(
+                Table::create()
+                    .table(MintJobs::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MintJobs::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(MintJobs::NftId).string().not_null())
+                    .col(ColumnDef::new(MintJobs::Status).string().not_null())
+                    .col(ColumnDef::new(MintJobs::TransactionHash).string().not_null())
+                    .col(ColumnDef::new(MintJobs::BlockNumber).big_integer().not_null())
+                    .col(ColumnDef::new(MintJobs::GasUsed).big_integer().not_null())
+                    .col(ColumnDef::new(MintJobs::GasPrice).big_integer().not_null())
+                    .col(ColumnDef::new(MintJobs::Confirmations).integer().not_null().default(0))
+                    .col(ColumnDef::new(MintJobs::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(MintJobs::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(MintJobs::ConfirmedAt).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mint_job_nft")
+                            .from(MintJobs::Table, MintJobs::NftId)
+                            .to(Nfts::Table, Nfts::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The confirmation worker looks up unfinished jobs by status on startup
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mint_jobs_status")
+                    .table(MintJobs::Table)
+                    .col(MintJobs::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mint_jobs_nft_id")
+                    .table(MintJobs::Table)
+                    .col(MintJobs::NftId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(MintJobs::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum MintJobs {
+    Table,
+    Id,
+    NftId,
+    Status,
+    TransactionHash,
+    BlockNumber,
+    GasUsed,
+    GasPrice,
+    Confirmations,
+    CreatedAt,
+    UpdatedAt,
+    ConfirmedAt,
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    Id,
+}
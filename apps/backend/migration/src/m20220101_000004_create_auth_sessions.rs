@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create auth_nonces table; each SIWE nonce can be redeemed once
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuthNonces::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AuthNonces::Nonce).string().not_null().primary_key())
+                    .col(ColumnDef::new(AuthNonces::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(AuthNonces::ExpiresAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create sessions table; only a hash of the bearer token is stored
+        manager
+            .create_table(
+                Table::create()
+                    .table(Sessions::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Sessions::TokenHash).string().not_null().primary_key())
+                    .col(ColumnDef::new(Sessions::UserId).string().not_null())
+                    .col(ColumnDef::new(Sessions::WalletAddress).string().not_null())
+                    .col(ColumnDef::new(Sessions::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Sessions::ExpiresAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_user")
+                            .from(Sessions::Table, Sessions::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_sessions_user_id")
+                    .table(Sessions::Table)
+                    .col(Sessions::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Sessions::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(AuthNonces::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum AuthNonces {
+    Table,
+    Nonce,
+    CreatedAt,
+    ExpiresAt,
+}
+
+#[derive(Iden)]
+enum Sessions {
+    Table,
+    TokenHash,
+    UserId,
+    WalletAddress,
+    CreatedAt,
+    ExpiresAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
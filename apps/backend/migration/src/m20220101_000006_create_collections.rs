@@ -0,0 +1,149 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create collections table
+        manager
+            .create_table(
+                Table::create()
+                    .table(Collections::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Collections::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(Collections::CreatorId).string().not_null())
+                    .col(ColumnDef::new(Collections::Name).string().not_null())
+                    .col(ColumnDef::new(Collections::Slug).string().not_null().unique_key())
+                    .col(ColumnDef::new(Collections::Description).text().null())
+                    .col(ColumnDef::new(Collections::ImageUrl).string().null())
+                    .col(ColumnDef::new(Collections::BannerUrl).string().null())
+                    .col(ColumnDef::new(Collections::MaxSupply).big_integer().null())
+                    .col(ColumnDef::new(Collections::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Collections::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_collection_creator")
+                            .from(Collections::Table, Collections::CreatorId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Link nfts to their collection; deleting a collection leaves its NFTs uncategorised
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .add_column(ColumnDef::new(Nfts::CollectionId).string().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_nft_collection")
+                            .from_tbl(Nfts::Table)
+                            .from_col(Nfts::CollectionId)
+                            .to_tbl(Collections::Table)
+                            .to_col(Collections::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nfts_collection_id")
+                    .table(Nfts::Table)
+                    .col(Nfts::CollectionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backfill one collection per collection_name, created by whoever minted into it first and
+        // spelt as they did. Names are matched like find_or_create_collection_by_name does: trimmed
+        // and ignoring case. Names that slugify to the same value get a numeric suffix to keep slugs unique.
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            WITH names AS (
+                SELECT DISTINCT ON (lower(trim(collection_name)))
+                       trim(collection_name) AS name,
+                       owner_id AS creator_id,
+                       minted_at AS created_at,
+                       COALESCE(NULLIF(trim(both '-' from regexp_replace(lower(collection_name), '[^a-z0-9]+', '-', 'g')), ''), 'collection') AS base_slug
+                FROM nfts
+                WHERE trim(collection_name) <> ''
+                ORDER BY lower(trim(collection_name)), minted_at
+            ),
+            numbered AS (
+                SELECT *, ROW_NUMBER() OVER (PARTITION BY base_slug ORDER BY created_at, name) AS n
+                FROM names
+            )
+            INSERT INTO collections (id, creator_id, name, slug, created_at, updated_at)
+            SELECT substr(md5(name), 1, 24),
+                   creator_id,
+                   name,
+                   CASE WHEN n = 1 THEN base_slug ELSE base_slug || '-' || n END,
+                   created_at,
+                   created_at
+            FROM numbered
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            UPDATE nfts SET collection_id = collections.id, collection_name = collections.name
+            FROM collections
+            WHERE lower(trim(nfts.collection_name)) = lower(collections.name)
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .drop_foreign_key(Alias::new("fk_nft_collection"))
+                    .drop_column(Nfts::CollectionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager.drop_table(Table::drop().table(Collections::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum Collections {
+    Table,
+    Id,
+    CreatorId,
+    Name,
+    Slug,
+    Description,
+    ImageUrl,
+    BannerUrl,
+    MaxSupply,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    CollectionId,
+}
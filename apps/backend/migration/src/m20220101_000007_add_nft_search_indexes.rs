@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Store attributes as jsonb so trait filters can use containment (@>)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .modify_column(ColumnDef::new(Nfts::Attributes).json_binary().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // GIN index backing `attributes @> '[{"trait_type": ..., "value": ...}]'`
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_nfts_attributes ON nfts USING GIN (attributes jsonb_path_ops)",
+        )
+        .await?;
+
+        // Full-text index over name and description; search queries must use the same expression
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_nfts_search ON nfts USING GIN (to_tsvector('english', name || ' ' || coalesce(description, '')))",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_nfts_search").await?;
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_nfts_attributes").await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .modify_column(ColumnDef::new(Nfts::Attributes).json().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    Attributes,
+}
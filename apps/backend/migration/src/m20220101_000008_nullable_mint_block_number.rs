@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Transactions still in the mempool have not been included in a block yet
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .modify_column(ColumnDef::new(MintJobs::BlockNumber).big_integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("UPDATE mint_jobs SET block_number = 0 WHERE block_number IS NULL")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .modify_column(ColumnDef::new(MintJobs::BlockNumber).big_integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum MintJobs {
+    Table,
+    BlockNumber,
+}
@@ -0,0 +1,121 @@
+use sea_orm_migration::prelude::*;
+
+/// Contract address recorded for mints made before transactions were persisted
+const LEGACY_CONTRACT_ADDRESS: &str = "0x8a90CAb2b38dba80c64b7734e58Ee1dB38B8992e";
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per simulated transaction, backing receipt lookups by hash
+        manager
+            .create_table(
+                Table::create()
+                    .table(Transactions::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Transactions::Hash).string().not_null().primary_key())
+                    .col(ColumnDef::new(Transactions::FromAddress).string().not_null())
+                    .col(ColumnDef::new(Transactions::ToAddress).string().not_null())
+                    .col(ColumnDef::new(Transactions::NftId).string().null())
+                    .col(ColumnDef::new(Transactions::Status).string().not_null())
+                    .col(ColumnDef::new(Transactions::BlockNumber).big_integer().null())
+                    .col(ColumnDef::new(Transactions::BlockTimestamp).timestamp().null())
+                    .col(ColumnDef::new(Transactions::GasUsed).big_integer().not_null())
+                    .col(ColumnDef::new(Transactions::GasPrice).big_integer().not_null())
+                    .col(ColumnDef::new(Transactions::Logs).json_binary().not_null())
+                    .col(ColumnDef::new(Transactions::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Transactions::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_transaction_nft")
+                            .from(Transactions::Table, Transactions::NftId)
+                            .to(Nfts::Table, Nfts::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transactions_block_number")
+                    .table(Transactions::Table)
+                    .col(Transactions::BlockNumber)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transactions_nft_id")
+                    .table(Transactions::Table)
+                    .col(Transactions::NftId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backfill a transaction for every existing mint job; its block time is approximated by the job's creation
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                r#"INSERT INTO transactions
+                       (hash, from_address, to_address, nft_id, status, block_number, block_timestamp,
+                        gas_used, gas_price, logs, created_at, updated_at)
+                   SELECT DISTINCT ON (j.transaction_hash)
+                          j.transaction_hash, u.public_key, '{contract}', n.id,
+                          CASE j.status WHEN 'Confirmed' THEN 'Confirmed' WHEN 'Failed' THEN 'Failed' ELSE 'Pending' END,
+                          j.block_number,
+                          CASE WHEN j.block_number IS NULL THEN NULL ELSE j.created_at END,
+                          j.gas_used, j.gas_price,
+                          jsonb_build_array(jsonb_build_object(
+                              'log_index', 0,
+                              'address', '{contract}',
+                              'event', 'Transfer',
+                              'from', '0x0000000000000000000000000000000000000000',
+                              'to', u.public_key,
+                              'token_id', n.token_id
+                          )),
+                          j.created_at, j.updated_at
+                   FROM mint_jobs j
+                   JOIN nfts n ON n.id = j.nft_id
+                   JOIN users u ON u.id = n.owner_id
+                   ORDER BY j.transaction_hash, j.created_at
+                   ON CONFLICT (hash) DO NOTHING"#,
+                contract = LEGACY_CONTRACT_ADDRESS,
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Transactions::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum Transactions {
+    Table,
+    Hash,
+    FromAddress,
+    ToAddress,
+    NftId,
+    Status,
+    BlockNumber,
+    BlockTimestamp,
+    GasUsed,
+    GasPrice,
+    Logs,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    Id,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Track each NFT's mint so failed mints can be hidden; existing rows take their job's status
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .add_column(ColumnDef::new(Nfts::MintStatus).string().not_null().default("Confirmed"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("UPDATE nfts SET mint_status = j.status FROM mint_jobs j WHERE j.nft_id = nfts.id")
+            .await?;
+
+        // The failure a job was sent with survives a restart; the revert reason is kept once it fails
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .add_column(ColumnDef::new(MintJobs::SimulatedFailure).string().null())
+                    .add_column(ColumnDef::new(MintJobs::RevertReason).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(ColumnDef::new(Transactions::RevertReason).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(Transactions::RevertReason)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .drop_column(MintJobs::SimulatedFailure)
+                    .drop_column(MintJobs::RevertReason)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .drop_column(Nfts::MintStatus)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    MintStatus,
+}
+
+#[derive(Iden)]
+enum MintJobs {
+    Table,
+    SimulatedFailure,
+    RevertReason,
+}
+
+#[derive(Iden)]
+enum Transactions {
+    Table,
+    RevertReason,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Fee caps a mint was sent with; rows from before EIP-1559 simulation only have a gas price
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .add_column(ColumnDef::new(MintJobs::MaxFeePerGas).big_integer().null())
+                    .add_column(ColumnDef::new(MintJobs::MaxPriorityFeePerGas).big_integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The including block's base fee is kept so blocks can be described after a restart
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(ColumnDef::new(Transactions::MaxFeePerGas).big_integer().null())
+                    .add_column(ColumnDef::new(Transactions::MaxPriorityFeePerGas).big_integer().null())
+                    .add_column(ColumnDef::new(Transactions::BaseFeePerGas).big_integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(Transactions::MaxFeePerGas)
+                    .drop_column(Transactions::MaxPriorityFeePerGas)
+                    .drop_column(Transactions::BaseFeePerGas)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .drop_column(MintJobs::MaxFeePerGas)
+                    .drop_column(MintJobs::MaxPriorityFeePerGas)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum MintJobs {
+    Table,
+    MaxFeePerGas,
+    MaxPriorityFeePerGas,
+}
+
+#[derive(Iden)]
+enum Transactions {
+    Table,
+    MaxFeePerGas,
+    MaxPriorityFeePerGas,
+    BaseFeePerGas,
+}
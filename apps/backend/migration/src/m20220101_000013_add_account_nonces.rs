@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Each sender's transactions carry consecutive nonces; a replaced one points at its replacement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .add_column(ColumnDef::new(Transactions::Nonce).big_integer().null())
+                    .add_column(ColumnDef::new(Transactions::ReplacedBy).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing transactions are numbered per sender in the order they were sent
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE transactions SET nonce = numbered.nonce FROM (
+                    SELECT hash, ROW_NUMBER() OVER (PARTITION BY LOWER(from_address) ORDER BY created_at, hash) - 1 AS nonce
+                    FROM transactions
+                ) numbered
+                WHERE numbered.hash = transactions.hash",
+            )
+            .await?;
+
+        // Jobs keep the sender and nonce of their current transaction, and whether it cancels the mint
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .add_column(ColumnDef::new(MintJobs::FromAddress).string().null())
+                    .add_column(ColumnDef::new(MintJobs::Nonce).big_integer().null())
+                    .add_column(ColumnDef::new(MintJobs::Cancelled).boolean().not_null().default(false))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE mint_jobs SET from_address = t.from_address, nonce = t.nonce
+                FROM transactions t WHERE t.hash = mint_jobs.transaction_hash",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .drop_column(MintJobs::FromAddress)
+                    .drop_column(MintJobs::Nonce)
+                    .drop_column(MintJobs::Cancelled)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transactions::Table)
+                    .drop_column(Transactions::Nonce)
+                    .drop_column(Transactions::ReplacedBy)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum MintJobs {
+    Table,
+    FromAddress,
+    Nonce,
+    Cancelled,
+}
+
+#[derive(Iden)]
+enum Transactions {
+    Table,
+    Nonce,
+    ReplacedBy,
+}
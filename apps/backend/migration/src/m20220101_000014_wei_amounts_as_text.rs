@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Wei amounts outgrow bigint, so they are kept as decimal text
+const WEI_COLUMNS: [(&str, &str); 7] = [
+    ("mint_jobs", "gas_price"),
+    ("mint_jobs", "max_fee_per_gas"),
+    ("mint_jobs", "max_priority_fee_per_gas"),
+    ("transactions", "gas_price"),
+    ("transactions", "max_fee_per_gas"),
+    ("transactions", "max_priority_fee_per_gas"),
+    ("transactions", "base_fee_per_gas"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (table, column) in WEI_COLUMNS {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!("ALTER TABLE {table} ALTER COLUMN {column} TYPE text"))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Fails on any amount above the bigint range rather than truncating it
+        for (table, column) in WEI_COLUMNS {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!("ALTER TABLE {table} ALTER COLUMN {column} TYPE bigint USING {column}::bigint"))
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per change of owner after the mint, sent as its own simulated transaction
+        manager
+            .create_table(
+                Table::create()
+                    .table(Transfers::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Transfers::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(Transfers::NftId).string().not_null())
+                    .col(ColumnDef::new(Transfers::FromAddress).string().not_null())
+                    .col(ColumnDef::new(Transfers::ToAddress).string().not_null())
+                    .col(ColumnDef::new(Transfers::TransactionHash).string().not_null().unique_key())
+                    .col(ColumnDef::new(Transfers::Status).string().not_null())
+                    .col(ColumnDef::new(Transfers::BlockNumber).big_integer().null())
+                    .col(ColumnDef::new(Transfers::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Transfers::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(Transfers::ConfirmedAt).timestamp().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_transfer_nft")
+                            .from(Transfers::Table, Transfers::NftId)
+                            .to(Nfts::Table, Nfts::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_transfer_transaction")
+                            .from(Transfers::Table, Transfers::TransactionHash)
+                            .to(Transactions::Table, Transactions::Hash)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transfers_nft_id")
+                    .table(Transfers::Table)
+                    .col(Transfers::NftId)
+                    .col(Transfers::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transfers_status")
+                    .table(Transfers::Table)
+                    .col(Transfers::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Transfers::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum Transfers {
+    Table,
+    Id,
+    NftId,
+    FromAddress,
+    ToAddress,
+    TransactionHash,
+    Status,
+    BlockNumber,
+    CreatedAt,
+    UpdatedAt,
+    ConfirmedAt,
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Transactions {
+    Table,
+    Hash,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A burned NFT keeps its row for provenance; listings and supply skip it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .add_column(ColumnDef::new(Nfts::BurnedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Nfts::Table)
+                    .drop_column(Nfts::BurnedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    BurnedAt,
+}
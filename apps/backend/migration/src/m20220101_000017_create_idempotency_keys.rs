@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A client's Idempotency-Key, with a fingerprint of the request it was first sent with and the
+        // response to replay; the response stays empty while that request is still being handled
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdempotencyKeys::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(IdempotencyKeys::UserId).string().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::Key).string().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::RequestHash).string().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::StatusCode).small_integer().null())
+                    .col(ColumnDef::new(IdempotencyKeys::Response).json_binary().null())
+                    .col(ColumnDef::new(IdempotencyKeys::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(IdempotencyKeys::ExpiresAt).timestamp().not_null())
+                    .primary_key(Index::create().col(IdempotencyKeys::UserId).col(IdempotencyKeys::Key))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_idempotency_key_user")
+                            .from(IdempotencyKeys::Table, IdempotencyKeys::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_idempotency_keys_expires_at")
+                    .table(IdempotencyKeys::Table)
+                    .col(IdempotencyKeys::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(IdempotencyKeys::Table).to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum IdempotencyKeys {
+    Table,
+    UserId,
+    Key,
+    RequestHash,
+    StatusCode,
+    Response,
+    CreatedAt,
+    ExpiresAt,
+}
+
+#[derive(Iden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Jobs minted together by one batch request share its ID
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .add_column(ColumnDef::new(MintJobs::BatchId).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mint_jobs_batch_id")
+                    .table(MintJobs::Table)
+                    .col(MintJobs::BatchId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // A batch's jobs share a transaction, and fee totals split its fee between them
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mint_jobs_transaction_hash")
+                    .table(MintJobs::Table)
+                    .col(MintJobs::TransactionHash)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_mint_jobs_transaction_hash").table(MintJobs::Table).to_owned())
+            .await?;
+        manager
+            .drop_index(Index::drop().name("idx_mint_jobs_batch_id").table(MintJobs::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MintJobs::Table)
+                    .drop_column(MintJobs::BatchId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum MintJobs {
+    Table,
+    BatchId,
+    TransactionHash,
+}
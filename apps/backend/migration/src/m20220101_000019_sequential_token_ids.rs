@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Each collection hands out its token IDs from a counter, locked by the mint that takes them
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .add_column(ColumnDef::new(Collections::NextTokenId).big_integer().not_null().default(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Number existing NFTs 1, 2, 3... within their collection in mint order, and rewrite the
+        // Transfer logs that carried their old IDs. The logs go first, while the old IDs are still there.
+        let db = manager.get_connection();
+        let renumbered = "WITH renumbered AS (
+                              SELECT id, token_id AS old_token_id,
+                                     ROW_NUMBER() OVER (PARTITION BY collection_id ORDER BY minted_at, id)::text AS new_token_id
+                              FROM nfts
+                          )";
+        db.execute_unprepared(&format!(
+            "{renumbered}
+             UPDATE transactions t
+             SET logs = (
+                 SELECT jsonb_agg(COALESCE(
+                     (SELECT jsonb_set(e.log, '{{token_id}}', to_jsonb(r.new_token_id))
+                      FROM renumbered r WHERE r.old_token_id = e.log->>'token_id' LIMIT 1),
+                     e.log) ORDER BY e.position)
+                 FROM jsonb_array_elements(t.logs) WITH ORDINALITY AS e(log, position)
+             )
+             WHERE jsonb_array_length(t.logs) > 0"
+        ))
+        .await?;
+        db.execute_unprepared(&format!(
+            "{renumbered}
+             UPDATE nfts n SET token_id = r.new_token_id FROM renumbered r WHERE r.id = n.id"
+        ))
+        .await?;
+        db.execute_unprepared(
+            "UPDATE collections c
+             SET next_token_id = 1 + (SELECT COUNT(*) FROM nfts n WHERE n.collection_id = c.id)",
+        )
+        .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nfts_collection_token_id")
+                    .table(Nfts::Table)
+                    .col(Nfts::CollectionId)
+                    .col(Nfts::TokenId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The old random token IDs are gone; NFTs keep their numbers
+        manager
+            .drop_index(Index::drop().name("idx_nfts_collection_token_id").table(Nfts::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Collections::Table)
+                    .drop_column(Collections::NextTokenId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Collections {
+    Table,
+    NextTokenId,
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+    CollectionId,
+    TokenId,
+}
@@ -0,0 +1,31 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // An NFT has at most one transfer waiting to be confirmed, even if two requests race past the queue's checks
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE UNIQUE INDEX idx_transfers_pending_nft_id ON transfers (nft_id)
+                 WHERE status IN ('Pending', 'Confirming')",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_transfers_pending_nft_id").table(Transfers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Transfers {
+    Table,
+}
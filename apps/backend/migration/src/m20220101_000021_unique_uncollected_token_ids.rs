@@ -0,0 +1,32 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The (collection_id, token_id) index treats NULL collections as distinct, so NFTs outside
+        // any collection need their own
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE UNIQUE INDEX idx_nfts_uncollected_token_id ON nfts (token_id)
+                 WHERE collection_id IS NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_nfts_uncollected_token_id").table(Nfts::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Nfts {
+    Table,
+}
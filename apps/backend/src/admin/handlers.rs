@@ -4,131 +4,234 @@ use axum::{
     response::IntoResponse,
     http::StatusCode,
 };
-use serde::Deserialize;
 use crate::{
     database::DbPool,
     auth::types::ApiResponse,
     admin::types::*,
-    db_operations::{get_nfts_with_owner, find_user_by_public_key, get_nfts_by_owner},
+    db_operations::{
+        update_user_role, get_platform_totals, get_daily_mints, get_top_collections,
+        count_new_users_since, count_active_users_since, get_most_active_users, get_users_with_activity,
+        NftSearch,
+    },
+    nft::search::load_nft_page,
+    minting_queue::MintingQueue,
+    blockchain_sim::{Clock, SharedSimulator},
+    pagination::{PaginatedResponse, PaginationQuery},
 };
 use chrono::{Utc, Duration};
 
-#[derive(Debug, Deserialize)]
-pub struct AdminQuery {
-    pub page: Option<u64>,
-    pub limit: Option<u64>,
+/// Number of days covered by the minting trend and daily mint series
+const TREND_DAYS: i64 = 30;
+/// Longest single fast-forward of the simulator clock; each step produces every block it skips
+const MAX_CLOCK_ADVANCE_SECS: u64 = 24 * 60 * 60;
+
+async fn load_admin_stats(pool: &DbPool) -> anyhow::Result<AdminStats> {
+    let totals = get_platform_totals(pool).await?;
+    let now = Utc::now().naive_utc();
+    let day_ago = now - Duration::hours(24);
+    let week_ago = now - Duration::days(7);
+
+    let minting_trends = get_daily_mints(pool, TREND_DAYS)
+        .await?
+        .into_iter()
+        .map(|row| MintingTrend {
+            date: row.date,
+            count: row.count as u64,
+            volume: row.volume,
+        })
+        .collect();
+
+    let popular_collections = get_top_collections(pool, 10)
+        .await?
+        .into_iter()
+        .map(|row| PopularCollection {
+            id: row.id,
+            name: row.name,
+            nft_count: row.nft_count as u64,
+            total_volume: row.volume,
+        })
+        .collect();
+
+    Ok(AdminStats {
+        total_users: totals.total_users as u64,
+        total_nfts: totals.total_nfts as u64,
+        total_collections: totals.total_collections as u64,
+        total_transactions: totals.total_transactions as u64,
+        minting_trends,
+        popular_collections,
+        user_engagement: UserEngagement {
+            active_users_24h: count_active_users_since(pool, day_ago).await? as u64,
+            active_users_7d: count_active_users_since(pool, week_ago).await? as u64,
+            new_users_24h: count_new_users_since(pool, day_ago).await? as u64,
+            new_users_7d: count_new_users_since(pool, week_ago).await? as u64,
+        },
+    })
 }
 
 pub async fn get_admin_stats_handler(
     State(pool): State<DbPool>,
 ) -> impl IntoResponse {
-    // Generate demo stats
-    let stats = AdminStats {
-        total_users: 1247,
-        total_nfts: 8923,
-        total_collections: 156,
-        total_transactions: 15420,
-        minting_trends: generate_minting_trends(),
-        popular_collections: generate_popular_collections(),
-        user_engagement: UserEngagement {
-            active_users_24h: 342,
-            active_users_7d: 1247,
-            new_users_24h: 89,
-            new_users_7d: 456,
-        },
-    };
-
-    let response = ApiResponse {
-        success: true,
-        data: Some(stats),
-        message: "Admin stats retrieved successfully".to_string(),
-    };
-
-    (StatusCode::OK, Json(response))
+    match load_admin_stats(&pool).await {
+        Ok(stats) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(stats),
+                message: "Admin stats retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => {
+            let response = ApiResponse::<AdminStats> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve admin stats: {}", e),
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        }
+    }
 }
 
 pub async fn get_admin_users_handler(
     State(pool): State<DbPool>,
-    Query(query): Query<AdminQuery>,
+    Query(query): Query<PaginationQuery>,
 ) -> impl IntoResponse {
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.page.unwrap_or(0) * limit;
-
-    // Generate demo users
-    let users: Vec<AdminUser> = (offset..offset + limit)
-        .map(|i| AdminUser {
-            id: format!("user_{}", i + 1),
-            public_key: format!("0x{:040x}", i + 1),
-            nft_count: (i % 10) + 1,
-            created_at: Utc::now() - Duration::days((i % 30) as i64),
-            last_active: Some(Utc::now() - Duration::hours((i % 24) as i64)),
-        })
-        .collect();
+    if query.cursor.is_some() {
+        let response = ApiResponse::<PaginatedResponse<AdminUser>> {
+            success: false,
+            data: None,
+            message: "Users are paginated by page, not cursor".to_string(),
+        };
+        return (StatusCode::BAD_REQUEST, Json(response));
+    }
 
-    let response = ApiResponse {
-        success: true,
-        data: Some(PaginatedResponse {
-            data: users,
-            total: 1247,
-            page: query.page.unwrap_or(0),
-            limit,
-        }),
-        message: "Admin users retrieved successfully".to_string(),
-    };
+    match get_users_with_activity(&pool, query.limit(), query.offset()).await {
+        Ok((users, total)) => {
+            let users = users
+                .into_iter()
+                .map(|user| AdminUser {
+                    id: user.id,
+                    public_key: user.public_key,
+                    nft_count: user.nft_count as u64,
+                    created_at: chrono::DateTime::from_naive_utc_and_offset(user.created_at, Utc),
+                    last_active: user.last_active.map(|at| chrono::DateTime::from_naive_utc_and_offset(at, Utc)),
+                })
+                .collect();
 
-    (StatusCode::OK, Json(response))
+            let response = ApiResponse {
+                success: true,
+                data: Some(PaginatedResponse::new(users, total, &query)),
+                message: "Admin users retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => {
+            let response = ApiResponse::<PaginatedResponse<AdminUser>> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve users: {}", e),
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        }
+    }
 }
 
 pub async fn get_admin_nfts_handler(
     State(pool): State<DbPool>,
-    Query(query): Query<AdminQuery>,
+    Query(query): Query<PaginationQuery>,
 ) -> impl IntoResponse {
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.page.unwrap_or(0) * limit;
-
-    // Generate demo NFTs
-    let nfts: Vec<AdminNFT> = (offset..offset + limit)
-        .map(|i| AdminNFT {
-            id: format!("nft_{}", i + 1),
-            token_id: format!("NFT-{:06}", i + 1),
-            name: format!("Demo NFT #{}", i + 1),
-            owner: format!("0x{:040x}", i % 100),
-            collection: Some(format!("Collection {}", (i % 10) + 1)),
-            minted_at: Utc::now() - Duration::hours((i % 168) as i64),
-            is_featured: i % 20 == 0,
+    match load_nft_page(&pool, &NftSearch::default(), &query).await {
+        Ok(page) => {
+            let nfts = page.map(|(nft, owner)| AdminNFT {
+                id: nft.id,
+                token_id: nft.token_id,
+                name: nft.name,
+                owner: owner.public_key,
+                collection: nft.collection_name,
+                minted_at: chrono::DateTime::from_naive_utc_and_offset(nft.minted_at, Utc),
+                is_featured: false,
+            });
+
+            let response = ApiResponse {
+                success: true,
+                data: Some(nfts),
+                message: "Admin NFTs retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err((status, message)) => {
+            let response = ApiResponse::<PaginatedResponse<AdminNFT>> {
+                success: false,
+                data: None,
+                message,
+            };
+            (status, Json(response))
+        }
+    }
+}
+
+async fn load_analytics(pool: &DbPool) -> anyhow::Result<AnalyticsData> {
+    let daily_mints = get_daily_mints(pool, TREND_DAYS)
+        .await?
+        .into_iter()
+        .map(|row| DailyMint {
+            date: row.date,
+            count: row.count as u64,
+            unique_users: row.unique_users as u64,
         })
         .collect();
 
-    let response = ApiResponse {
-        success: true,
-        data: Some(PaginatedResponse {
-            data: nfts,
-            total: 8923,
-            page: query.page.unwrap_or(0),
-            limit,
-        }),
-        message: "Admin NFTs retrieved successfully".to_string(),
-    };
+    let collection_performance = get_top_collections(pool, 15)
+        .await?
+        .into_iter()
+        .map(|row| CollectionPerformance {
+            collection_id: row.id,
+            collection_name: row.name,
+            nft_count: row.nft_count as u64,
+            unique_owners: row.unique_owners as u64,
+            // Nothing is sold on the platform yet, so there is no price to average
+            avg_price: None,
+        })
+        .collect();
 
-    (StatusCode::OK, Json(response))
+    let user_activity = get_most_active_users(pool, 20)
+        .await?
+        .into_iter()
+        .map(|row| UserActivity {
+            user_id: row.user_id,
+            wallet_address: row.wallet_address,
+            nft_count: row.nft_count as u64,
+            last_mint: row.last_mint.map(|at| chrono::DateTime::from_naive_utc_and_offset(at, Utc)),
+        })
+        .collect();
+
+    Ok(AnalyticsData {
+        daily_mints,
+        collection_performance,
+        user_activity,
+    })
 }
 
 pub async fn get_admin_analytics_handler(
     State(pool): State<DbPool>,
 ) -> impl IntoResponse {
-    let analytics = AnalyticsData {
-        daily_mints: generate_daily_mints(),
-        collection_performance: generate_collection_performance(),
-        user_activity: generate_user_activity(),
-    };
-
-    let response = ApiResponse {
-        success: true,
-        data: Some(analytics),
-        message: "Admin analytics retrieved successfully".to_string(),
-    };
-
-    (StatusCode::OK, Json(response))
+    match load_analytics(&pool).await {
+        Ok(analytics) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(analytics),
+                message: "Admin analytics retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => {
+            let response = ApiResponse::<AnalyticsData> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve admin analytics: {}", e),
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        }
+    }
 }
 
 pub async fn set_featured_nfts_handler(
@@ -162,63 +265,135 @@ pub async fn reset_demo_data_handler(
     (StatusCode::OK, Json(response))
 }
 
-// Helper functions to generate demo data
-fn generate_minting_trends() -> Vec<MintingTrend> {
-    (0..30).map(|i| {
-        let date = (Utc::now() - Duration::days(i as i64)).format("%Y-%m-%d").to_string();
-        MintingTrend {
-            date,
-            count: (i % 50) + 10,
-            volume: (i as f64 * 0.5) + 1.0,
+pub async fn update_user_role_handler(
+    State(pool): State<DbPool>,
+    Path(user_id): Path<String>,
+    Json(payload): Json<UpdateRoleRequest>,
+) -> impl IntoResponse {
+    match update_user_role(&pool, &user_id, payload.role).await {
+        Ok(Some(user)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(UserRoleResponse {
+                    id: user.id,
+                    public_key: user.public_key,
+                    role: payload.role,
+                }),
+                message: "User role updated successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
         }
-    }).rev().collect()
+        Ok(None) => {
+            let response = ApiResponse::<UserRoleResponse> {
+                success: false,
+                data: None,
+                message: "User not found".to_string(),
+            };
+            (StatusCode::NOT_FOUND, Json(response))
+        }
+        Err(e) => {
+            let response = ApiResponse::<UserRoleResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to update user role: {}", e),
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        }
+    }
 }
 
-fn generate_popular_collections() -> Vec<PopularCollection> {
-    (1..11).map(|i| PopularCollection {
-        id: format!("collection_{}", i),
-        name: format!("Popular Collection {}", i),
-        nft_count: (i * 100) + (i % 50),
-        total_volume: (i as f64 * 10.5) + 5.0,
-    }).collect()
-}
+/// Fast-forward the simulator's manual clock and let the confirmation worker catch up
+pub async fn advance_simulator_clock_handler(
+    State(simulator): State<SharedSimulator>,
+    State(minting_queue): State<MintingQueue>,
+    Json(payload): Json<AdvanceClockRequest>,
+) -> impl IntoResponse {
+    if payload.seconds == 0 || payload.seconds > MAX_CLOCK_ADVANCE_SECS {
+        let response = ApiResponse::<SimulatorClockResponse> {
+            success: false,
+            data: None,
+            message: format!("Seconds must be between 1 and {}", MAX_CLOCK_ADVANCE_SECS),
+        };
+        return (StatusCode::BAD_REQUEST, Json(response));
+    }
 
-fn generate_daily_mints() -> Vec<DailyMint> {
-    (0..30).map(|i| {
-        let date = (Utc::now() - Duration::days(i as i64)).format("%Y-%m-%d").to_string();
-        DailyMint {
-            date,
-            count: (i % 100) + 20,
-            unique_users: (i % 50) + 10,
-        }
-    }).rev().collect()
-}
+    let Some(now) = simulator.clock().advance(payload.seconds) else {
+        let (status, message) = match simulator.clock() {
+            Clock::System => (
+                StatusCode::CONFLICT,
+                "The simulator is using the system clock; start the server with SIM_CLOCK=manual".to_string(),
+            ),
+            Clock::Manual(_) => (StatusCode::BAD_REQUEST, "The simulator clock cannot move that far".to_string()),
+        };
+        let response = ApiResponse::<SimulatorClockResponse> {
+            success: false,
+            data: None,
+            message,
+        };
+        return (status, Json(response));
+    };
+    minting_queue.process_pending().await;
 
-fn generate_collection_performance() -> Vec<CollectionPerformance> {
-    (1..16).map(|i| CollectionPerformance {
-        collection_id: format!("col_{}", i),
-        collection_name: format!("Collection {}", i),
-        nft_count: (i * 50) + (i % 30),
-        unique_owners: (i * 20) + (i % 15),
-        avg_price: Some((i as f64 * 0.1) + 0.5),
-    }).collect()
+    let response = ApiResponse {
+        success: true,
+        data: Some(SimulatorClockResponse { now }),
+        message: "Simulator clock advanced".to_string(),
+    };
+    (StatusCode::OK, Json(response))
 }
 
-fn generate_user_activity() -> Vec<UserActivity> {
-    (1..21).map(|i| UserActivity {
-        user_id: format!("user_{}", i),
-        wallet_address: format!("0x{:040x}", i),
-        nft_count: (i % 15) + 1,
-        last_mint: Some(Utc::now() - Duration::hours((i % 48) as i64)),
-    }).collect()
-}
+/// Orphan the top blocks of the simulated chain, rolling back the mints they included
+pub async fn reorg_simulator_handler(
+    State(simulator): State<SharedSimulator>,
+    State(minting_queue): State<MintingQueue>,
+    Json(payload): Json<ReorgRequest>,
+) -> impl IntoResponse {
+    if payload.depth == 0 {
+        let response = ApiResponse::<ReorgResponse> {
+            success: false,
+            data: None,
+            message: "Reorg depth must be at least 1".to_string(),
+        };
+        return (StatusCode::BAD_REQUEST, Json(response));
+    }
+
+    let orphaned_blocks = simulator.reorg(payload.depth);
+    // Replacement blocks are already due, so the rollback and the new blocks are applied straight away
+    minting_queue.process_pending().await;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaginatedResponse<T> {
-    pub data: Vec<T>,
-    pub total: u64,
-    pub page: u64,
-    pub limit: u64,
+    let response = ApiResponse {
+        success: true,
+        data: Some(ReorgResponse { orphaned_blocks, head_block_number: simulator.head_block_number() }),
+        message: format!("Orphaned {} blocks", orphaned_blocks),
+    };
+    (StatusCode::OK, Json(response))
 }
 
-use serde::Serialize; 
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use crate::blockchain_sim::{BlockchainSimulator, SimulatorConfig};
+
+    const START: u64 = 1_700_000_000;
+
+    #[tokio::test]
+    async fn test_advance_clock_rejects_empty_and_oversized_steps() {
+        let pool: DbPool = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let simulator = Arc::new(BlockchainSimulator::new(SimulatorConfig::default(), Clock::manual(START)));
+        let minting_queue = MintingQueue::new(pool, simulator.clone());
+
+        for seconds in [0, MAX_CLOCK_ADVANCE_SECS + 1, u64::MAX] {
+            let response = advance_simulator_clock_handler(
+                State(simulator.clone()),
+                State(minting_queue.clone()),
+                Json(AdvanceClockRequest { seconds }),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+        assert_eq!(simulator.current_timestamp(), START);
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use crate::auth::roles::Role;
+use crate::wei::Wei;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AdminStats {
@@ -16,7 +18,7 @@ pub struct AdminStats {
 pub struct MintingTrend {
     pub date: String,
     pub count: u64,
-    pub volume: f64,
+    pub volume: Wei,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,7 +26,7 @@ pub struct PopularCollection {
     pub id: String,
     pub name: String,
     pub nft_count: u64,
-    pub total_volume: f64,
+    pub total_volume: Wei,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -75,7 +77,7 @@ pub struct CollectionPerformance {
     pub collection_name: String,
     pub nft_count: u64,
     pub unique_owners: u64,
-    pub avg_price: Option<f64>,
+    pub avg_price: Option<Wei>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -94,4 +96,40 @@ pub struct SetFeaturedRequest {
 #[derive(Debug, Deserialize)]
 pub struct DemoResetRequest {
     pub reset_type: String,
-} 
\ No newline at end of file
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdvanceClockRequest {
+    pub seconds: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulatorClockResponse {
+    pub now: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReorgRequest {
+    /// Blocks to orphan from the top of the chain
+    pub depth: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReorgResponse {
+    /// Fewer than requested when the chain holds fewer recent blocks
+    pub orphaned_blocks: usize,
+    /// Head once the orphaned slots were produced again
+    pub head_block_number: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRoleResponse {
+    pub id: String,
+    pub public_key: String,
+    pub role: Role,
+}
@@ -0,0 +1,36 @@
+// auth/config.rs
+
+use crate::env::env_or;
+
+/// Settings for Sign-In With Ethereum and session issuance
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    /// Domain (host[:port]) SIWE messages must be addressed to
+    pub siwe_domain: String,
+    pub chain_id: u64,
+    pub nonce_ttl_secs: i64,
+    pub session_ttl_secs: i64,
+    /// Lowercased wallets that are granted the admin role when they sign in
+    pub admin_wallets: Vec<String>,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        Self {
+            siwe_domain: std::env::var("SIWE_DOMAIN").unwrap_or_else(|_| "localhost:3000".to_string()),
+            chain_id: env_or("CHAIN_ID", 1337),
+            nonce_ttl_secs: env_or("SIWE_NONCE_TTL_SECS", 300),
+            session_ttl_secs: env_or("SESSION_TTL_SECS", 86_400),
+            admin_wallets: std::env::var("ADMIN_WALLETS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|wallet| wallet.trim().to_lowercase())
+                .filter(|wallet| !wallet.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_admin_wallet(&self, wallet_address: &str) -> bool {
+        self.admin_wallets.contains(&wallet_address.to_lowercase())
+    }
+}
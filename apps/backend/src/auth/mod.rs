@@ -1,8 +1,12 @@
+pub mod config;
+pub mod roles;
+pub mod session;
 pub mod signup;
+pub mod siwe;
 pub mod types;
 pub mod user;
 
 // Re-export the signup function so it's easier to use
 pub use signup::signup_handler;
-
+pub use session::AuthSession;
 
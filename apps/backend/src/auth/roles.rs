@@ -0,0 +1,81 @@
+// auth/roles.rs
+
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use super::session::{AuthRejection, AuthSession};
+
+/// Roles stored in `users.role`, ordered from least to most privileged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(Role::User),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            other => Err(anyhow::anyhow!("Unknown role: {}", other)),
+        }
+    }
+}
+
+impl AuthSession {
+    /// Unknown role strings are treated as the least privileged role
+    pub fn role(&self) -> Role {
+        self.user.role.parse().unwrap_or(Role::User)
+    }
+
+    pub fn require_role(&self, minimum: Role) -> Result<(), AuthRejection> {
+        if self.role() >= minimum {
+            Ok(())
+        } else {
+            Err(AuthRejection::forbidden(format!("This action requires the {} role", minimum.as_str())))
+        }
+    }
+}
+
+/// Middleware for routes open to moderators and admins
+pub async fn require_moderator(session: AuthSession, request: Request, next: Next) -> Result<Response, AuthRejection> {
+    session.require_role(Role::Moderator)?;
+    Ok(next.run(request).await)
+}
+
+/// Middleware for routes open to admins only
+pub async fn require_admin(session: AuthSession, request: Request, next: Next) -> Result<Response, AuthRejection> {
+    session.require_role(Role::Admin)?;
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_ordering() {
+        assert!(Role::Admin > Role::Moderator);
+        assert!(Role::Moderator > Role::User);
+        assert_eq!("moderator".parse::<Role>().unwrap(), Role::Moderator);
+        assert!("root".parse::<Role>().is_err());
+    }
+}
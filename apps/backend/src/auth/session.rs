@@ -0,0 +1,279 @@
+// auth/session.rs
+
+use axum::{
+    extract::{FromRef, FromRequestParts, State},
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use super::{config::AuthConfig, roles::Role, siwe::SiweMessage, types::ApiResponse};
+use crate::database::DbPool;
+use crate::db_operations::{
+    consume_nonce, create_nonce, create_session, create_user, delete_session, find_session_with_user,
+    find_user_by_public_key, update_user_role,
+};
+use crate::entities::UserModel;
+
+/// The signed-in wallet behind a request's `Authorization: Bearer` token
+#[derive(Debug, Clone)]
+pub struct AuthSession {
+    pub user: UserModel,
+    pub wallet_address: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    token_hash: String,
+}
+
+impl AuthSession {
+    /// Whether this session may act on behalf of `wallet_address`
+    pub fn owns_wallet(&self, wallet_address: &str) -> bool {
+        self.wallet_address.eq_ignore_ascii_case(wallet_address)
+    }
+
+    /// A session signed in with `user`'s own wallet, for handler tests
+    #[cfg(test)]
+    pub fn for_user(user: UserModel) -> Self {
+        AuthSession {
+            wallet_address: user.public_key.clone(),
+            expires_at: chrono::Utc::now() + chrono::Duration::hours(1),
+            token_hash: String::new(),
+            user,
+        }
+    }
+}
+
+pub struct AuthRejection {
+    status: StatusCode,
+    message: String,
+}
+
+impl AuthRejection {
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::UNAUTHORIZED, message: message.into() }
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self { status: StatusCode::FORBIDDEN, message: message.into() }
+    }
+}
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        let response = ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: self.message,
+        };
+        (self.status, Json(response)).into_response()
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(crate::crypto::keccak256(token.as_bytes()))
+}
+
+fn bearer_token(parts: &Parts) -> Option<&str> {
+    parts
+        .headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+impl<S> FromRequestParts<S> for AuthSession
+where
+    DbPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token = bearer_token(parts)
+            .ok_or_else(|| AuthRejection::unauthorized("Missing bearer token"))?;
+        let token_hash = hash_token(token);
+
+        let pool = DbPool::from_ref(state);
+        match find_session_with_user(&pool, &token_hash).await {
+            Ok(Some((session, user))) => Ok(AuthSession {
+                user,
+                wallet_address: session.wallet_address,
+                expires_at: chrono::DateTime::from_naive_utc_and_offset(session.expires_at, chrono::Utc),
+                token_hash,
+            }),
+            Ok(None) => Err(AuthRejection::unauthorized("Session is invalid or has expired")),
+            Err(e) => Err(AuthRejection {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                message: format!("Failed to load session: {}", e),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NonceReply {
+    pub nonce: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyRequest {
+    pub message: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionReply {
+    pub token: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub user_id: String,
+    pub wallet_address: String,
+}
+
+pub async fn nonce_handler(
+    State(pool): State<DbPool>,
+    State(config): State<AuthConfig>,
+) -> impl IntoResponse {
+    let nonce = cuid::cuid2();
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(config.nonce_ttl_secs);
+
+    match create_nonce(&pool, nonce.clone(), expires_at.naive_utc()).await {
+        Ok(_) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(NonceReply { nonce, expires_at }),
+                message: "Nonce issued".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => {
+            let response = ApiResponse::<NonceReply> {
+                success: false,
+                data: None,
+                message: format!("Failed to issue nonce: {}", e),
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        }
+    }
+}
+
+fn verify_error(status: StatusCode, message: String) -> (StatusCode, Json<ApiResponse<SessionReply>>) {
+    let response = ApiResponse::<SessionReply> {
+        success: false,
+        data: None,
+        message,
+    };
+    (status, Json(response))
+}
+
+pub async fn verify_handler(
+    State(pool): State<DbPool>,
+    State(config): State<AuthConfig>,
+    Json(payload): Json<VerifyRequest>,
+) -> impl IntoResponse {
+    let message: SiweMessage = match payload.message.parse() {
+        Ok(message) => message,
+        Err(e) => return verify_error(StatusCode::BAD_REQUEST, format!("Invalid SIWE message: {}", e)),
+    };
+
+    if let Err(e) = message.validate(&config, chrono::Utc::now()) {
+        return verify_error(StatusCode::UNAUTHORIZED, e.to_string());
+    }
+
+    let wallet_address = match message.verify_signature(&payload.message, &payload.signature) {
+        Ok(wallet_address) => wallet_address,
+        Err(e) => return verify_error(StatusCode::UNAUTHORIZED, format!("Invalid signature: {}", e)),
+    };
+
+    match consume_nonce(&pool, &message.nonce).await {
+        Ok(true) => {}
+        Ok(false) => return verify_error(StatusCode::UNAUTHORIZED, "Nonce is invalid, expired or already used".to_string()),
+        Err(e) => return verify_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to check nonce: {}", e)),
+    }
+
+    // The first sign-in registers the wallet
+    let user = match find_user_by_public_key(pool.as_ref(), &wallet_address).await {
+        Ok(Some(user)) => user,
+        Ok(None) => match create_user(pool.as_ref(), wallet_address.clone()).await {
+            Ok(user) => user,
+            Err(e) => return verify_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create user: {}", e)),
+        },
+        Err(e) => return verify_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to find user: {}", e)),
+    };
+
+    // Wallets listed in ADMIN_WALLETS are promoted on sign-in so the first admin can be bootstrapped
+    if config.is_admin_wallet(&wallet_address) && user.role != Role::Admin.as_str() {
+        if let Err(e) = update_user_role(&pool, &user.id, Role::Admin).await {
+            return verify_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to promote admin: {}", e));
+        }
+    }
+
+    let mut token_bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut token_bytes);
+    let token = hex::encode(token_bytes);
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(config.session_ttl_secs);
+
+    match create_session(&pool, hash_token(&token), user.id.clone(), wallet_address.clone(), expires_at.naive_utc()).await {
+        Ok(_) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(SessionReply {
+                    token,
+                    expires_at,
+                    user_id: user.id,
+                    wallet_address,
+                }),
+                message: "Signed in successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => verify_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create session: {}", e)),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    pub user_id: String,
+    pub wallet_address: String,
+    pub role: Role,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn current_session_handler(session: AuthSession) -> impl IntoResponse {
+    let response = ApiResponse {
+        success: true,
+        data: Some(SessionInfo {
+            role: session.role(),
+            user_id: session.user.id,
+            wallet_address: session.wallet_address,
+            expires_at: session.expires_at,
+        }),
+        message: "Session retrieved successfully".to_string(),
+    };
+    (StatusCode::OK, Json(response))
+}
+
+pub async fn logout_handler(
+    State(pool): State<DbPool>,
+    session: AuthSession,
+) -> impl IntoResponse {
+    match delete_session(&pool, &session.token_hash).await {
+        Ok(()) => {
+            let response = ApiResponse::<()> {
+                success: true,
+                data: None,
+                message: "Signed out successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => {
+            let response = ApiResponse::<()> {
+                success: false,
+                data: None,
+                message: format!("Failed to sign out: {}", e),
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        }
+    }
+}
@@ -1,7 +1,7 @@
 // auth/signup.rs
 
 use axum::{Json, response::IntoResponse, extract::State, http::StatusCode};
-use super::types::ApiResponse;
+use super::{session::AuthSession, types::ApiResponse};
 use crate::database::DbPool;
 use crate::db_operations::{create_user, find_user_by_public_key};
 use serde::{Deserialize, Serialize};
@@ -20,10 +20,21 @@ pub struct SignupReply {
 
 pub async fn signup_handler(
     State(pool): State<DbPool>,
+    session: AuthSession,
     Json(payload): Json<SignupRequest>,
 ) -> impl IntoResponse {
+    // Only the wallet that signed in may register itself
+    if !session.owns_wallet(&payload.wallet_address) {
+        let response = ApiResponse::<SignupReply> {
+            success: false,
+            data: None,
+            message: "Wallet address does not match the signed-in wallet".to_string(),
+        };
+        return (StatusCode::FORBIDDEN, Json(response));
+    }
+
     // Check if user already exists
-    match find_user_by_public_key(&pool, &payload.wallet_address).await {
+    match find_user_by_public_key(pool.as_ref(), &payload.wallet_address).await {
         Ok(Some(existing_user)) => {
             let response = ApiResponse {
                 success: true,
@@ -38,7 +49,7 @@ pub async fn signup_handler(
         }
         Ok(None) => {
             // Create new user
-            match create_user(&pool, payload.wallet_address.clone()).await {
+            match create_user(pool.as_ref(), payload.wallet_address.clone()).await {
                 Ok(new_user) => {
                     let response = ApiResponse {
                         success: true,
@@ -0,0 +1,236 @@
+// auth/siwe.rs
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use crate::auth::config::AuthConfig;
+use crate::crypto::{parse_address, recover_personal_sign_address, to_checksum_address};
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Ethereum account:";
+
+/// A parsed EIP-4361 (Sign-In With Ethereum) message
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiweMessage {
+    pub scheme: Option<String>,
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+fn parse_timestamp(field: &str, value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|e| anyhow!("Invalid {}: {}", field, e))
+}
+
+impl std::str::FromStr for SiweMessage {
+    type Err = anyhow::Error;
+
+    fn from_str(message: &str) -> Result<Self> {
+        let mut lines = message.split('\n').peekable();
+
+        let header = lines.next().ok_or_else(|| anyhow!("Message is empty"))?;
+        let origin = header
+            .strip_suffix(HEADER_SUFFIX)
+            .ok_or_else(|| anyhow!("Missing sign-in preamble"))?;
+        let (scheme, domain) = match origin.split_once("://") {
+            Some((scheme, domain)) => (Some(scheme.to_string()), domain.to_string()),
+            None => (None, origin.to_string()),
+        };
+
+        let address = lines.next().ok_or_else(|| anyhow!("Missing address"))?.to_string();
+        parse_address(&address)?;
+
+        // The statement is optional and surrounded by blank lines
+        if lines.next() != Some("") {
+            bail!("Expected a blank line after the address");
+        }
+        let statement = match lines.peek() {
+            Some(line) if !line.is_empty() && !line.starts_with("URI: ") => {
+                let statement = line.to_string();
+                lines.next();
+                Some(statement)
+            }
+            _ => None,
+        };
+        if lines.peek() == Some(&"") {
+            lines.next();
+        }
+
+        let mut uri = None;
+        let mut version = None;
+        let mut chain_id = None;
+        let mut nonce = None;
+        let mut issued_at = None;
+        let mut expiration_time = None;
+        let mut not_before = None;
+        let mut request_id = None;
+        let mut resources = Vec::new();
+
+        while let Some(line) = lines.next() {
+            if line == "Resources:" {
+                for resource in lines.by_ref() {
+                    let resource = resource
+                        .strip_prefix("- ")
+                        .ok_or_else(|| anyhow!("Invalid resource line: {}", resource))?;
+                    resources.push(resource.to_string());
+                }
+                break;
+            }
+
+            let (key, value) = line
+                .split_once(": ")
+                .ok_or_else(|| anyhow!("Invalid field line: {}", line))?;
+            match key {
+                "URI" => uri = Some(value.to_string()),
+                "Version" => version = Some(value.to_string()),
+                "Chain ID" => chain_id = Some(value.parse::<u64>().map_err(|e| anyhow!("Invalid Chain ID: {}", e))?),
+                "Nonce" => nonce = Some(value.to_string()),
+                "Issued At" => issued_at = Some(parse_timestamp("Issued At", value)?),
+                "Expiration Time" => expiration_time = Some(parse_timestamp("Expiration Time", value)?),
+                "Not Before" => not_before = Some(parse_timestamp("Not Before", value)?),
+                "Request ID" => request_id = Some(value.to_string()),
+                other => bail!("Unknown field: {}", other),
+            }
+        }
+
+        let nonce = nonce.ok_or_else(|| anyhow!("Missing Nonce"))?;
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            bail!("Nonce must be at least 8 alphanumeric characters");
+        }
+
+        Ok(SiweMessage {
+            scheme,
+            domain,
+            address,
+            statement,
+            uri: uri.ok_or_else(|| anyhow!("Missing URI"))?,
+            version: version.ok_or_else(|| anyhow!("Missing Version"))?,
+            chain_id: chain_id.ok_or_else(|| anyhow!("Missing Chain ID"))?,
+            nonce,
+            issued_at: issued_at.ok_or_else(|| anyhow!("Missing Issued At"))?,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+}
+
+impl SiweMessage {
+    /// Check the message targets this server and is valid at `now`
+    pub fn validate(&self, config: &AuthConfig, now: DateTime<Utc>) -> Result<()> {
+        if self.domain != config.siwe_domain {
+            bail!("Message domain {} does not match {}", self.domain, config.siwe_domain);
+        }
+        if self.version != "1" {
+            bail!("Unsupported SIWE version {}", self.version);
+        }
+        if self.chain_id != config.chain_id {
+            bail!("Message chain ID {} does not match {}", self.chain_id, config.chain_id);
+        }
+        if let Some(expiration_time) = self.expiration_time {
+            if expiration_time <= now {
+                bail!("Message has expired");
+            }
+        }
+        if let Some(not_before) = self.not_before {
+            if not_before > now {
+                bail!("Message is not valid yet");
+            }
+        }
+        Ok(())
+    }
+
+    /// Recover the signer of `raw_message` and return its checksummed address if it is ours
+    pub fn verify_signature(&self, raw_message: &str, signature: &str) -> Result<String> {
+        let signer = recover_personal_sign_address(raw_message, signature)?;
+        let claimed = parse_address(&self.address)?;
+        if signer != claimed {
+            bail!("Signature was not produced by {}", self.address);
+        }
+        Ok(to_checksum_address(&signer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{eip191_hash, public_key_to_address};
+    use k256::ecdsa::SigningKey;
+
+    fn config() -> AuthConfig {
+        AuthConfig {
+            siwe_domain: "localhost:3000".to_string(),
+            chain_id: 1337,
+            nonce_ttl_secs: 300,
+            session_ttl_secs: 3600,
+            admin_wallets: Vec::new(),
+        }
+    }
+
+    fn message(address: &str, statement: Option<&str>) -> String {
+        let statement = statement.map(|s| format!("{}\n", s)).unwrap_or_default();
+        format!(
+            "localhost:3000 wants you to sign in with your Ethereum account:\n{}\n\n{}\nURI: http://localhost:3000\nVersion: 1\nChain ID: 1337\nNonce: abcdef123456\nIssued At: 2026-10-18T00:00:00Z\nExpiration Time: 2026-10-19T00:00:00Z\nResources:\n- https://example.com/terms",
+            address, statement,
+        )
+    }
+
+    fn sign(signing_key: &SigningKey, message: &str) -> String {
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&eip191_hash(message)).unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let parsed: SiweMessage = message(address, Some("Sign in to Mintverse")).parse().unwrap();
+        assert_eq!(parsed.domain, "localhost:3000");
+        assert_eq!(parsed.address, address);
+        assert_eq!(parsed.statement.as_deref(), Some("Sign in to Mintverse"));
+        assert_eq!(parsed.chain_id, 1337);
+        assert_eq!(parsed.nonce, "abcdef123456");
+        assert_eq!(parsed.resources, vec!["https://example.com/terms".to_string()]);
+
+        let without_statement: SiweMessage = message(address, None).parse().unwrap();
+        assert_eq!(without_statement.statement, None);
+        assert_eq!(without_statement.uri, "http://localhost:3000");
+    }
+
+    #[test]
+    fn test_validate_message() {
+        let parsed: SiweMessage = message("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", None).parse().unwrap();
+        let now = parse_timestamp("now", "2026-10-18T12:00:00Z").unwrap();
+        assert!(parsed.validate(&config(), now).is_ok());
+
+        let later = parse_timestamp("later", "2026-10-20T00:00:00Z").unwrap();
+        assert!(parsed.validate(&config(), later).is_err());
+
+        let other_domain = AuthConfig { siwe_domain: "evil.example".to_string(), ..config() };
+        assert!(parsed.validate(&other_domain, now).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let signing_key = SigningKey::from_slice(&[42u8; 32]).unwrap();
+        let address = to_checksum_address(&public_key_to_address(signing_key.verifying_key()));
+        let raw = message(&address, Some("Sign in to Mintverse"));
+        let parsed: SiweMessage = raw.parse().unwrap();
+
+        assert_eq!(parsed.verify_signature(&raw, &sign(&signing_key, &raw)).unwrap(), address);
+
+        let impostor = SigningKey::from_slice(&[43u8; 32]).unwrap();
+        assert!(parsed.verify_signature(&raw, &sign(&impostor, &raw)).is_err());
+    }
+}
@@ -0,0 +1,549 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::crypto::keccak256;
+use crate::wei::Wei;
+use super::failure::SimulatedFailure;
+use super::gas::{effective_gas_price, min_replacement_fee, next_base_fee, GasEstimate, BLOCK_GAS_TARGET, FEE_ESTIMATE_BLOCKS, INITIAL_BASE_FEE};
+
+/// Seconds between simulated blocks, matching mainnet's slot time
+pub const DEFAULT_BLOCK_TIME: u64 = 12;
+/// Gas available to each simulated block
+pub const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+/// Block the simulated chain starts from when nothing has been persisted yet
+pub const GENESIS_BLOCK: u64 = 19_000_000;
+/// Blocks kept in memory for fee estimates and block lookups
+pub const RECENT_BLOCKS: usize = 256;
+
+/// Deterministic hash standing in for the simulated block's header hash.
+/// Each reorg starts a new fork, so a replacement block never shares the hash of the block it replaced.
+pub fn block_hash(number: u64, fork: u64) -> String {
+    let preimage = match fork {
+        0 => format!("mintverse:block:{}", number),
+        fork => format!("mintverse:block:{}:fork:{}", number, fork),
+    };
+    format!("0x{}", hex::encode(keccak256(preimage.as_bytes())))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub number: u64,
+    pub hash: String,
+    pub timestamp: u64,
+    /// Gas used by the included transactions and by simulated background traffic
+    pub gas_used: u64,
+    pub base_fee_per_gas: Wei,
+    /// Included transactions, in inclusion order
+    pub transactions: Vec<IncludedTransaction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludedTransaction {
+    pub hash: String,
+    pub gas_used: u64,
+    /// Price per unit of gas actually paid: the block's base fee plus the priority fee, up to the max fee
+    pub effective_gas_price: Wei,
+    /// Set when the transaction was mined but failed
+    pub revert_reason: Option<String>,
+}
+
+/// What producing blocks changed: the new blocks, the blocks a reorg took off the chain
+/// and the transactions dropped from the mempool
+#[derive(Debug, Default, PartialEq)]
+pub struct ChainUpdate {
+    pub blocks: Vec<Block>,
+    /// Blocks that are no longer part of the chain; their transactions are pending again
+    pub orphaned: Vec<Block>,
+    pub dropped: Vec<String>,
+}
+
+impl ChainUpdate {
+    /// Append an update that happened after this one. A block produced by this update
+    /// and orphaned by the later one was never seen, so it is left out of both.
+    pub fn extend(&mut self, later: ChainUpdate) {
+        self.blocks.extend(later.blocks);
+        let unseen: HashSet<String> = later
+            .orphaned
+            .iter()
+            .filter(|orphan| self.blocks.iter().any(|block| block.hash == orphan.hash))
+            .map(|orphan| orphan.hash.clone())
+            .collect();
+        self.blocks.retain(|block| !unseen.contains(&block.hash));
+        self.orphaned.extend(later.orphaned.into_iter().filter(|orphan| !unseen.contains(&orphan.hash)));
+        self.dropped.extend(later.dropped);
+    }
+}
+
+/// Sender of a transaction and its place in the sender's sequence of transactions
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AccountNonce {
+    /// Lowercase, so checksummed and plain addresses share one sequence
+    pub address: String,
+    pub nonce: u64,
+}
+
+impl AccountNonce {
+    pub fn new(address: &str, nonce: u64) -> Self {
+        Self { address: address.to_lowercase(), nonce }
+    }
+}
+
+/// Why a pending transaction could not be replaced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplacementError {
+    /// Nothing from that sender with that nonce is waiting in the mempool; it was mined, dropped or never sent
+    NotPending,
+    /// Both fee caps must rise by at least the minimum bump nodes require
+    Underpriced {
+        min_max_fee_per_gas: Wei,
+        min_max_priority_fee_per_gas: Wei,
+    },
+}
+
+impl std::fmt::Display for ReplacementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplacementError::NotPending => write!(f, "Transaction is no longer pending"),
+            ReplacementError::Underpriced { min_max_fee_per_gas, min_max_priority_fee_per_gas } => write!(
+                f,
+                "Replacement transaction underpriced: needs a max fee of at least {} gwei and a priority fee of at least {} gwei",
+                min_max_fee_per_gas.to_gwei_string(),
+                min_max_priority_fee_per_gas.to_gwei_string()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplacementError {}
+
+/// A transaction waiting in the mempool
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingTransaction {
+    pub hash: String,
+    pub gas_used: u64,
+    pub max_fee_per_gas: Wei,
+    pub max_priority_fee_per_gas: Wei,
+    pub failure: Option<SimulatedFailure>,
+    pub submitted_at: u64,
+    /// Lowest block number the transaction may be included in
+    pub earliest_block: u64,
+    /// `None` for transactions sent before nonces were simulated, which are included in arrival order
+    pub sender: Option<AccountNonce>,
+}
+
+/// A single simulated chain: a head block, the blocks before it and a FIFO mempool.
+///
+/// Blocks are produced every `block_time` seconds; each one takes pending transactions
+/// in submission order until its gas limit is reached, skipping any whose max fee is below
+/// its base fee. Background traffic then fills part of what is left, which moves the base fee.
+/// Transactions destined to be dropped are never included and leave the mempool
+/// `drop_timeout` seconds after submission.
+///
+/// Each account's transactions are included in nonce order. One waiting behind a nonce that
+/// never gets mined is stuck, and is dropped once the drop timeout passes.
+///
+/// A reorg orphans blocks from the top of the chain and moves the head back to their parent;
+/// the orphaned slots are produced again on a new fork, and their transactions return to the
+/// front of the mempool to be included in a later block.
+#[derive(Debug)]
+pub struct ChainState {
+    block_time: u64,
+    drop_timeout: u64,
+    /// The most recent blocks, ending with the head
+    recent: VecDeque<Block>,
+    mempool: VecDeque<PendingTransaction>,
+    /// Transactions included in `recent`, kept so a reorg can put them back in the mempool
+    mined: HashMap<String, PendingTransaction>,
+    /// Blocks orphaned since the last update
+    orphaned: Vec<Block>,
+    /// Number of reorgs so far
+    fork: u64,
+    /// Nonce each account's next included transaction must carry
+    account_nonces: HashMap<String, u64>,
+}
+
+impl ChainState {
+    pub fn new(head_number: u64, timestamp: u64, block_time: u64, drop_timeout: u64) -> Self {
+        let head = Block {
+            number: head_number,
+            hash: block_hash(head_number, 0),
+            timestamp,
+            // At the target, so the first new block keeps the initial base fee
+            gas_used: BLOCK_GAS_TARGET,
+            base_fee_per_gas: INITIAL_BASE_FEE,
+            transactions: Vec::new(),
+        };
+        Self {
+            block_time: block_time.max(1),
+            drop_timeout,
+            recent: VecDeque::from([head]),
+            mempool: VecDeque::new(),
+            mined: HashMap::new(),
+            orphaned: Vec::new(),
+            fork: 0,
+            account_nonces: HashMap::new(),
+        }
+    }
+
+    pub fn head(&self) -> &Block {
+        self.recent.back().expect("the chain always has a head block")
+    }
+
+    /// A block still held in memory; older blocks were only persisted through their transactions
+    pub fn block(&self, number: u64) -> Option<&Block> {
+        let oldest = self.recent.front()?.number;
+        self.recent.get(number.checked_sub(oldest)? as usize)
+    }
+
+    /// Suggested fees from the priority fees paid in recent blocks
+    pub fn gas_estimate(&self) -> GasEstimate {
+        let skip = self.recent.len().saturating_sub(FEE_ESTIMATE_BLOCKS);
+        let recent: Vec<Block> = self.recent.iter().skip(skip).cloned().collect();
+        GasEstimate::from_blocks(&recent)
+    }
+
+    /// Queue a transaction for the next block with room for it
+    pub fn submit(&mut self, tx: PendingTransaction) {
+        self.mempool.push_back(tx);
+    }
+
+    /// Nonce of the next transaction from `address` the chain will include
+    pub fn account_nonce(&self, address: &str) -> u64 {
+        self.account_nonces.get(&address.to_lowercase()).copied().unwrap_or(0)
+    }
+
+    /// Nonce for a new transaction from `address`: the one after any it has waiting in the mempool
+    pub fn pending_nonce(&self, address: &str) -> u64 {
+        let address = address.to_lowercase();
+        self.mempool
+            .iter()
+            .filter_map(|tx| tx.sender.as_ref())
+            .filter(|sender| sender.address == address)
+            .map(|sender| sender.nonce + 1)
+            .fold(self.account_nonce(&address), u64::max)
+    }
+
+    /// Continue an account's nonces from what was mined before a restart
+    pub fn set_account_nonce(&mut self, address: &str, nonce: u64) {
+        self.account_nonces.insert(address.to_lowercase(), nonce);
+    }
+
+    /// Swap a waiting transaction for one from the same sender with the same nonce. The
+    /// replacement must raise both fee caps by the minimum bump, and takes the original's place
+    /// in the mempool. Returns the transaction it replaced.
+    pub fn replace(&mut self, replacement: PendingTransaction) -> Result<PendingTransaction, ReplacementError> {
+        let position = self
+            .mempool
+            .iter()
+            .position(|tx| tx.sender.is_some() && tx.sender == replacement.sender)
+            .ok_or(ReplacementError::NotPending)?;
+
+        let original = &self.mempool[position];
+        let min_max_fee_per_gas = min_replacement_fee(original.max_fee_per_gas);
+        let min_max_priority_fee_per_gas = min_replacement_fee(original.max_priority_fee_per_gas);
+        if replacement.max_fee_per_gas < min_max_fee_per_gas || replacement.max_priority_fee_per_gas < min_max_priority_fee_per_gas {
+            return Err(ReplacementError::Underpriced { min_max_fee_per_gas, min_max_priority_fee_per_gas });
+        }
+
+        let replacement = PendingTransaction { earliest_block: original.earliest_block, ..replacement };
+        Ok(std::mem::replace(&mut self.mempool[position], replacement))
+    }
+
+    /// Whether the account sending `tx` has other transactions to include first
+    fn nonce_too_high(&self, tx: &PendingTransaction) -> bool {
+        tx.sender.as_ref().is_some_and(|sender| sender.nonce != self.account_nonce(&sender.address))
+    }
+
+    /// Produce every block due by `now`, oldest first, then drop transactions that timed out.
+    /// `background_gas` draws the gas other traffic would like to use in each block.
+    pub fn advance_to(&mut self, now: u64, mut background_gas: impl FnMut() -> u64) -> ChainUpdate {
+        let mut produced = Vec::new();
+
+        while self.head().timestamp + self.block_time <= now {
+            let head = self.head();
+            let number = head.number + 1;
+            let mut block = Block {
+                number,
+                hash: block_hash(number, self.fork),
+                timestamp: head.timestamp + self.block_time,
+                gas_used: 0,
+                base_fee_per_gas: next_base_fee(head),
+                transactions: Vec::new(),
+            };
+
+            // Transactions that will be dropped, can't pay the base fee, are held back by a reorg
+            // or wait on an earlier nonce sit this block out
+            let mut skipped = VecDeque::new();
+            while let Some(tx) = self.mempool.front() {
+                if tx.failure == Some(SimulatedFailure::Dropped)
+                    || tx.max_fee_per_gas < block.base_fee_per_gas
+                    || tx.earliest_block > block.number
+                    || self.nonce_too_high(tx)
+                {
+                    skipped.push_back(self.mempool.pop_front().unwrap());
+                    continue;
+                }
+                // A transaction larger than a whole block still gets a block to itself
+                if block.gas_used + tx.gas_used > BLOCK_GAS_LIMIT && !block.transactions.is_empty() {
+                    break;
+                }
+                let tx = self.mempool.pop_front().unwrap();
+                block.gas_used += tx.gas_used;
+                block.transactions.push(IncludedTransaction {
+                    hash: tx.hash.clone(),
+                    gas_used: tx.gas_used,
+                    effective_gas_price: effective_gas_price(block.base_fee_per_gas, tx.max_fee_per_gas, tx.max_priority_fee_per_gas),
+                    revert_reason: tx.failure.and_then(|failure| failure.revert_reason()).map(str::to_string),
+                });
+                if let Some(ref sender) = tx.sender {
+                    self.account_nonces.insert(sender.address.clone(), sender.nonce + 1);
+                }
+                self.mined.insert(tx.hash.clone(), tx);
+            }
+            skipped.append(&mut self.mempool);
+            self.mempool = skipped;
+            block.gas_used += background_gas().min(BLOCK_GAS_LIMIT.saturating_sub(block.gas_used));
+
+            self.recent.push_back(block.clone());
+            if self.recent.len() > RECENT_BLOCKS {
+                if let Some(oldest) = self.recent.pop_front() {
+                    for tx in &oldest.transactions {
+                        self.mined.remove(&tx.hash);
+                    }
+                }
+            }
+            produced.push(block);
+        }
+
+        // A transaction is stuck when the nonce its account needs next is no longer waiting anywhere
+        let waiting: HashSet<AccountNonce> = self.mempool.iter().filter_map(|tx| tx.sender.clone()).collect();
+        let stuck: HashSet<String> = self
+            .mempool
+            .iter()
+            .filter(|tx| {
+                tx.sender.as_ref().is_some_and(|sender| {
+                    let next = AccountNonce { address: sender.address.clone(), nonce: self.account_nonce(&sender.address) };
+                    sender.nonce > next.nonce && !waiting.contains(&next)
+                })
+            })
+            .map(|tx| tx.hash.clone())
+            .collect();
+
+        let mut dropped = Vec::new();
+        self.mempool.retain(|tx| {
+            let doomed = tx.failure == Some(SimulatedFailure::Dropped) || stuck.contains(&tx.hash);
+            let expired = tx.submitted_at + self.drop_timeout <= now && doomed;
+            if expired {
+                dropped.push(tx.hash.clone());
+            }
+            !expired
+        });
+
+        ChainUpdate { blocks: produced, orphaned: std::mem::take(&mut self.orphaned), dropped }
+    }
+
+    /// Orphan the top `depth` blocks, never going back past the oldest block held in memory.
+    /// Their transactions wait for a block after the new head's first replacement, so each one
+    /// is included in a different block than before. Returns the number of blocks orphaned.
+    pub fn reorg(&mut self, depth: u64) -> usize {
+        let depth = (depth as usize).min(self.recent.len() - 1);
+        if depth == 0 {
+            return 0;
+        }
+
+        let orphaned = self.recent.split_off(self.recent.len() - depth);
+        let earliest_block = self.head().number + 2;
+        let mut requeued: VecDeque<PendingTransaction> = orphaned
+            .iter()
+            .flat_map(|block| &block.transactions)
+            .filter_map(|tx| self.mined.remove(&tx.hash))
+            .map(|tx| PendingTransaction { earliest_block, ..tx })
+            .collect();
+        // Their accounts go back to the lowest nonce that is no longer mined
+        for sender in requeued.iter().filter_map(|tx| tx.sender.as_ref()) {
+            let next = self.account_nonces.entry(sender.address.clone()).or_default();
+            *next = (*next).min(sender.nonce);
+        }
+        requeued.append(&mut self.mempool);
+        self.mempool = requeued;
+
+        self.fork += 1;
+        self.orphaned.extend(orphaned);
+        depth
+    }
+
+    /// Timestamp of a block up to the head, assuming no slot since then was missed
+    pub fn block_timestamp(&self, number: u64) -> Option<u64> {
+        let head = self.head();
+        let behind = head.number.checked_sub(number)?;
+        Some(head.timestamp.saturating_sub(behind * self.block_time))
+    }
+
+    /// Blocks built on top of `block_number`, counting from the current head
+    pub fn confirmations(&self, block_number: u64) -> u32 {
+        self.head().number.saturating_sub(block_number) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain_sim::failure::REVERT_REASON;
+
+    #[test]
+    fn test_blocks_follow_interval() {
+        let mut chain = ChainState::new(100, 1_000, 12, 120);
+        assert!(chain.advance_to(1_011, idle).blocks.is_empty());
+
+        let blocks = chain.advance_to(1_036, idle).blocks;
+        assert_eq!(blocks.iter().map(|b| (b.number, b.timestamp)).collect::<Vec<_>>(), vec![(101, 1_012), (102, 1_024), (103, 1_036)]);
+        assert_eq!(chain.head().number, 103);
+        assert_eq!(chain.confirmations(101), 2);
+        assert_eq!(chain.block_timestamp(101), Some(1_012));
+        assert_eq!(chain.block_timestamp(104), None);
+        assert_eq!(chain.block(101).map(|b| b.timestamp), Some(1_012));
+        assert_eq!(chain.block(99), None);
+    }
+
+    #[test]
+    fn test_mempool_fills_blocks_in_order() {
+        let mut chain = ChainState::new(0, 0, 12, 120);
+        chain.submit(pending("a", 20_000_000, None));
+        chain.submit(pending("b", 20_000_000, None));
+        chain.submit(pending("c", 5_000_000, None));
+
+        let blocks = chain.advance_to(24, idle).blocks;
+        assert_eq!(hashes(&blocks[0]), vec!["a"]);
+        assert_eq!(blocks[0].gas_used, 20_000_000);
+        // "c" would fit in the first block but may not jump ahead of "b"
+        assert_eq!(hashes(&blocks[1]), vec!["b", "c"]);
+        assert_eq!(blocks[1].gas_used, 25_000_000);
+    }
+
+    #[test]
+    fn test_failed_transactions() {
+        let mut chain = ChainState::new(0, 0, 12, 30);
+        chain.submit(pending("lost", 100_000, Some(SimulatedFailure::Dropped)));
+        chain.submit(pending("reverted", 100_000, Some(SimulatedFailure::Revert)));
+        chain.submit(pending("ok", 100_000, None));
+
+        let update = chain.advance_to(24, idle);
+        assert!(update.dropped.is_empty());
+        assert_eq!(hashes(&update.blocks[0]), vec!["reverted", "ok"]);
+        assert_eq!(update.blocks[0].transactions[0].revert_reason.as_deref(), Some(REVERT_REASON));
+        assert_eq!(update.blocks[0].transactions[1].revert_reason, None);
+        assert!(update.blocks[1].transactions.is_empty());
+
+        // Dropped once the timeout passes, without ever being mined
+        chain.submit(PendingTransaction { submitted_at: 24, ..pending("later", 100_000, None) });
+        let update = chain.advance_to(36, idle);
+        assert_eq!(update.dropped, vec!["lost".to_string()]);
+        assert_eq!(hashes(&update.blocks[0]), vec!["later"]);
+    }
+
+    #[test]
+    fn test_base_fee_and_effective_price() {
+        let mut chain = ChainState::new(0, 0, 12, 120);
+        // Full blocks push the base fee up 12.5% at a time
+        let blocks = chain.advance_to(24, || BLOCK_GAS_LIMIT).blocks;
+        assert_eq!(blocks[0].base_fee_per_gas, INITIAL_BASE_FEE);
+        assert_eq!(blocks[1].base_fee_per_gas, (INITIAL_BASE_FEE / 8).saturating_mul(9));
+        assert_eq!(blocks[1].gas_used, BLOCK_GAS_LIMIT);
+
+        let base_fee = chain.gas_estimate().base_fee_per_gas;
+        chain.submit(PendingTransaction { max_fee_per_gas: base_fee.saturating_sub(Wei::new(1)), ..pending("cheap", 100_000, None) });
+        chain.submit(PendingTransaction { max_fee_per_gas: base_fee.saturating_add(Wei::new(1)), ..pending("capped", 100_000, None) });
+        let blocks = chain.advance_to(48, idle).blocks;
+        // The cheap transaction waits until empty blocks bring the base fee down to its max fee
+        assert_eq!(hashes(&blocks[0]), vec!["capped"]);
+        assert_eq!(blocks[0].transactions[0].effective_gas_price, base_fee.saturating_add(Wei::new(1)));
+        assert_eq!(hashes(&blocks[1]), vec!["cheap"]);
+        assert_eq!(blocks[1].transactions[0].effective_gas_price, blocks[1].base_fee_per_gas.saturating_add(Wei::from_gwei(1)));
+    }
+
+    #[test]
+    fn test_reorg_requeues_transactions() {
+        let mut chain = ChainState::new(0, 0, 12, 120);
+        chain.submit(pending("a", 100_000, None));
+        chain.submit(pending("b", 100_000, None));
+        let blocks = chain.advance_to(24, idle).blocks;
+        assert_eq!(hashes(&blocks[0]), vec!["a", "b"]);
+
+        assert_eq!(chain.reorg(2), 2);
+        assert_eq!(chain.head().number, 0);
+        // Never reorgs past the oldest block held in memory
+        assert_eq!(chain.reorg(5), 0);
+
+        chain.submit(pending("c", 100_000, None));
+        let mut update = chain.advance_to(36, idle);
+        assert_eq!(update.orphaned, blocks);
+        // The orphaned slots are produced again on a new fork; their transactions keep their order
+        assert_eq!(update.blocks.iter().map(|b| b.number).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_ne!(update.blocks[0].hash, blocks[0].hash);
+        assert_eq!(hashes(&update.blocks[0]), vec!["c"]);
+        assert_eq!(hashes(&update.blocks[1]), vec!["a", "b"]);
+
+        // A block produced and orphaned within one update is left out of it
+        chain.reorg(1);
+        update.extend(chain.advance_to(36, idle));
+        assert_eq!(update.blocks.iter().map(|b| b.number).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_ne!(update.blocks[2].hash, block_hash(3, 1));
+        assert_eq!(update.orphaned, blocks);
+    }
+
+    #[test]
+    fn test_account_nonces_order_and_replacement() {
+        let mut chain = ChainState::new(0, 0, 12, 60);
+        // Arrives first but has to wait for nonce 0
+        chain.submit(from("second", "0xAbC", 1));
+        chain.submit(from("first", "0xabc", 0));
+        chain.submit(from("gap", "0xdef", 1));
+        assert_eq!(chain.pending_nonce("0xABC"), 2);
+        assert_eq!((chain.account_nonce("0xdef"), chain.pending_nonce("0xdef")), (0, 2));
+
+        let blocks = chain.advance_to(24, idle).blocks;
+        assert_eq!(hashes(&blocks[0]), vec!["first"]);
+        assert_eq!(hashes(&blocks[1]), vec!["second"]);
+        assert_eq!(chain.account_nonce("0xabc"), 2);
+
+        // Replacements must bump both fees and keep the sender and nonce
+        chain.submit(from("slow", "0xabc", 2));
+        let underpriced = PendingTransaction { max_fee_per_gas: Wei::from_gwei(105), ..from("fast", "0xabc", 2) };
+        assert!(matches!(chain.replace(underpriced), Err(ReplacementError::Underpriced { min_max_fee_per_gas, .. }) if min_max_fee_per_gas == Wei::from_gwei(110)));
+        let fast = PendingTransaction { max_fee_per_gas: Wei::from_gwei(110), max_priority_fee_per_gas: Wei::new(1_100_000_000), ..from("fast", "0xabc", 2) };
+        assert_eq!(chain.replace(fast.clone()).unwrap().hash, "slow");
+        assert_eq!(chain.replace(from("unknown", "0xabc", 9)), Err(ReplacementError::NotPending));
+
+        // "gap" waits for a nonce that never arrives, so it is dropped at the timeout
+        let update = chain.advance_to(60, idle);
+        assert_eq!(hashes(&update.blocks[0]), vec!["fast"]);
+        assert_eq!(update.dropped, vec!["gap".to_string()]);
+
+        // A reorg hands the nonces of orphaned transactions back
+        chain.reorg(4);
+        assert_eq!(chain.account_nonce("0xabc"), 1);
+    }
+
+    fn idle() -> u64 {
+        0
+    }
+
+    fn pending(hash: &str, gas_used: u64, failure: Option<SimulatedFailure>) -> PendingTransaction {
+        PendingTransaction {
+            hash: hash.to_string(),
+            gas_used,
+            max_fee_per_gas: Wei::from_gwei(100),
+            max_priority_fee_per_gas: Wei::from_gwei(1),
+            failure,
+            submitted_at: 0,
+            earliest_block: 0,
+            sender: None,
+        }
+    }
+
+    fn from(hash: &str, address: &str, nonce: u64) -> PendingTransaction {
+        PendingTransaction { sender: Some(AccountNonce::new(address, nonce)), ..pending(hash, 100_000, None) }
+    }
+
+    fn hashes(block: &Block) -> Vec<&str> {
+        block.transactions.iter().map(|tx| tx.hash.as_str()).collect()
+    }
+}
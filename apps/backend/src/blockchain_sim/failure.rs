@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use crate::env::env_or;
+
+/// Seconds a transaction may wait in the mempool before it is dropped
+pub const DEFAULT_DROP_TIMEOUT: u64 = 120;
+/// Reason recorded for a simulated revert, as a node would report it
+pub const REVERT_REASON: &str = "execution reverted: ERC721: mint rejected by simulator";
+pub const OUT_OF_GAS_REASON: &str = "out of gas";
+
+/// A way a simulated transaction can fail
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum SimulatedFailure {
+    /// Mined, but the contract call reverts
+    Revert,
+    /// Mined, but runs out of gas
+    OutOfGas,
+    /// Never mined; removed from the mempool once the drop timeout passes
+    Dropped,
+}
+
+impl SimulatedFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SimulatedFailure::Revert => "revert",
+            SimulatedFailure::OutOfGas => "out-of-gas",
+            SimulatedFailure::Dropped => "dropped",
+        }
+    }
+
+    /// Reason a mined transaction failed with; dropped transactions are never mined
+    pub fn revert_reason(&self) -> Option<&'static str> {
+        match self {
+            SimulatedFailure::Revert => Some(REVERT_REASON),
+            SimulatedFailure::OutOfGas => Some(OUT_OF_GAS_REASON),
+            SimulatedFailure::Dropped => None,
+        }
+    }
+
+    /// Failure requested by a tag in an NFT name, e.g. `Broken ape [revert]`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        [SimulatedFailure::Revert, SimulatedFailure::OutOfGas, SimulatedFailure::Dropped]
+            .into_iter()
+            .find(|failure| name.contains(&format!("[{}]", failure.as_str())))
+    }
+}
+
+impl std::str::FromStr for SimulatedFailure {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "revert" => Ok(SimulatedFailure::Revert),
+            "out-of-gas" => Ok(SimulatedFailure::OutOfGas),
+            "dropped" => Ok(SimulatedFailure::Dropped),
+            other => Err(anyhow::anyhow!("Unknown simulated failure '{}'; expected revert, out-of-gas or dropped", other)),
+        }
+    }
+}
+
+/// Probability, between 0 and 1, of each failure happening to a submitted transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FailureRates {
+    pub revert: f64,
+    pub out_of_gas: f64,
+    pub dropped: f64,
+}
+
+impl FailureRates {
+    pub fn from_env() -> Self {
+        Self {
+            revert: env_or("SIM_REVERT_RATE", 0.0),
+            out_of_gas: env_or("SIM_OUT_OF_GAS_RATE", 0.0),
+            dropped: env_or("SIM_DROP_RATE", 0.0),
+        }
+    }
+
+    /// The failure a uniform roll in `[0, 1)` lands on, if any
+    pub fn pick(&self, roll: f64) -> Option<SimulatedFailure> {
+        let mut threshold = 0.0;
+        for (rate, failure) in [
+            (self.revert, SimulatedFailure::Revert),
+            (self.out_of_gas, SimulatedFailure::OutOfGas),
+            (self.dropped, SimulatedFailure::Dropped),
+        ] {
+            threshold += rate.max(0.0);
+            if roll < threshold {
+                return Some(failure);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_from_name_and_header() {
+        assert_eq!(SimulatedFailure::from_name("Broken Ape [REVERT]"), Some(SimulatedFailure::Revert));
+        assert_eq!(SimulatedFailure::from_name("[out-of-gas] #4"), Some(SimulatedFailure::OutOfGas));
+        assert_eq!(SimulatedFailure::from_name("Revert"), None);
+        assert_eq!(" Dropped".parse::<SimulatedFailure>().unwrap(), SimulatedFailure::Dropped);
+        assert!("explode".parse::<SimulatedFailure>().is_err());
+    }
+
+    #[test]
+    fn test_rates_partition_the_roll() {
+        let rates = FailureRates { revert: 0.1, out_of_gas: 0.2, dropped: 0.3 };
+        assert_eq!(rates.pick(0.05), Some(SimulatedFailure::Revert));
+        assert_eq!(rates.pick(0.25), Some(SimulatedFailure::OutOfGas));
+        assert_eq!(rates.pick(0.55), Some(SimulatedFailure::Dropped));
+        assert_eq!(rates.pick(0.65), None);
+        assert_eq!(FailureRates::default().pick(0.0), None);
+    }
+}
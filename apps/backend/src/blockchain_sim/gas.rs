@@ -0,0 +1,153 @@
+use serde::Serialize;
+use crate::wei::Wei;
+use super::chain::{Block, BLOCK_GAS_LIMIT};
+
+/// Base fee of the first simulated block, in wei (10 gwei)
+pub const INITIAL_BASE_FEE: Wei = Wei::new(10_000_000_000);
+/// Gas each block aims to use; the base fee rises when blocks are fuller and falls when emptier
+pub const BLOCK_GAS_TARGET: u64 = BLOCK_GAS_LIMIT / 2;
+/// Limits base fee changes to 12.5% per block
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+/// Recent blocks the fee estimate is derived from
+pub const FEE_ESTIMATE_BLOCKS: usize = 20;
+/// Priority fees suggested when recent blocks carried no transactions, in wei (1, 1.5 and 2 gwei)
+const DEFAULT_PRIORITY_FEES: [Wei; 3] = [Wei::new(1_000_000_000), Wei::new(1_500_000_000), Wei::new(2_000_000_000)];
+/// Percentiles of recent priority fees behind the slow, standard and fast suggestions
+const PRIORITY_FEE_PERCENTILES: [usize; 3] = [10, 50, 90];
+
+/// Highest fee per gas a client may offer, in wei (100,000 gwei), far above any real market
+pub const MAX_FEE_PER_GAS: Wei = Wei::new(100_000_000_000_000);
+
+/// Percentage both fee caps of a replacement transaction must rise by, as geth requires
+pub const REPLACEMENT_FEE_BUMP_PERCENT: u64 = 10;
+
+/// Base fee of the block after `parent`, following EIP-1559
+pub fn next_base_fee(parent: &Block) -> Wei {
+    let base_fee = parent.base_fee_per_gas;
+    let target = BLOCK_GAS_TARGET;
+    let gas_used = parent.gas_used;
+
+    if gas_used > target {
+        let delta = base_fee.saturating_mul(gas_used - target) / target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee.saturating_add(delta.max(Wei::new(1)))
+    } else {
+        let delta = base_fee.saturating_mul(target - gas_used) / target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee.saturating_sub(delta)
+    }
+}
+
+/// Price per unit of gas a transaction pays when included at `base_fee`
+pub fn effective_gas_price(base_fee: Wei, max_fee_per_gas: Wei, max_priority_fee_per_gas: Wei) -> Wei {
+    max_fee_per_gas.min(base_fee.saturating_add(max_priority_fee_per_gas))
+}
+
+/// Lowest fee a replacement may offer in place of `fee`
+pub fn min_replacement_fee(fee: Wei) -> Wei {
+    fee.mul_div_ceil(100 + REPLACEMENT_FEE_BUMP_PERCENT, 100)
+}
+
+/// Fees to offer for inclusion at some speed, in wei
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FeeSuggestion {
+    pub max_priority_fee_per_gas: Wei,
+    /// Leaves room for the base fee to double before the transaction is priced out
+    pub max_fee_per_gas: Wei,
+}
+
+impl FeeSuggestion {
+    fn new(base_fee: Wei, max_priority_fee_per_gas: Wei) -> Self {
+        Self {
+            max_priority_fee_per_gas,
+            max_fee_per_gas: base_fee.saturating_mul(2).saturating_add(max_priority_fee_per_gas),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GasEstimate {
+    /// Head the estimate was made at
+    pub block_number: u64,
+    /// Base fee the next block will charge
+    pub base_fee_per_gas: Wei,
+    pub slow: FeeSuggestion,
+    pub standard: FeeSuggestion,
+    pub fast: FeeSuggestion,
+}
+
+impl GasEstimate {
+    /// Suggest fees from the priority fees paid in `recent` blocks, oldest first and ending at the head
+    pub fn from_blocks(recent: &[Block]) -> Self {
+        let head = recent.last().expect("the chain always has a head block");
+        let base_fee = next_base_fee(head);
+
+        let mut tips: Vec<Wei> = recent
+            .iter()
+            .flat_map(|block| {
+                block
+                    .transactions
+                    .iter()
+                    .map(move |tx| tx.effective_gas_price.saturating_sub(block.base_fee_per_gas))
+            })
+            .collect();
+        tips.sort_unstable();
+
+        let [slow, standard, fast] = if tips.is_empty() {
+            DEFAULT_PRIORITY_FEES
+        } else {
+            PRIORITY_FEE_PERCENTILES.map(|percentile| tips[(tips.len() - 1) * percentile / 100])
+        };
+
+        Self {
+            block_number: head.number,
+            base_fee_per_gas: base_fee,
+            slow: FeeSuggestion::new(base_fee, slow),
+            standard: FeeSuggestion::new(base_fee, standard),
+            fast: FeeSuggestion::new(base_fee, fast),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain_sim::chain::IncludedTransaction;
+
+    fn block(gas_used: u64, base_fee_per_gas: u64) -> Block {
+        Block { number: 1, hash: String::new(), timestamp: 0, gas_used, base_fee_per_gas: Wei::new(base_fee_per_gas), transactions: Vec::new() }
+    }
+
+    #[test]
+    fn test_base_fee_follows_utilisation() {
+        assert_eq!(next_base_fee(&block(BLOCK_GAS_TARGET, 1_000)), Wei::new(1_000));
+        assert_eq!(next_base_fee(&block(BLOCK_GAS_LIMIT, 1_000)), Wei::new(1_125));
+        assert_eq!(next_base_fee(&block(0, 1_000)), Wei::new(875));
+        // Always rises by at least one wei above the target
+        assert_eq!(next_base_fee(&block(BLOCK_GAS_TARGET + 1, 7)), Wei::new(8));
+    }
+
+    #[test]
+    fn test_effective_gas_price_is_capped() {
+        assert_eq!(effective_gas_price(Wei::new(10), Wei::new(100), Wei::new(2)), Wei::new(12));
+        assert_eq!(effective_gas_price(Wei::new(10), Wei::new(11), Wei::new(2)), Wei::new(11));
+        assert_eq!(min_replacement_fee(Wei::new(1_000)), Wei::new(1_100));
+        assert_eq!(min_replacement_fee(Wei::new(1)), Wei::new(2));
+    }
+
+    #[test]
+    fn test_estimate_from_recent_tips() {
+        let quiet = GasEstimate::from_blocks(&[block(BLOCK_GAS_TARGET, 1_000_000_000)]);
+        assert_eq!(quiet.base_fee_per_gas, Wei::from_gwei(1));
+        assert_eq!(quiet.standard.max_priority_fee_per_gas, DEFAULT_PRIORITY_FEES[1]);
+        assert_eq!(quiet.standard.max_fee_per_gas, Wei::from_gwei(2).saturating_add(DEFAULT_PRIORITY_FEES[1]));
+
+        let mut busy = block(BLOCK_GAS_TARGET, 100);
+        busy.transactions = (1..=11)
+            .map(|tip| IncludedTransaction { hash: tip.to_string(), gas_used: 1, effective_gas_price: Wei::new(100 + tip), revert_reason: None })
+            .collect();
+        let estimate = GasEstimate::from_blocks(&[busy]);
+        assert_eq!(
+            (estimate.slow.max_priority_fee_per_gas, estimate.standard.max_priority_fee_per_gas, estimate.fast.max_priority_fee_per_gas),
+            (Wei::new(2), Wei::new(6), Wei::new(10))
+        );
+    }
+}
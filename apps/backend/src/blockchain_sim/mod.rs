@@ -0,0 +1,789 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::env::env_or;
+use crate::wei::Wei;
+
+pub mod chain;
+pub mod failure;
+pub mod gas;
+
+pub use chain::{block_hash, AccountNonce, Block, ChainState, ChainUpdate, IncludedTransaction, PendingTransaction, ReplacementError, BLOCK_GAS_LIMIT, DEFAULT_BLOCK_TIME, GENESIS_BLOCK};
+pub use failure::{FailureRates, SimulatedFailure, DEFAULT_DROP_TIMEOUT};
+pub use gas::{GasEstimate, MAX_FEE_PER_GAS};
+
+/// Confirmations a mint needs by default before it counts as confirmed
+pub const DEFAULT_REQUIRED_CONFIRMATIONS: u32 = 3;
+/// Address of the simulated NFT contract every mint is sent to, unless overridden
+pub const DEFAULT_CONTRACT_ADDRESS: &str = "0x8a90CAb2b38dba80c64b7734e58Ee1dB38B8992e";
+/// Share of each block's gas that other traffic uses on average
+pub const DEFAULT_BACKGROUND_LOAD: f64 = 0.5;
+/// Deepest reorg simulated by default; deeper than the required confirmations so confirmed mints can roll back
+pub const DEFAULT_MAX_REORG_DEPTH: u64 = 4;
+/// Gas used by the plain self-transfer that cancels a pending transaction
+pub const CANCEL_GAS_USED: u64 = 21_000;
+/// Gas used by a `transferFrom` call moving an NFT between wallets
+pub const TRANSFER_GAS_USED: u64 = 55_000;
+/// Gas a batch mint call uses whatever it mints, on top of [`BATCH_MINT_GAS_PER_TOKEN`] per token
+pub const BATCH_MINT_BASE_GAS: u64 = 50_000;
+pub const BATCH_MINT_GAS_PER_TOKEN: u64 = 45_000;
+/// Sender of the `Transfer` event emitted for a mint
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Gas used by a single call minting `tokens` NFTs
+pub fn batch_mint_gas_used(tokens: usize) -> u64 {
+    BATCH_MINT_BASE_GAS + BATCH_MINT_GAS_PER_TOKEN * tokens as u64
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionDetails {
+    pub transaction_hash: String,
+    /// Block the transaction was included in; `None` while it waits in the mempool
+    pub block_number: Option<u64>,
+    pub gas_used: u64,
+    /// Price per unit of gas paid once included; until then, what the next block would charge
+    pub gas_price: Wei,
+    /// EIP-1559 fee caps; `None` for transactions sent before fees were simulated
+    #[serde(default)]
+    pub max_fee_per_gas: Option<Wei>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<Wei>,
+    pub status: TransactionStatus,
+    pub timestamp: u64,
+    pub confirmations: u32,
+    /// Why the transaction failed once mined
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// Failure the simulator will apply to this transaction
+    #[serde(skip)]
+    pub simulated_failure: Option<SimulatedFailure>,
+    /// Sending account and its nonce; `None` for transactions sent before nonces were simulated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// A self-transfer replacing the mint transaction, sent to cancel it
+    #[serde(skip)]
+    pub cancellation: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum TransactionStatus {
+    Pending,
+    Confirmed,
+    Failed,
+    /// Evicted from the mempool without ever being mined
+    Dropped,
+    /// Superseded by a transaction with the same sender and nonce before it was mined
+    Replaced,
+}
+
+impl TransactionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStatus::Pending => "Pending",
+            TransactionStatus::Confirmed => "Confirmed",
+            TransactionStatus::Failed => "Failed",
+            TransactionStatus::Dropped => "Dropped",
+            TransactionStatus::Replaced => "Replaced",
+        }
+    }
+}
+
+impl std::str::FromStr for TransactionStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Pending" => Ok(TransactionStatus::Pending),
+            "Confirmed" => Ok(TransactionStatus::Confirmed),
+            "Failed" => Ok(TransactionStatus::Failed),
+            "Dropped" => Ok(TransactionStatus::Dropped),
+            "Replaced" => Ok(TransactionStatus::Replaced),
+            other => Err(anyhow::anyhow!("Unknown transaction status: {}", other)),
+        }
+    }
+}
+
+/// An event emitted by a simulated transaction, stored in decoded form
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct TransactionLog {
+    pub log_index: u32,
+    /// Contract that emitted the event
+    pub address: String,
+    pub event: String,
+    pub from: String,
+    pub to: String,
+    pub token_id: String,
+}
+
+impl TransactionLog {
+    /// ERC-721 `Transfer` event; a mint is a transfer from the zero address
+    pub fn transfer(log_index: u32, contract: &str, from: &str, to: &str, token_id: &str) -> Self {
+        Self {
+            log_index,
+            address: contract.to_string(),
+            event: "Transfer".to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            token_id: token_id.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MintingStatus {
+    pub mint_id: String,
+    pub status: MintStatus,
+    pub transaction_details: Option<TransactionDetails>,
+    pub created_at: u64,
+    pub confirmed_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum MintStatus {
+    Pending,
+    Confirming,
+    Confirmed,
+    Failed,
+}
+
+impl MintStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MintStatus::Pending => "Pending",
+            MintStatus::Confirming => "Confirming",
+            MintStatus::Confirmed => "Confirmed",
+            MintStatus::Failed => "Failed",
+        }
+    }
+
+    /// Whether the mint has reached a state the confirmation worker no longer drives. A reorg can
+    /// still roll it back while its block is within the max reorg depth.
+    pub fn is_final(&self) -> bool {
+        matches!(self, MintStatus::Confirmed | MintStatus::Failed)
+    }
+}
+
+impl std::str::FromStr for MintStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Pending" => Ok(MintStatus::Pending),
+            "Confirming" => Ok(MintStatus::Confirming),
+            "Confirmed" => Ok(MintStatus::Confirmed),
+            "Failed" => Ok(MintStatus::Failed),
+            other => Err(anyhow::anyhow!("Unknown mint status: {}", other)),
+        }
+    }
+}
+
+/// Source of the current unix time in seconds for the simulator.
+///
+/// A manual clock only moves when advanced, so confirmation delays can be fast-forwarded.
+#[derive(Debug)]
+pub enum Clock {
+    System,
+    Manual(AtomicU64),
+}
+
+impl Clock {
+    pub fn manual(start: u64) -> Self {
+        Clock::Manual(AtomicU64::new(start))
+    }
+
+    pub fn now(&self) -> u64 {
+        match self {
+            Clock::System => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            Clock::Manual(now) => now.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Move a manual clock forward, returning the new time. The system clock cannot be advanced,
+    /// and a manual clock is left alone rather than wrapped past `u64::MAX`.
+    pub fn advance(&self, secs: u64) -> Option<u64> {
+        match self {
+            Clock::System => None,
+            Clock::Manual(now) => now
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |now| now.checked_add(secs))
+                .ok()
+                .map(|previous| previous + secs),
+        }
+    }
+}
+
+/// Settings for the simulated chain, read from the environment
+#[derive(Debug, Clone)]
+pub struct SimulatorConfig {
+    /// Makes every generated hash and gas figure reproducible when set
+    pub seed: Option<u64>,
+    pub block_time: u64,
+    /// Confirmations after which a mint counts as confirmed
+    pub required_confirmations: u32,
+    pub contract_address: String,
+    /// Chance of each submitted transaction failing on its own
+    pub failure_rates: FailureRates,
+    /// Seconds before a transaction destined to be dropped leaves the mempool
+    pub drop_timeout: u64,
+    /// Share of each block's gas other traffic uses on average, which drives the base fee
+    pub background_load: f64,
+    /// Chance of each new block being followed by a reorg; 0 disables reorgs
+    pub reorg_rate: f64,
+    /// Most blocks a single simulated reorg orphans
+    pub max_reorg_depth: u64,
+}
+
+impl Default for SimulatorConfig {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            block_time: DEFAULT_BLOCK_TIME,
+            required_confirmations: DEFAULT_REQUIRED_CONFIRMATIONS,
+            contract_address: DEFAULT_CONTRACT_ADDRESS.to_string(),
+            failure_rates: FailureRates::default(),
+            drop_timeout: DEFAULT_DROP_TIMEOUT,
+            background_load: DEFAULT_BACKGROUND_LOAD,
+            reorg_rate: 0.0,
+            max_reorg_depth: DEFAULT_MAX_REORG_DEPTH,
+        }
+    }
+}
+
+impl SimulatorConfig {
+    pub fn from_env() -> Self {
+        Self {
+            seed: std::env::var("SIM_SEED").ok().and_then(|seed| seed.parse().ok()),
+            block_time: env_or("SIM_BLOCK_TIME_SECS", DEFAULT_BLOCK_TIME),
+            required_confirmations: env_or("SIM_REQUIRED_CONFIRMATIONS", DEFAULT_REQUIRED_CONFIRMATIONS),
+            contract_address: env_or("SIM_CONTRACT_ADDRESS", DEFAULT_CONTRACT_ADDRESS.to_string()),
+            failure_rates: FailureRates::from_env(),
+            drop_timeout: env_or("SIM_DROP_TIMEOUT_SECS", DEFAULT_DROP_TIMEOUT),
+            background_load: env_or("SIM_BACKGROUND_LOAD", DEFAULT_BACKGROUND_LOAD),
+            reorg_rate: env_or("SIM_REORG_RATE", 0.0),
+            max_reorg_depth: env_or("SIM_MAX_REORG_DEPTH", DEFAULT_MAX_REORG_DEPTH),
+        }
+    }
+}
+
+/// The simulator shared between request handlers and the minting queue
+pub type SharedSimulator = Arc<BlockchainSimulator>;
+
+/// What a sender asks of a new transaction; unset fees follow the current gas estimate
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TransactionRequest {
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+    /// Overrides the configured failure rates
+    pub failure: Option<SimulatedFailure>,
+    /// Gas the call uses; a mint's usage is simulated when unset
+    pub gas_used: Option<u64>,
+}
+
+/// How to replace a pending transaction; unset fees are bumped just enough to be accepted,
+/// or follow the fast gas estimate when that is higher
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReplacementRequest {
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+    /// Send a plain self-transfer in the original's place instead of the same call with higher fees
+    pub cancel: bool,
+}
+
+/// Simulates the chain mints are sent to. With a seed every value it generates is
+/// reproducible for the same sequence of calls; without one it draws from OS entropy.
+#[derive(Debug)]
+pub struct BlockchainSimulator {
+    config: SimulatorConfig,
+    rng: Mutex<StdRng>,
+    /// Draws background traffic and reorgs separately, so transaction values don't depend on how many blocks were produced
+    traffic_rng: Mutex<StdRng>,
+    chain: Mutex<ChainState>,
+    clock: Clock,
+}
+
+impl BlockchainSimulator {
+    pub fn new(config: SimulatorConfig, clock: Clock) -> Self {
+        let mut rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let traffic_rng = StdRng::seed_from_u64(rng.gen());
+        let chain = ChainState::new(GENESIS_BLOCK, clock.now(), config.block_time, config.drop_timeout);
+        Self {
+            config,
+            rng: Mutex::new(rng),
+            traffic_rng: Mutex::new(traffic_rng),
+            chain: Mutex::new(chain),
+            clock,
+        }
+    }
+
+    /// Configured from the environment. `SIM_CLOCK=manual` starts a manual clock at the current time.
+    pub fn from_env() -> Self {
+        let clock = match std::env::var("SIM_CLOCK").as_deref() {
+            Ok("manual") => Clock::manual(Clock::System.now()),
+            _ => Clock::System,
+        };
+        Self::new(SimulatorConfig::from_env(), clock)
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.config.seed
+    }
+
+    pub fn required_confirmations(&self) -> u32 {
+        self.config.required_confirmations
+    }
+
+    pub fn contract_address(&self) -> &str {
+        &self.config.contract_address
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    fn gen_range(&self, range: std::ops::Range<u64>) -> u64 {
+        self.rng.lock().unwrap().gen_range(range)
+    }
+
+    /// Generate a realistic Ethereum transaction hash
+    pub fn generate_transaction_hash(&self) -> String {
+        let mut bytes = [0u8; 32];
+        self.rng.lock().unwrap().fill(&mut bytes);
+        format!("0x{}", hex::encode(bytes))
+    }
+
+    /// Generate realistic gas usage for NFT minting
+    pub fn generate_gas_used(&self) -> u64 {
+        // NFT minting typically uses 150,000 - 300,000 gas
+        self.gen_range(150_000..300_000)
+    }
+
+    /// Suggested fees for the next block, derived from recent blocks
+    pub fn gas_estimate(&self) -> GasEstimate {
+        self.chain.lock().unwrap().gas_estimate()
+    }
+
+    /// Legacy gas price offered to clients asking what to pay: the next base fee plus a standard tip
+    pub fn suggested_gas_price(&self) -> Wei {
+        let estimate = self.gas_estimate();
+        estimate.base_fee_per_gas.saturating_add(estimate.standard.max_priority_fee_per_gas)
+    }
+
+    /// Get current timestamp from the simulator's clock
+    pub fn current_timestamp(&self) -> u64 {
+        self.clock.now()
+    }
+
+    /// Roll whether a transaction fails under the configured failure rates
+    fn roll_failure(&self) -> Option<SimulatedFailure> {
+        let roll: f64 = self.rng.lock().unwrap().gen();
+        self.config.failure_rates.pick(roll)
+    }
+
+    /// Create a transaction from `from` with its next nonce and add it to the mempool; it has no block until one is produced
+    pub fn submit_transaction(&self, from: &str, request: TransactionRequest) -> TransactionDetails {
+        let mut transaction = self.new_transaction(from, request);
+        // The nonce is taken under the same lock as the submission so concurrent sends never share one
+        let mut chain = self.chain.lock().unwrap();
+        transaction.nonce = Some(chain.pending_nonce(from));
+        chain.submit(pending_transaction(&transaction));
+        transaction
+    }
+
+    /// Create transactions from `from` with consecutive nonces after its last one in the mempool,
+    /// without sending them. Nothing else may be sent from `from` until they are, with
+    /// [`send_transaction`](Self::send_transaction) in the same order.
+    pub fn prepare_transactions(&self, from: &str, requests: &[TransactionRequest]) -> Vec<TransactionDetails> {
+        let mut transactions: Vec<TransactionDetails> = requests.iter().map(|request| self.new_transaction(from, *request)).collect();
+        let first_nonce = self.pending_nonce(from);
+        for (nonce, transaction) in (first_nonce..).zip(transactions.iter_mut()) {
+            transaction.nonce = Some(nonce);
+        }
+        transactions
+    }
+
+    /// A transaction from `from` with its hash, gas and fees drawn, but no nonce yet
+    fn new_transaction(&self, from: &str, request: TransactionRequest) -> TransactionDetails {
+        let transaction_hash = self.generate_transaction_hash();
+        // Always drawn so a fixed gas usage doesn't shift later seeded values
+        let generated_gas_used = self.generate_gas_used();
+        let gas_used = request.gas_used.unwrap_or(generated_gas_used);
+        // Always rolled so forcing a failure doesn't shift later seeded values
+        let rolled = self.roll_failure();
+
+        let estimate = self.gas_estimate();
+        let max_priority_fee_per_gas = request
+            .max_priority_fee_per_gas
+            .unwrap_or(estimate.standard.max_priority_fee_per_gas);
+        let max_fee_per_gas = request
+            .max_fee_per_gas
+            .unwrap_or(estimate.base_fee_per_gas.saturating_mul(2).saturating_add(max_priority_fee_per_gas));
+        let max_priority_fee_per_gas = max_priority_fee_per_gas.min(max_fee_per_gas);
+
+        TransactionDetails {
+            transaction_hash,
+            block_number: None,
+            gas_used,
+            gas_price: gas::effective_gas_price(estimate.base_fee_per_gas, max_fee_per_gas, max_priority_fee_per_gas),
+            max_fee_per_gas: Some(max_fee_per_gas),
+            max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+            status: TransactionStatus::Pending,
+            timestamp: self.current_timestamp(),
+            confirmations: 0,
+            revert_reason: None,
+            simulated_failure: request.failure.or(rolled),
+            from: Some(from.to_string()),
+            nonce: None,
+            cancellation: false,
+        }
+    }
+
+    /// Replace a transaction still waiting in the mempool with one from the same sender and nonce.
+    /// A sped-up transaction no longer gets dropped; a cancellation does nothing, so it never fails.
+    pub fn replace_transaction(
+        &self,
+        original: &TransactionDetails,
+        request: ReplacementRequest,
+    ) -> Result<TransactionDetails, ReplacementError> {
+        let (Some(from), Some(nonce)) = (original.from.clone(), original.nonce) else {
+            return Err(ReplacementError::NotPending);
+        };
+        let transaction_hash = self.generate_transaction_hash();
+
+        let estimate = self.gas_estimate();
+        let original_max_fee = original.max_fee_per_gas.unwrap_or(original.gas_price);
+        let original_priority_fee = original.max_priority_fee_per_gas.unwrap_or(original.gas_price);
+        let max_priority_fee_per_gas = request
+            .max_priority_fee_per_gas
+            .unwrap_or_else(|| gas::min_replacement_fee(original_priority_fee).max(estimate.fast.max_priority_fee_per_gas));
+        let max_fee_per_gas = request.max_fee_per_gas.unwrap_or_else(|| {
+            gas::min_replacement_fee(original_max_fee).max(estimate.base_fee_per_gas.saturating_mul(2).saturating_add(max_priority_fee_per_gas))
+        });
+        let max_priority_fee_per_gas = max_priority_fee_per_gas.min(max_fee_per_gas);
+
+        let replacement = TransactionDetails {
+            transaction_hash,
+            block_number: None,
+            gas_used: if request.cancel { CANCEL_GAS_USED } else { original.gas_used },
+            gas_price: gas::effective_gas_price(estimate.base_fee_per_gas, max_fee_per_gas, max_priority_fee_per_gas),
+            max_fee_per_gas: Some(max_fee_per_gas),
+            max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+            status: TransactionStatus::Pending,
+            timestamp: self.current_timestamp(),
+            confirmations: 0,
+            revert_reason: None,
+            simulated_failure: original
+                .simulated_failure
+                .filter(|failure| !request.cancel && *failure != SimulatedFailure::Dropped),
+            from: Some(from),
+            nonce: Some(nonce),
+            cancellation: request.cancel,
+        };
+        self.chain.lock().unwrap().replace(pending_transaction(&replacement))?;
+        Ok(replacement)
+    }
+
+    /// Add a prepared transaction to the mempool, or put back one that was pending before a restart
+    pub fn send_transaction(&self, transaction: &TransactionDetails) {
+        self.chain.lock().unwrap().submit(pending_transaction(transaction));
+    }
+
+    /// Nonce of the next transaction from `address` the chain will include
+    pub fn account_nonce(&self, address: &str) -> u64 {
+        self.chain.lock().unwrap().account_nonce(address)
+    }
+
+    /// Nonce a new transaction from `address` would be sent with
+    pub fn pending_nonce(&self, address: &str) -> u64 {
+        self.chain.lock().unwrap().pending_nonce(address)
+    }
+
+    /// Continue an account's nonces from what was mined before a restart
+    pub fn resume_account_nonce(&self, address: &str, nonce: u64) {
+        self.chain.lock().unwrap().set_account_nonce(address, nonce);
+    }
+
+    /// Continue the chain from the last block persisted before a restart
+    pub fn resume_from_block(&self, head_number: u64) {
+        let mut chain = self.chain.lock().unwrap();
+        *chain = ChainState::new(head_number, self.clock.now(), self.config.block_time, self.config.drop_timeout);
+    }
+
+    /// Produce every block that is due on the simulator's clock. With a reorg rate set, each
+    /// new block may be followed by a reorg, whose replacement blocks are produced straight away.
+    pub fn produce_blocks(&self) -> ChainUpdate {
+        let mut chain = self.chain.lock().unwrap();
+        let mut traffic_rng = self.traffic_rng.lock().unwrap();
+        // Uniform around the configured load: at 0.5 the base fee drifts around its starting level
+        let load = self.config.background_load.clamp(0.0, 1.0);
+        let spread = load.min(1.0 - load);
+        let low = (BLOCK_GAS_LIMIT as f64 * (load - spread)) as u64;
+        let high = (BLOCK_GAS_LIMIT as f64 * (load + spread)) as u64;
+
+        let now = self.clock.now();
+        let mut update = chain.advance_to(now, || traffic_rng.gen_range(low..=high));
+        if self.config.reorg_rate > 0.0 && self.config.max_reorg_depth > 0 {
+            let reorged = (0..update.blocks.len()).any(|_| traffic_rng.gen::<f64>() < self.config.reorg_rate);
+            if reorged {
+                let depth = traffic_rng.gen_range(1..=self.config.max_reorg_depth);
+                chain.reorg(depth);
+                update.extend(chain.advance_to(now, || traffic_rng.gen_range(low..=high)));
+            }
+        }
+        update
+    }
+
+    /// Orphan up to `depth` blocks from the top of the chain. The next `produce_blocks`
+    /// reports them and builds their replacements. Returns the number of blocks orphaned.
+    pub fn reorg(&self, depth: u64) -> usize {
+        self.chain.lock().unwrap().reorg(depth)
+    }
+
+    pub fn head_block_number(&self) -> u64 {
+        self.chain.lock().unwrap().head().number
+    }
+
+    /// A recent block, if the simulator still holds it
+    pub fn block(&self, number: u64) -> Option<Block> {
+        self.chain.lock().unwrap().block(number).cloned()
+    }
+
+    pub fn block_timestamp(&self, number: u64) -> Option<u64> {
+        self.chain.lock().unwrap().block_timestamp(number)
+    }
+
+    pub fn confirmations(&self, block_number: u64) -> u32 {
+        self.chain.lock().unwrap().confirmations(block_number)
+    }
+
+    /// Whether a simulated reorg could still orphan `block_number`, being within the max reorg depth of the head
+    pub fn reorg_can_reach(&self, block_number: u64) -> bool {
+        (self.confirmations(block_number) as u64) < self.config.max_reorg_depth
+    }
+}
+
+/// The mempool entry for a transaction. Legacy transactions offer their gas price as both fee caps.
+fn pending_transaction(transaction: &TransactionDetails) -> PendingTransaction {
+    PendingTransaction {
+        hash: transaction.transaction_hash.clone(),
+        gas_used: transaction.gas_used,
+        max_fee_per_gas: transaction.max_fee_per_gas.unwrap_or(transaction.gas_price),
+        max_priority_fee_per_gas: transaction.max_priority_fee_per_gas.unwrap_or(transaction.gas_price),
+        failure: transaction.simulated_failure,
+        submitted_at: transaction.timestamp,
+        earliest_block: 0,
+        sender: transaction.from.as_deref().zip(transaction.nonce).map(|(from, nonce)| AccountNonce::new(from, nonce)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0x0CB030d11A8Be48b60418857874deEe61D1071e0";
+
+    fn simulator(seed: Option<u64>) -> BlockchainSimulator {
+        let config = SimulatorConfig { seed, ..SimulatorConfig::default() };
+        BlockchainSimulator::new(config, Clock::manual(1_700_000_000))
+    }
+
+    fn failure(failure: SimulatedFailure) -> TransactionRequest {
+        TransactionRequest { failure: Some(failure), ..TransactionRequest::default() }
+    }
+
+    #[test]
+    fn test_transaction_hash_format() {
+        let hash = simulator(None).generate_transaction_hash();
+        assert!(hash.starts_with("0x"));
+        assert_eq!(hash.len(), 66); // 0x + 64 hex chars
+    }
+
+    #[test]
+    fn test_gas_usage_range() {
+        let gas = simulator(None).generate_gas_used();
+        assert!(gas >= 150_000);
+        assert!(gas < 300_000);
+    }
+
+    #[test]
+    fn test_seeded_simulator_is_reproducible() {
+        let first = simulator(Some(42));
+        let second = simulator(Some(42));
+        for _ in 0..3 {
+            let a = first.submit_transaction(WALLET, TransactionRequest::default());
+            let b = second.submit_transaction(WALLET, TransactionRequest::default());
+            assert_eq!(a.transaction_hash, b.transaction_hash);
+            assert_eq!((a.gas_used, a.gas_price), (b.gas_used, b.gas_price));
+            first.clock().advance(DEFAULT_BLOCK_TIME);
+            second.clock().advance(DEFAULT_BLOCK_TIME);
+            assert_eq!(first.produce_blocks(), second.produce_blocks());
+        }
+
+        let other = simulator(Some(43));
+        assert_ne!(other.generate_transaction_hash(), simulator(Some(42)).generate_transaction_hash());
+    }
+
+    #[test]
+    fn test_transactions_are_included_in_later_blocks() {
+        let simulator = simulator(Some(1));
+        let first = simulator.submit_transaction(WALLET, TransactionRequest::default());
+        assert_eq!(first.block_number, None);
+
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        let second = simulator.submit_transaction(WALLET, TransactionRequest::default());
+        let blocks = simulator.produce_blocks().blocks;
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].number, GENESIS_BLOCK + 1);
+        let included: Vec<&str> = blocks[0].transactions.iter().map(|tx| tx.hash.as_str()).collect();
+        assert_eq!(included, vec![first.transaction_hash.as_str(), second.transaction_hash.as_str()]);
+        let included_gas: u64 = blocks[0].transactions.iter().map(|tx| tx.gas_used).sum();
+        assert_eq!(included_gas, first.gas_used + second.gas_used);
+        // Background traffic fills some of the rest of the block
+        assert!(blocks[0].gas_used >= included_gas && blocks[0].gas_used <= BLOCK_GAS_LIMIT);
+
+        simulator.clock().advance(2 * DEFAULT_BLOCK_TIME);
+        assert_eq!(simulator.produce_blocks().blocks.len(), 2);
+        assert_eq!(simulator.confirmations(GENESIS_BLOCK + 1), 2);
+
+        simulator.resume_from_block(GENESIS_BLOCK + 50);
+        assert_eq!(simulator.head_block_number(), GENESIS_BLOCK + 50);
+    }
+
+    #[test]
+    fn test_reorgs_orphan_recent_blocks() {
+        let config = SimulatorConfig { seed: Some(7), reorg_rate: 1.0, max_reorg_depth: 2, ..SimulatorConfig::default() };
+        let reorging = BlockchainSimulator::new(config, Clock::manual(1_700_000_000));
+        reorging.clock().advance(3 * DEFAULT_BLOCK_TIME);
+        let update = reorging.produce_blocks();
+        // Blocks orphaned within the update they were produced in are never reported
+        assert!(update.orphaned.is_empty());
+        let numbers: Vec<u64> = update.blocks.iter().map(|block| block.number).collect();
+        assert_eq!(numbers, vec![GENESIS_BLOCK + 1, GENESIS_BLOCK + 2, GENESIS_BLOCK + 3]);
+        assert_eq!(reorging.head_block_number(), GENESIS_BLOCK + 3);
+
+        let simulator = simulator(Some(7));
+        let tx = simulator.submit_transaction(WALLET, TransactionRequest::default());
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        let mined = simulator.produce_blocks().blocks;
+        assert_eq!(simulator.reorg(3), 1);
+        assert_eq!(simulator.head_block_number(), GENESIS_BLOCK);
+
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        let update = simulator.produce_blocks();
+        assert_eq!(update.orphaned, mined);
+        // The orphaned slot is produced again, and the transaction goes into the block after it
+        assert_eq!(update.blocks.len(), 2);
+        assert_ne!(update.blocks[0].hash, mined[0].hash);
+        assert!(update.blocks[0].transactions.is_empty());
+        assert_eq!(update.blocks[1].transactions[0].hash, tx.transaction_hash);
+    }
+
+    #[test]
+    fn test_nonces_and_replacements() {
+        let simulator = simulator(Some(11));
+        let first = simulator.submit_transaction(WALLET, failure(SimulatedFailure::Dropped));
+        let second = simulator.submit_transaction(&WALLET.to_lowercase(), TransactionRequest::default());
+        assert_eq!((first.nonce, second.nonce), (Some(0), Some(1)));
+        assert_eq!(simulator.pending_nonce(WALLET), 2);
+
+        // Speeding up rescues a transaction that would have been dropped
+        let sped_up = simulator.replace_transaction(&first, ReplacementRequest::default()).unwrap();
+        assert_eq!((sped_up.nonce, sped_up.simulated_failure), (Some(0), None));
+        assert!(sped_up.max_fee_per_gas >= first.max_fee_per_gas.map(gas::min_replacement_fee));
+        let lowball = ReplacementRequest { max_fee_per_gas: second.max_fee_per_gas, ..ReplacementRequest::default() };
+        assert!(matches!(simulator.replace_transaction(&second, lowball), Err(ReplacementError::Underpriced { .. })));
+
+        let cancel = simulator
+            .replace_transaction(&second, ReplacementRequest { cancel: true, ..ReplacementRequest::default() })
+            .unwrap();
+        assert_eq!((cancel.gas_used, cancel.cancellation), (CANCEL_GAS_USED, true));
+
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        let included: Vec<String> = simulator.produce_blocks().blocks[0].transactions.iter().map(|tx| tx.hash.clone()).collect();
+        assert_eq!(included, vec![sped_up.transaction_hash, cancel.transaction_hash.clone()]);
+        assert_eq!(simulator.account_nonce(WALLET), 2);
+        // Mined transactions can't be replaced
+        assert_eq!(simulator.replace_transaction(&cancel, ReplacementRequest::default()).unwrap_err(), ReplacementError::NotPending);
+    }
+
+    #[test]
+    fn test_prepared_transactions_wait_until_sent() {
+        let simulator = simulator(Some(5));
+        let prepared = simulator.prepare_transactions(WALLET, &[TransactionRequest::default(), TransactionRequest::default()]);
+        assert_eq!((prepared[0].nonce, prepared[1].nonce), (Some(0), Some(1)));
+        // Nothing prepared is in the mempool, so dropping it leaves no gap in the nonces
+        assert_eq!(simulator.pending_nonce(WALLET), 0);
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        assert!(simulator.produce_blocks().blocks[0].transactions.is_empty());
+
+        for transaction in &prepared {
+            simulator.send_transaction(transaction);
+        }
+        assert_eq!(simulator.pending_nonce(WALLET), 2);
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        let included: Vec<String> = simulator.produce_blocks().blocks[0].transactions.iter().map(|tx| tx.hash.clone()).collect();
+        assert_eq!(included, vec![prepared[0].transaction_hash.clone(), prepared[1].transaction_hash.clone()]);
+    }
+
+    #[test]
+    fn test_manual_clock() {
+        let simulator = simulator(Some(1));
+        assert_eq!(simulator.current_timestamp(), 1_700_000_000);
+        assert_eq!(simulator.clock().advance(45), Some(1_700_000_045));
+        assert_eq!(simulator.current_timestamp(), 1_700_000_045);
+        assert_eq!(simulator.submit_transaction(WALLET, TransactionRequest::default()).timestamp, 1_700_000_045);
+        assert_eq!(Clock::System.advance(1), None);
+
+        let clock = Clock::manual(u64::MAX - 1);
+        assert_eq!(clock.advance(2), None);
+        assert_eq!(clock.now(), u64::MAX - 1);
+        assert_eq!(clock.advance(1), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_failure_rates_and_forced_failures() {
+        let config = SimulatorConfig {
+            seed: Some(3),
+            failure_rates: FailureRates { revert: 1.0, ..FailureRates::default() },
+            ..SimulatorConfig::default()
+        };
+        let failing = BlockchainSimulator::new(config, Clock::manual(1_700_000_000));
+        assert_eq!(failing.submit_transaction(WALLET, TransactionRequest::default()).simulated_failure, Some(SimulatedFailure::Revert));
+        assert_eq!(
+            failing.submit_transaction(WALLET, failure(SimulatedFailure::Dropped)).simulated_failure,
+            Some(SimulatedFailure::Dropped)
+        );
+
+        // Forcing a failure draws the same values as not forcing one
+        let (plain, forced) = (simulator(Some(5)), simulator(Some(5)));
+        assert_eq!(plain.submit_transaction(WALLET, TransactionRequest::default()).simulated_failure, None);
+        forced.submit_transaction(WALLET, failure(SimulatedFailure::OutOfGas));
+        assert_eq!(plain.submit_transaction(WALLET, TransactionRequest::default()).transaction_hash, forced.submit_transaction(WALLET, TransactionRequest::default()).transaction_hash);
+    }
+
+    #[test]
+    fn test_fees_default_to_the_estimate() {
+        let simulator = simulator(Some(9));
+        let estimate = simulator.gas_estimate();
+        let tx = simulator.submit_transaction(WALLET, TransactionRequest::default());
+        assert_eq!(tx.max_priority_fee_per_gas, Some(estimate.standard.max_priority_fee_per_gas));
+        assert_eq!(tx.max_fee_per_gas, Some(estimate.standard.max_fee_per_gas));
+        assert_eq!(tx.gas_price, estimate.base_fee_per_gas.saturating_add(estimate.standard.max_priority_fee_per_gas));
+
+        // A tip above the fee cap is lowered to it
+        let capped = simulator.submit_transaction(WALLET, TransactionRequest {
+            max_fee_per_gas: Some(estimate.base_fee_per_gas),
+            ..TransactionRequest::default()
+        });
+        assert_eq!(capped.max_priority_fee_per_gas, Some(estimate.base_fee_per_gas.min(estimate.standard.max_priority_fee_per_gas)));
+        assert_eq!(capped.gas_price, estimate.base_fee_per_gas);
+    }
+}
@@ -4,174 +4,243 @@ use axum::{
     response::IntoResponse,
     http::StatusCode,
 };
-use serde::Deserialize;
 use crate::{
     database::DbPool,
-    auth::types::ApiResponse,
-    collections::types::*,
-    nft::types::{NftResponse, NftAttribute},
+    auth::{roles::Role, types::ApiResponse, AuthSession},
+    collections::{slug::{is_valid_slug, slugify}, types::*},
+    db_operations::{
+        create_collection, delete_collection, find_collection, get_collection_with_stats,
+        get_collections_with_stats, slug_exists, update_collection, CollectionChanges, NewCollection, NftSearch,
+    },
+    entities::CollectionModel,
+    nft::{search::load_nft_page, types::NftResponse},
+    pagination::{PaginatedResponse, PaginationQuery},
 };
-use chrono::{Utc, Duration};
 
-#[derive(Debug, Deserialize)]
-pub struct CollectionQuery {
-    pub page: Option<u64>,
-    pub limit: Option<u64>,
+fn error_response<T>(status: StatusCode, message: String) -> (StatusCode, Json<ApiResponse<T>>) {
+    let response = ApiResponse::<T> {
+        success: false,
+        data: None,
+        message,
+    };
+    (status, Json(response))
+}
+
+/// Load a collection for a write, allowing its creator or anyone holding `override_role`
+async fn load_owned_collection(
+    pool: &DbPool,
+    session: &AuthSession,
+    id: &str,
+    override_role: Role,
+) -> Result<CollectionModel, (StatusCode, String)> {
+    let collection = match find_collection(pool, id).await {
+        Ok(Some(collection)) => collection,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, "Collection not found".to_string())),
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to find collection: {}", e))),
+    };
+
+    if collection.creator_id != session.user.id && session.role() < override_role {
+        return Err((StatusCode::FORBIDDEN, "Only the collection creator may change this collection".to_string()));
+    }
+
+    Ok(collection)
 }
 
 pub async fn get_collections_handler(
     State(pool): State<DbPool>,
-    Query(query): Query<CollectionQuery>,
+    Query(query): Query<PaginationQuery>,
 ) -> impl IntoResponse {
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.page.unwrap_or(0) * limit;
-
-    // Generate demo collections
-    let collections: Vec<Collection> = (offset..offset + limit)
-        .map(|i| Collection {
-            id: format!("collection_{}", i + 1),
-            name: format!("Ethereal Collection {}", i + 1),
-            description: format!("A stunning collection of digital art pieces showcasing the beauty of ethereal dreams and cosmic wonders."),
-            image_url: Some(format!("https://picsum.photos/400/400?random={}", i + 1)),
-            banner_url: Some(format!("https://picsum.photos/1200/400?random={}", i + 1)),
-            creator_wallet: format!("0x{:040x}", i + 1),
-            nft_count: (i * 50) + (i % 100) + 10,
-            unique_owners: (i * 20) + (i % 50) + 5,
-            total_volume: (i as f64 * 10.5) + 5.0,
-            floor_price: Some((i as f64 * 0.1) + 0.5),
-            created_at: Utc::now() - Duration::days((i % 30) as i64),
-            is_featured: i % 5 == 0,
-        })
-        .collect();
-
-    let response = ApiResponse {
-        success: true,
-        data: Some(PaginatedResponse {
-            data: collections,
-            total: 156,
-            page: query.page.unwrap_or(0),
-            limit,
-        }),
-        message: "Collections retrieved successfully".to_string(),
-    };
-
-    (StatusCode::OK, Json(response))
+    if query.cursor.is_some() {
+        return error_response(StatusCode::BAD_REQUEST, "Collections are paginated by page, not cursor".to_string());
+    }
+
+    match get_collections_with_stats(&pool, query.limit(), query.offset()).await {
+        Ok((collections, total)) => {
+            let collections = collections.into_iter().map(Collection::from).collect();
+            let response = ApiResponse {
+                success: true,
+                data: Some(PaginatedResponse::new(collections, total, &query)),
+                message: "Collections retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to retrieve collections: {}", e)),
+    }
 }
 
 pub async fn get_collection_by_id_handler(
     State(pool): State<DbPool>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
-    // Generate demo collection
-    let collection = Collection {
-        id: id.clone(),
-        name: format!("Ethereal Collection {}", id),
-        description: "A stunning collection of digital art pieces showcasing the beauty of ethereal dreams and cosmic wonders. Each piece is carefully crafted to evoke emotions and inspire creativity.".to_string(),
-        image_url: Some(format!("https://picsum.photos/400/400?random={}", id)),
-        banner_url: Some(format!("https://picsum.photos/1200/400?random={}", id)),
-                    creator_wallet: format!("0x{:040x}", id.parse::<u64>().unwrap_or(1)).to_string(),
-        nft_count: 342,
-        unique_owners: 156,
-        total_volume: 1247.5,
-        floor_price: Some(0.85),
-        created_at: Utc::now() - Duration::days(15),
-        is_featured: true,
-    };
-
-    let response = ApiResponse {
-        success: true,
-        data: Some(collection),
-        message: "Collection retrieved successfully".to_string(),
-    };
-
-    (StatusCode::OK, Json(response))
+    match get_collection_with_stats(&pool, &id).await {
+        Ok(Some(collection)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(Collection::from(collection)),
+                message: "Collection retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Collection not found".to_string()),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to retrieve collection: {}", e)),
+    }
 }
 
 pub async fn get_collection_nfts_handler(
     State(pool): State<DbPool>,
     Path(id): Path<String>,
-    Query(query): Query<CollectionQuery>,
+    Query(query): Query<PaginationQuery>,
 ) -> impl IntoResponse {
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.page.unwrap_or(0) * limit;
-
-    // Generate demo NFTs for collection
-    let nfts: Vec<NftResponse> = (offset..offset + limit)
-        .map(|i| NftResponse {
-            id: format!("nft_{}", i + 1),
-            token_id: format!("NFT-{:06}", i + 1),
-            name: format!("Ethereal Dream #{}", i + 1),
-            description: Some(format!("A beautiful piece from the {} collection", id)),
-            image: format!("https://picsum.photos/400/400?random={}", i + 1),
-            minted_at: Utc::now() - Duration::hours((i % 168) as i64),
-            transaction_hash: Some(format!("0x{:064x}", i + 1)),
-            owner_id: format!("user_{}", (i % 50) + 1),
-            attributes: Some(vec![
-                NftAttribute {
-                    trait_type: "Rarity".to_string(),
-                    value: if i % 10 == 0 { "Legendary".to_string() } else if i % 5 == 0 { "Epic".to_string() } else { "Common".to_string() }
-                },
-                NftAttribute {
-                    trait_type: "Element".to_string(),
-                    value: if i % 4 == 0 { "Fire".to_string() } else if i % 4 == 1 { "Water".to_string() } else if i % 4 == 2 { "Earth".to_string() } else { "Air".to_string() }
-                },
-            ]),
-            collection_name: Some(format!("Collection {}", id)),
-            mint_status: Some(crate::blockchain_sim::MintStatus::Confirmed),
-            block_number: Some(12345678 + i as u64),
-            gas_used: Some(210000 + (i * 1000) as u64),
-            gas_price: Some(20000000000 + (i * 1000000) as u64),
-        })
-        .collect();
-
-    let response = ApiResponse {
-        success: true,
-        data: Some(PaginatedResponse {
-            data: nfts,
-            total: 342,
-            page: query.page.unwrap_or(0),
-            limit,
-        }),
-        message: "Collection NFTs retrieved successfully".to_string(),
+    let collection = match find_collection(&pool, &id).await {
+        Ok(Some(collection)) => collection,
+        Ok(None) => return error_response(StatusCode::NOT_FOUND, "Collection not found".to_string()),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to find collection: {}", e)),
     };
 
-    (StatusCode::OK, Json(response))
+    let search = NftSearch {
+        collection: Some(collection.id),
+        ..NftSearch::default()
+    };
+    match load_nft_page(&pool, &search, &query).await {
+        Ok(page) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(page.map(|(nft, _)| NftResponse::from(nft))),
+                message: "Collection NFTs retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err((status, message)) => error_response(status, message),
+    }
 }
 
 pub async fn create_collection_handler(
     State(pool): State<DbPool>,
+    session: AuthSession,
     Json(payload): Json<CreateCollectionRequest>,
 ) -> impl IntoResponse {
-    let collection = Collection {
-        id: format!("collection_{}", cuid::cuid2()),
-        name: payload.name,
+    // Collections may only be created for the wallet that signed in
+    if !session.owns_wallet(&payload.creator_wallet) {
+        return error_response(StatusCode::FORBIDDEN, "Creator wallet does not match the signed-in wallet".to_string());
+    }
+
+    let name = payload.name.trim().to_string();
+    if name.is_empty() {
+        return error_response(StatusCode::BAD_REQUEST, "Collection name must not be empty".to_string());
+    }
+    if payload.max_supply == Some(0) {
+        return error_response(StatusCode::BAD_REQUEST, "Max supply must be at least 1".to_string());
+    }
+
+    let slug = payload.slug.unwrap_or_else(|| slugify(&name));
+    if !is_valid_slug(&slug) {
+        return error_response(StatusCode::BAD_REQUEST, format!("Invalid slug '{}'; use lowercase letters, digits and dashes", slug));
+    }
+    match slug_exists(&pool, &slug).await {
+        Ok(false) => {}
+        Ok(true) => return error_response(StatusCode::CONFLICT, format!("Slug '{}' is already taken", slug)),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to check slug: {}", e)),
+    }
+
+    let new_collection = NewCollection {
+        creator_id: session.user.id.clone(),
+        name,
+        slug,
         description: payload.description,
         image_url: payload.image_url,
         banner_url: payload.banner_url,
-        creator_wallet: payload.creator_wallet,
-        nft_count: 0,
-        unique_owners: 0,
-        total_volume: 0.0,
-        floor_price: None,
-        created_at: Utc::now(),
-        is_featured: false,
+        max_supply: payload.max_supply.map(|max_supply| max_supply as i64),
     };
-
-    let response = ApiResponse {
-        success: true,
-        data: Some(collection),
-        message: "Collection created successfully".to_string(),
+    let collection = match create_collection(&pool, new_collection).await {
+        Ok(collection) => collection,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create collection: {}", e)),
     };
 
-    (StatusCode::CREATED, Json(response))
+    match get_collection_with_stats(&pool, &collection.id).await {
+        Ok(Some(collection)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(Collection::from(collection)),
+                message: "Collection created successfully".to_string(),
+            };
+            (StatusCode::CREATED, Json(response))
+        }
+        Ok(None) => error_response(StatusCode::INTERNAL_SERVER_ERROR, "Created collection could not be loaded".to_string()),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load collection: {}", e)),
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PaginatedResponse<T> {
-    pub data: Vec<T>,
-    pub total: u64,
-    pub page: u64,
-    pub limit: u64,
+pub async fn update_collection_handler(
+    State(pool): State<DbPool>,
+    session: AuthSession,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateCollectionRequest>,
+) -> impl IntoResponse {
+    // Moderators may tidy up any collection's details
+    let collection = match load_owned_collection(&pool, &session, &id, Role::Moderator).await {
+        Ok(collection) => collection,
+        Err((status, message)) => return error_response(status, message),
+    };
+
+    let name = payload.name.map(|name| name.trim().to_string());
+    if name.as_deref() == Some("") {
+        return error_response(StatusCode::BAD_REQUEST, "Collection name must not be empty".to_string());
+    }
+
+    // Token IDs already handed out, failed mints included, stay within the max supply
+    let issued = (collection.next_token_id - 1) as u64;
+    if payload.max_supply.is_some_and(|max_supply| max_supply == 0 || max_supply < issued) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Max supply must be at least 1 and no lower than the {} token IDs already issued", issued),
+        );
+    }
+
+    let changes = CollectionChanges {
+        name,
+        description: payload.description,
+        image_url: payload.image_url,
+        banner_url: payload.banner_url,
+        max_supply: payload.max_supply.map(|max_supply| max_supply as i64),
+    };
+    let collection = match update_collection(&pool, collection, changes).await {
+        Ok(collection) => collection,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to update collection: {}", e)),
+    };
+
+    match get_collection_with_stats(&pool, &collection.id).await {
+        Ok(Some(collection)) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(Collection::from(collection)),
+                message: "Collection updated successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Collection not found".to_string()),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load collection: {}", e)),
+    }
 }
 
-use serde::Serialize; 
\ No newline at end of file
+pub async fn delete_collection_handler(
+    State(pool): State<DbPool>,
+    session: AuthSession,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let collection = match load_owned_collection(&pool, &session, &id, Role::Admin).await {
+        Ok(collection) => collection,
+        Err((status, message)) => return error_response::<()>(status, message),
+    };
+
+    match delete_collection(&pool, &collection.id).await {
+        Ok(()) => {
+            let response = ApiResponse::<()> {
+                success: true,
+                data: None,
+                message: "Collection deleted successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to delete collection: {}", e)),
+    }
+}
@@ -1,2 +1,3 @@
 pub mod handlers;
-pub mod types; 
\ No newline at end of file
+pub mod slug;
+pub mod types;
@@ -0,0 +1,40 @@
+/// Turn a collection name into a URL-safe slug: lowercase ASCII words joined by dashes
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "collection".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Whether a client-supplied slug is already in the form `slugify` produces
+pub fn is_valid_slug(slug: &str) -> bool {
+    !slug.is_empty() && slugify(slug) == slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Ethereal Dreams"), "ethereal-dreams");
+        assert_eq!(slugify("  Fire & Ice!! 2 "), "fire-ice-2");
+        assert_eq!(slugify("ÆON"), "on");
+        assert_eq!(slugify("???"), "collection");
+
+        assert!(is_valid_slug("fire-ice-2"));
+        assert!(!is_valid_slug("Fire-Ice"));
+        assert!(!is_valid_slug("fire--ice"));
+    }
+}
@@ -1,44 +1,71 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use crate::db_operations::CollectionWithStats;
+use crate::wei::Wei;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Collection {
     pub id: String,
     pub name: String,
-    pub description: String,
+    pub slug: String,
+    pub description: Option<String>,
     pub image_url: Option<String>,
     pub banner_url: Option<String>,
     pub creator_wallet: String,
+    pub max_supply: Option<u64>,
+    /// NFTs in circulation; burning one moves it to `burned_count`
     pub nft_count: u64,
+    pub burned_count: u64,
     pub unique_owners: u64,
-    pub total_volume: f64,
-    pub floor_price: Option<f64>,
+    /// Gas fees paid to mint into the collection, in wei
+    pub total_volume: Wei,
+    pub floor_price: Option<Wei>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
     pub is_featured: bool,
 }
 
+impl From<CollectionWithStats> for Collection {
+    fn from(collection: CollectionWithStats) -> Self {
+        Collection {
+            id: collection.id,
+            name: collection.name,
+            slug: collection.slug,
+            description: collection.description,
+            image_url: collection.image_url,
+            banner_url: collection.banner_url,
+            creator_wallet: collection.creator_wallet,
+            max_supply: collection.max_supply.map(|max_supply| max_supply as u64),
+            nft_count: collection.nft_count as u64,
+            burned_count: collection.burned_count as u64,
+            unique_owners: collection.unique_owners as u64,
+            total_volume: collection.volume,
+            // Nothing is listed for sale on the platform yet
+            floor_price: None,
+            created_at: DateTime::from_naive_utc_and_offset(collection.created_at, Utc),
+            updated_at: DateTime::from_naive_utc_and_offset(collection.updated_at, Utc),
+            is_featured: false,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateCollectionRequest {
     pub name: String,
-    pub description: String,
+    /// Derived from `name` when omitted
+    pub slug: Option<String>,
+    pub description: Option<String>,
     pub image_url: Option<String>,
     pub banner_url: Option<String>,
     pub creator_wallet: String,
+    pub max_supply: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct CollectionStats {
-    pub total_nfts: u64,
-    pub unique_owners: u64,
-    pub total_volume: f64,
-    pub floor_price: Option<f64>,
-    pub avg_price: Option<f64>,
-    pub minting_trends: Vec<MintingTrend>,
+pub struct UpdateCollectionRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub max_supply: Option<u64>,
 }
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MintingTrend {
-    pub date: String,
-    pub count: u64,
-    pub volume: f64,
-} 
\ No newline at end of file
@@ -0,0 +1,119 @@
+use anyhow::{anyhow, Result};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+/// Keccak-256 as used throughout Ethereum (not the NIST SHA3-256 variant)
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Format a 20-byte address with the EIP-55 mixed-case checksum
+pub fn to_checksum_address(address: &[u8; 20]) -> String {
+    let lower = hex::encode(address);
+    let hash = keccak256(lower.as_bytes());
+
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if c.is_ascii_alphabetic() && nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    format!("0x{}", checksummed)
+}
+
+/// Parse a `0x`-prefixed hex address, enforcing the EIP-55 checksum when mixed case is used
+pub fn parse_address(address: &str) -> Result<[u8; 20]> {
+    let digits = address
+        .strip_prefix("0x")
+        .ok_or_else(|| anyhow!("Address must start with 0x"))?;
+    let bytes: [u8; 20] = hex::decode(digits)?
+        .try_into()
+        .map_err(|_| anyhow!("Address must be 20 bytes"))?;
+
+    let is_mixed_case = digits.chars().any(|c| c.is_ascii_lowercase()) && digits.chars().any(|c| c.is_ascii_uppercase());
+    if is_mixed_case && to_checksum_address(&bytes) != address {
+        return Err(anyhow!("Address checksum is invalid"));
+    }
+
+    Ok(bytes)
+}
+
+/// Hash a message the way `personal_sign` does (EIP-191 version 0x45)
+pub fn eip191_hash(message: &str) -> [u8; 32] {
+    let prefixed = format!("\x19Ethereum Signed Message:\n{}{}", message.len(), message);
+    keccak256(prefixed.as_bytes())
+}
+
+/// Recover the address that produced a 65-byte `personal_sign` signature over `message`
+pub fn recover_personal_sign_address(message: &str, signature: &str) -> Result<[u8; 20]> {
+    let bytes = hex::decode(signature.trim_start_matches("0x"))?;
+    if bytes.len() != 65 {
+        return Err(anyhow!("Signature must be 65 bytes"));
+    }
+
+    let signature = Signature::from_slice(&bytes[..64])?;
+    let v = match bytes[64] {
+        27 | 28 => bytes[64] - 27,
+        0 | 1 => bytes[64],
+        other => return Err(anyhow!("Invalid signature recovery id: {}", other)),
+    };
+    let mut recovery_id = RecoveryId::from_byte(v).ok_or_else(|| anyhow!("Invalid signature recovery id"))?;
+
+    // Wallets may hand us a high-S signature; normalising it flips the y parity
+    let signature = match signature.normalize_s() {
+        Some(normalized) => {
+            recovery_id = RecoveryId::new(!recovery_id.is_y_odd(), recovery_id.is_x_reduced());
+            normalized
+        }
+        None => signature,
+    };
+
+    let verifying_key = VerifyingKey::recover_from_prehash(&eip191_hash(message), &signature, recovery_id)?;
+    Ok(public_key_to_address(&verifying_key))
+}
+
+pub fn public_key_to_address(key: &VerifyingKey) -> [u8; 20] {
+    let encoded = key.to_encoded_point(false);
+    let hash = keccak256(&encoded.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    #[test]
+    fn test_checksum_address() {
+        // Test vector from EIP-55
+        let address = parse_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+        assert_eq!(to_checksum_address(&address), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        assert!(parse_address("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+    }
+
+    #[test]
+    fn test_recover_personal_sign_address() {
+        let signing_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let expected = public_key_to_address(signing_key.verifying_key());
+
+        let message = "hello mintverse";
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&eip191_hash(message)).unwrap();
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(recovery_id.to_byte() + 27);
+
+        let recovered = recover_personal_sign_address(message, &format!("0x{}", hex::encode(&bytes))).unwrap();
+        assert_eq!(recovered, expected);
+
+        let other = recover_personal_sign_address("another message", &hex::encode(&bytes)).unwrap();
+        assert_ne!(other, expected);
+    }
+}
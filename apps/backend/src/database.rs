@@ -1,22 +1,78 @@
-use sea_orm::{Database, DatabaseConnection, DbErr};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
+use serde::Serialize;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use crate::env::env_or;
 
-pub type DbPool = Arc<Mutex<DatabaseConnection>>;
+/// A pooled SeaORM connection; the `Arc` only lets handlers share it cheaply, the pooling happens inside
+pub type DbPool = Arc<DatabaseConnection>;
 
-pub async fn establish_connection() -> Result<DatabaseConnection, DbErr> {
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set");
-    
-    Database::connect(&database_url).await
+/// Connection pool sizing and timeouts, read from `DB_*` environment variables
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout_secs: u64,
+    pub acquire_timeout_secs: u64,
+    pub idle_timeout_secs: u64,
+    pub max_lifetime_secs: u64,
 }
 
-pub async fn create_connection_pool() -> Result<DbPool, DbErr> {
-    let connection = establish_connection().await?;
-    Ok(Arc::new(Mutex::new(connection)))
+impl DatabaseConfig {
+    pub fn from_env() -> Self {
+        Self {
+            url: std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+            max_connections: env_or("DB_MAX_CONNECTIONS", 10),
+            min_connections: env_or("DB_MIN_CONNECTIONS", 1),
+            connect_timeout_secs: env_or("DB_CONNECT_TIMEOUT_SECS", 8),
+            acquire_timeout_secs: env_or("DB_ACQUIRE_TIMEOUT_SECS", 8),
+            idle_timeout_secs: env_or("DB_IDLE_TIMEOUT_SECS", 300),
+            max_lifetime_secs: env_or("DB_MAX_LIFETIME_SECS", 1800),
+        }
+    }
+
+    fn connect_options(&self) -> ConnectOptions {
+        let mut options = ConnectOptions::new(self.url.clone());
+        options
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections.min(self.max_connections))
+            .connect_timeout(Duration::from_secs(self.connect_timeout_secs))
+            .acquire_timeout(Duration::from_secs(self.acquire_timeout_secs))
+            .idle_timeout(Duration::from_secs(self.idle_timeout_secs))
+            .max_lifetime(Duration::from_secs(self.max_lifetime_secs))
+            .sqlx_logging(false);
+        options
+    }
+}
+
+pub async fn create_connection_pool(config: &DatabaseConfig) -> Result<DbPool, DbErr> {
+    let connection = Database::connect(config.connect_options()).await?;
+    Ok(Arc::new(connection))
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolStats {
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: u32,
+    pub max_connections: u32,
+}
+
+pub fn pool_stats(pool: &DbPool) -> PoolStats {
+    let sqlx_pool = pool.get_postgres_connection_pool();
+    let size = sqlx_pool.size();
+    let idle = sqlx_pool.num_idle();
+
+    PoolStats {
+        size,
+        idle,
+        in_use: size.saturating_sub(idle as u32),
+        max_connections: sqlx_pool.options().get_max_connections(),
+    }
 }
 
 pub async fn health_check(pool: &DbPool) -> Result<(), DbErr> {
-    let conn = pool.lock().await;
-    conn.ping().await
-} 
\ No newline at end of file
+    pool.ping().await
+}
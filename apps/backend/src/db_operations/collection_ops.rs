@@ -0,0 +1,447 @@
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, Func};
+use crate::entities::{Collection, CollectionModel, Nft, collection, nft};
+use crate::collections::slug::slugify;
+use crate::wei::Wei;
+use super::stats_ops::FEE_VOLUME_WEI;
+use anyhow::Result;
+
+/// Advisory lock key serialising token ID allocation for NFTs outside any collection
+const UNCOLLECTED_TOKEN_IDS_LOCK: &str = "nfts:uncollected:token_ids";
+
+/// A mint that would take a collection's token IDs past its max supply
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupplyExhausted {
+    pub collection_name: String,
+    pub max_supply: u64,
+    /// Token IDs already handed out, failed mints included
+    pub issued: u64,
+}
+
+impl std::fmt::Display for SupplyExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Collection {} has issued {} of the {} token IDs its max supply allows",
+            self.collection_name, self.issued, self.max_supply
+        )
+    }
+}
+
+impl std::error::Error for SupplyExhausted {}
+
+pub struct NewCollection {
+    pub creator_id: String,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub max_supply: Option<i64>,
+}
+
+/// Fields of a collection that may be changed after creation; `None` leaves a field untouched
+pub struct CollectionChanges {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub max_supply: Option<i64>,
+}
+
+/// A collection with its creator's wallet and aggregates over its NFTs
+#[derive(Debug, FromQueryResult)]
+pub struct CollectionWithStats {
+    pub id: String,
+    pub creator_wallet: String,
+    pub name: String,
+    pub slug: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub max_supply: Option<i64>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub nft_count: i64,
+    pub burned_count: i64,
+    pub unique_owners: i64,
+    pub volume: Wei,
+}
+
+fn collection_stats_sql(filter: &str) -> String {
+    format!(
+        r#"SELECT c.id, u.public_key AS creator_wallet, c.name, c.slug, c.description,
+                  c.image_url, c.banner_url, c.max_supply, c.created_at, c.updated_at,
+                  COUNT(n.id) FILTER (WHERE n.burned_at IS NULL) AS nft_count,
+                  COUNT(n.id) FILTER (WHERE n.burned_at IS NOT NULL) AS burned_count,
+                  COUNT(DISTINCT n.owner_id) FILTER (WHERE n.burned_at IS NULL) AS unique_owners,
+                  {volume} AS volume
+           FROM collections c
+           JOIN users u ON u.id = c.creator_id
+           LEFT JOIN nfts n ON n.collection_id = c.id AND n.mint_status <> 'Failed'
+           LEFT JOIN mint_jobs j ON j.nft_id = n.id
+           {filter}
+           GROUP BY c.id, u.public_key"#,
+        volume = FEE_VOLUME_WEI,
+        filter = filter,
+    )
+}
+
+pub async fn create_collection(pool: &DatabaseConnection, new_collection: NewCollection) -> Result<CollectionModel> {
+    let now = chrono::Utc::now().naive_utc();
+    let collection = CollectionModel {
+        id: cuid::cuid2(),
+        creator_id: new_collection.creator_id,
+        name: new_collection.name,
+        slug: new_collection.slug,
+        description: new_collection.description,
+        image_url: new_collection.image_url,
+        banner_url: new_collection.banner_url,
+        max_supply: new_collection.max_supply,
+        created_at: now,
+        updated_at: now,
+        next_token_id: 1,
+    };
+
+    let result = collection.into_active_model().insert(pool).await?;
+    Ok(result)
+}
+
+/// Look a collection up by id or slug
+pub async fn find_collection(pool: &DatabaseConnection, id_or_slug: &str) -> Result<Option<CollectionModel>> {
+    let collection = Collection::find()
+        .filter(
+            Condition::any()
+                .add(collection::Column::Id.eq(id_or_slug))
+                .add(collection::Column::Slug.eq(id_or_slug)),
+        )
+        .one(pool)
+        .await?;
+
+    Ok(collection)
+}
+
+pub async fn slug_exists(pool: &DatabaseConnection, slug: &str) -> Result<bool> {
+    let count = Collection::find()
+        .filter(collection::Column::Slug.eq(slug))
+        .count(pool)
+        .await?;
+
+    Ok(count > 0)
+}
+
+/// Resolve a free-text collection name from a mint request, creating the collection on first use
+pub async fn find_or_create_collection_by_name(pool: &DatabaseConnection, name: &str, creator_id: &str) -> Result<CollectionModel> {
+    // Concurrent mints into the same new name must not create it twice
+    let txn = pool.begin().await?;
+    txn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock(hashtext(lower($1)))",
+        [name.into()],
+    ))
+    .await?;
+
+    let existing = Collection::find()
+        .filter(Expr::expr(Func::lower(Expr::col(collection::Column::Name))).eq(name.to_lowercase()))
+        .order_by_asc(collection::Column::CreatedAt)
+        .one(&txn)
+        .await?;
+    if let Some(collection) = existing {
+        txn.commit().await?;
+        return Ok(collection);
+    }
+
+    // Pick the first free slug, suffixing a counter when another name already slugifies the same way
+    let base_slug = slugify(name);
+    let taken: Vec<String> = Collection::find()
+        .select_only()
+        .column(collection::Column::Slug)
+        .filter(collection::Column::Slug.starts_with(&base_slug))
+        .into_tuple()
+        .all(&txn)
+        .await?;
+    let slug = std::iter::once(base_slug.clone())
+        .chain((2..).map(|n| format!("{}-{}", base_slug, n)))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or(base_slug);
+
+    let now = chrono::Utc::now().naive_utc();
+    let collection = CollectionModel {
+        id: cuid::cuid2(),
+        creator_id: creator_id.to_string(),
+        name: name.to_string(),
+        slug,
+        description: None,
+        image_url: None,
+        banner_url: None,
+        max_supply: None,
+        created_at: now,
+        updated_at: now,
+        next_token_id: 1,
+    };
+
+    let result = collection.into_active_model().insert(&txn).await?;
+    txn.commit().await?;
+    Ok(result)
+}
+
+pub async fn get_collections_with_stats(
+    pool: &DatabaseConnection,
+    limit: u64,
+    offset: u64,
+) -> Result<(Vec<CollectionWithStats>, u64)> {
+    let sql = format!("{} ORDER BY c.created_at DESC, c.id LIMIT $1 OFFSET $2", collection_stats_sql(""));
+    let collections = CollectionWithStats::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        [(limit as i64).into(), (offset as i64).into()],
+    ))
+    .all(pool)
+    .await?;
+
+    let total = Collection::find().count(pool).await?;
+
+    Ok((collections, total))
+}
+
+/// Look a collection up with its aggregates by id or slug
+pub async fn get_collection_with_stats(pool: &DatabaseConnection, id_or_slug: &str) -> Result<Option<CollectionWithStats>> {
+    let collection = CollectionWithStats::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        collection_stats_sql("WHERE c.id = $1 OR c.slug = $1"),
+        [id_or_slug.into()],
+    ))
+    .one(pool)
+    .await?;
+
+    Ok(collection)
+}
+
+/// Take the next `count` token IDs of a collection, or of the NFTs outside any collection, in order.
+///
+/// Must run inside the database transaction that inserts the NFTs: the collection's row, or an
+/// advisory lock for NFTs outside any collection, stays locked until it ends, so concurrent mints
+/// take consecutive IDs, and a mint that is rolled back hands its IDs back. Unique indexes on
+/// `(collection_id, token_id)` and on uncollected token IDs back the locks up.
+///
+/// A collection's token IDs never go past its max supply; fails with [`SupplyExhausted`] when
+/// there isn't room. The ID of a mint that failed on chain isn't handed out again, so failures use
+/// up supply.
+pub async fn allocate_token_ids<C: ConnectionTrait>(db: &C, collection_id: Option<&str>, count: u64) -> Result<Vec<String>> {
+    let first = match collection_id {
+        Some(collection_id) => {
+            let collection = Collection::update_many()
+                .col_expr(
+                    collection::Column::NextTokenId,
+                    Expr::col(collection::Column::NextTokenId).add(count as i64),
+                )
+                .filter(collection::Column::Id.eq(collection_id))
+                .exec_with_returning(db)
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow::anyhow!("Collection {} not found", collection_id))?;
+
+            // The counter comes back already advanced past the IDs taken
+            let issued = (collection.next_token_id - 1) as u64 - count;
+            if let Some(max_supply) = collection.max_supply {
+                if issued + count > max_supply as u64 {
+                    return Err(SupplyExhausted {
+                        collection_name: collection.name,
+                        max_supply: max_supply as u64,
+                        issued,
+                    }
+                    .into());
+                }
+            }
+            collection.next_token_id - count as i64
+        }
+        None => {
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT pg_advisory_xact_lock(hashtext($1))",
+                [UNCOLLECTED_TOKEN_IDS_LOCK.into()],
+            ))
+            .await?;
+            let last = db
+                .query_one(Statement::from_string(
+                    DbBackend::Postgres,
+                    "SELECT MAX(token_id::bigint) AS last_token_id FROM nfts
+                     WHERE collection_id IS NULL AND token_id ~ '^[0-9]+$'",
+                ))
+                .await?
+                .map(|row| row.try_get::<Option<i64>>("", "last_token_id"))
+                .transpose()?
+                .flatten();
+            last.unwrap_or(0) + 1
+        }
+    };
+
+    Ok((first..first + count as i64).map(|token_id| token_id.to_string()).collect())
+}
+
+/// Apply `changes`, keeping the denormalised `nfts.collection_name` in step with a rename
+pub async fn update_collection(
+    pool: &DatabaseConnection,
+    collection: CollectionModel,
+    changes: CollectionChanges,
+) -> Result<CollectionModel> {
+    let txn = pool.begin().await?;
+
+    let collection_id = collection.id.clone();
+    let mut collection_active = collection.into_active_model();
+    if let Some(name) = changes.name.clone() {
+        collection_active.name = Set(name);
+    }
+    if let Some(description) = changes.description {
+        collection_active.description = Set(Some(description));
+    }
+    if let Some(image_url) = changes.image_url {
+        collection_active.image_url = Set(Some(image_url));
+    }
+    if let Some(banner_url) = changes.banner_url {
+        collection_active.banner_url = Set(Some(banner_url));
+    }
+    if let Some(max_supply) = changes.max_supply {
+        collection_active.max_supply = Set(Some(max_supply));
+    }
+    collection_active.updated_at = Set(chrono::Utc::now().naive_utc());
+    let result = collection_active.update(&txn).await?;
+
+    if let Some(name) = changes.name {
+        Nft::update_many()
+            .col_expr(nft::Column::CollectionName, Expr::value(name))
+            .filter(nft::Column::CollectionId.eq(&collection_id))
+            .exec(&txn)
+            .await?;
+    }
+
+    txn.commit().await?;
+    Ok(result)
+}
+
+/// Delete a collection; its NFTs stay with their owners but no longer belong to a collection.
+///
+/// They join the NFTs outside any collection, so they take the next of their token IDs, in mint
+/// order. Transaction logs keep the IDs the NFTs were minted with.
+pub async fn delete_collection(pool: &DatabaseConnection, collection_id: &str) -> Result<()> {
+    let txn = pool.begin().await?;
+
+    let orphaned = Nft::find()
+        .filter(nft::Column::CollectionId.eq(collection_id))
+        .count(&txn)
+        .await?;
+    if orphaned > 0 {
+        let first: i64 = allocate_token_ids(&txn, None, orphaned).await?[0].parse()?;
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE nfts n
+             SET token_id = ($2 + r.position - 1)::text, collection_id = NULL, collection_name = NULL
+             FROM (
+                 SELECT id, ROW_NUMBER() OVER (ORDER BY minted_at, id) AS position
+                 FROM nfts WHERE collection_id = $1
+             ) r
+             WHERE n.id = r.id",
+            [collection_id.into(), first.into()],
+        ))
+        .await?;
+    }
+    Collection::delete_by_id(collection_id).exec(&txn).await?;
+
+    txn.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn collection(next_token_id: i64, max_supply: Option<i64>) -> CollectionModel {
+        let now = chrono::Utc::now().naive_utc();
+        CollectionModel {
+            id: "collection_1".to_string(),
+            creator_id: "user_1".to_string(),
+            name: "Dreams".to_string(),
+            slug: "dreams".to_string(),
+            description: None,
+            image_url: None,
+            banner_url: None,
+            max_supply,
+            created_at: now,
+            updated_at: now,
+            next_token_id,
+        }
+    }
+
+    fn count_row(count: i64) -> BTreeMap<&'static str, Value> {
+        BTreeMap::from([("num_items", Value::BigInt(Some(count)))])
+    }
+
+    #[tokio::test]
+    async fn test_allocate_collection_token_ids() {
+        // The counter comes back already advanced past the IDs taken
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![collection(8, Some(10))]])
+            .append_query_results([vec![collection(11, Some(10))]])
+            .into_connection();
+
+        let token_ids = allocate_token_ids(&db, Some("collection_1"), 3).await.unwrap();
+        assert_eq!(token_ids, ["5", "6", "7"]);
+        // The last ID the max supply allows
+        assert_eq!(allocate_token_ids(&db, Some("collection_1"), 1).await.unwrap(), ["10"]);
+    }
+
+    #[tokio::test]
+    async fn test_allocate_past_max_supply() {
+        // IDs 1 to 9 were issued, some of them to mints that failed, and don't come back
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![collection(12, Some(10))]])
+            .into_connection();
+
+        let error = allocate_token_ids(&db, Some("collection_1"), 2).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<SupplyExhausted>(),
+            Some(&SupplyExhausted { collection_name: "Dreams".to_string(), max_supply: 10, issued: 9 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_allocate_uncollected_token_ids() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([
+                vec![BTreeMap::from([("last_token_id", Value::BigInt(Some(41)))])],
+                vec![BTreeMap::from([("last_token_id", Value::BigInt(None))])],
+            ])
+            .append_exec_results((0..2).map(|_| MockExecResult { last_insert_id: 0, rows_affected: 1 }))
+            .into_connection();
+
+        // IDs follow the highest one taken, or start at 1, with the advisory lock taken first
+        assert_eq!(allocate_token_ids(&db, None, 2).await.unwrap(), ["42", "43"]);
+        assert_eq!(allocate_token_ids(&db, None, 1).await.unwrap(), ["1"]);
+        let log = db.into_transaction_log();
+        assert!(log[0].statements()[0].sql.contains("pg_advisory_xact_lock"));
+        assert!(log[1].statements()[0].sql.contains("MAX(token_id::bigint)"));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_collection_nfts_take_uncollected_token_ids() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // Three NFTs in the collection, and uncollected ones up to token 41
+            .append_query_results([vec![count_row(3)]])
+            .append_query_results([vec![BTreeMap::from([("last_token_id", Value::BigInt(Some(41)))])]])
+            .append_exec_results((0..3).map(|_| MockExecResult { last_insert_id: 0, rows_affected: 3 }))
+            .into_connection();
+
+        delete_collection(&db, "collection_1").await.unwrap();
+        let statements: Vec<Statement> = db
+            .into_transaction_log()
+            .into_iter()
+            .flat_map(|transaction| transaction.statements().to_vec())
+            .collect();
+        let position = |prefix: &str| statements.iter().position(|statement| statement.sql.starts_with(prefix)).unwrap();
+        let renumbered = &statements[position("UPDATE nfts n")];
+        assert_eq!(renumbered.values.as_ref().unwrap().0, [Value::from("collection_1"), Value::from(42i64)]);
+        // The NFTs are renumbered before their collection goes
+        assert!(position("UPDATE nfts n") < position(r#"DELETE FROM "collections""#));
+    }
+}
@@ -0,0 +1,134 @@
+use sea_orm::*;
+use sea_orm::sea_query::{Expr, OnConflict};
+use crate::entities::{IdempotencyKey, IdempotencyKeyModel, idempotency_key};
+use anyhow::Result;
+
+/// Outcome of claiming an idempotency key for a new request
+#[derive(Debug)]
+pub enum IdempotencyClaim {
+    /// The key was free; the caller handles the request and records its response
+    Claimed,
+    /// An earlier request holds the key and hasn't expired
+    Existing(IdempotencyKeyModel),
+}
+
+/// Claim `key` for a request with the given fingerprint, unless an unexpired request already holds it.
+///
+/// A claim whose response was never recorded, as when the server stopped mid-request, only holds
+/// the key for `lease`; after that the key may be claimed again.
+pub async fn claim_idempotency_key(
+    pool: &DatabaseConnection,
+    user_id: &str,
+    key: &str,
+    request_hash: String,
+    expires_at: chrono::NaiveDateTime,
+    lease: chrono::Duration,
+) -> Result<IdempotencyClaim> {
+    let now = chrono::Utc::now().naive_utc();
+
+    // Expired keys, and claims whose lease ran out, may be reused
+    IdempotencyKey::delete_many()
+        .filter(
+            Condition::any()
+                .add(idempotency_key::Column::ExpiresAt.lte(now))
+                .add(
+                    Condition::all()
+                        .add(idempotency_key::Column::StatusCode.is_null())
+                        .add(idempotency_key::Column::CreatedAt.lte(now - lease)),
+                ),
+        )
+        .exec(pool)
+        .await?;
+
+    let claim = IdempotencyKeyModel {
+        user_id: user_id.to_string(),
+        key: key.to_string(),
+        request_hash,
+        status_code: None,
+        response: None,
+        created_at: now,
+        expires_at,
+    };
+    // Concurrent requests with the same key race on the primary key; only one inserts
+    let inserted = IdempotencyKey::insert(claim.into_active_model())
+        .on_conflict(
+            OnConflict::columns([idempotency_key::Column::UserId, idempotency_key::Column::Key])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(pool)
+        .await?;
+    if inserted == 1 {
+        return Ok(IdempotencyClaim::Claimed);
+    }
+
+    let existing = IdempotencyKey::find_by_id((user_id.to_string(), key.to_string()))
+        .one(pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Idempotency key {} was released while being claimed", key))?;
+    Ok(IdempotencyClaim::Existing(existing))
+}
+
+/// Store the response to replay for later requests with the key
+pub async fn complete_idempotency_key(
+    pool: &DatabaseConnection,
+    user_id: &str,
+    key: &str,
+    status_code: u16,
+    response: serde_json::Value,
+) -> Result<()> {
+    IdempotencyKey::update_many()
+        .col_expr(idempotency_key::Column::StatusCode, Expr::value(status_code as i16))
+        .col_expr(idempotency_key::Column::Response, Expr::value(response))
+        .filter(idempotency_key::Column::UserId.eq(user_id))
+        .filter(idempotency_key::Column::Key.eq(key))
+        .exec(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Free a key whose request failed in a way worth retrying
+pub async fn release_idempotency_key(pool: &DatabaseConnection, user_id: &str, key: &str) -> Result<()> {
+    IdempotencyKey::delete_many()
+        .filter(idempotency_key::Column::UserId.eq(user_id))
+        .filter(idempotency_key::Column::Key.eq(key))
+        .exec(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_claim_reuses_expired_keys_and_lapsed_leases() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([
+                MockExecResult { last_insert_id: 0, rows_affected: 1 },
+                MockExecResult { last_insert_id: 0, rows_affected: 1 },
+            ])
+            .into_connection();
+
+        let expires_at = chrono::Utc::now().naive_utc() + chrono::Duration::hours(24);
+        let claim = claim_idempotency_key(&db, "user_1", "key_1", "hash".to_string(), expires_at, chrono::Duration::minutes(5))
+            .await
+            .unwrap();
+        assert!(matches!(claim, IdempotencyClaim::Claimed));
+
+        let cleanup = db.into_transaction_log()[0].statements()[0].clone();
+        assert!(
+            cleanup.sql.ends_with(r#"WHERE "idempotency_keys"."expires_at" <= $1 OR ("idempotency_keys"."status_code" IS NULL AND "idempotency_keys"."created_at" <= $2)"#),
+            "{}",
+            cleanup.sql
+        );
+        // The lease runs from when the claim was made
+        let values = cleanup.values.unwrap().0;
+        let (Value::ChronoDateTime(Some(now)), Value::ChronoDateTime(Some(leased_since))) = (&values[0], &values[1]) else {
+            panic!("unexpected values {:?}", values);
+        };
+        assert_eq!(**now - **leased_since, chrono::Duration::minutes(5));
+    }
+}
@@ -0,0 +1,149 @@
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use crate::entities::{MintJob, MintJobModel, Nft, User, mint_job, nft};
+use crate::blockchain_sim::{MintingStatus, TransactionDetails};
+use anyhow::Result;
+
+pub async fn create_mint_job<C: ConnectionTrait>(
+    db: &C,
+    id: String,
+    nft_id: String,
+    status: String,
+    transaction: &TransactionDetails,
+    batch_id: Option<String>,
+) -> Result<MintJobModel> {
+    let now = chrono::Utc::now().naive_utc();
+    let job = MintJobModel {
+        id,
+        nft_id,
+        status,
+        transaction_hash: transaction.transaction_hash.clone(),
+        block_number: transaction.block_number.map(|block| block as i64),
+        gas_used: transaction.gas_used as i64,
+        gas_price: transaction.gas_price,
+        confirmations: transaction.confirmations as i32,
+        created_at: now,
+        updated_at: now,
+        confirmed_at: None,
+        simulated_failure: transaction.simulated_failure.map(|failure| failure.as_str().to_string()),
+        revert_reason: None,
+        max_fee_per_gas: transaction.max_fee_per_gas,
+        max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+        from_address: transaction.from.clone(),
+        nonce: transaction.nonce.map(|nonce| nonce as i64),
+        cancelled: transaction.cancellation,
+        batch_id,
+    };
+
+    let job_active = job.clone().into_active_model();
+    let result = job_active.insert(db).await?;
+
+    Ok(result)
+}
+
+pub async fn find_mint_job_by_id(pool: &DatabaseConnection, id: &str) -> Result<Option<MintJobModel>> {
+    let job = MintJob::find_by_id(id)
+        .one(pool)
+        .await?;
+
+    Ok(job)
+}
+
+/// Jobs the confirmation worker still has to drive to a final state, with the owner's wallet
+pub async fn find_unfinished_mint_jobs(pool: &DatabaseConnection, statuses: &[&str]) -> Result<Vec<(MintJobModel, String)>> {
+    let jobs = MintJob::find()
+        .filter(mint_job::Column::Status.is_in(statuses.iter().copied()))
+        .order_by_asc(mint_job::Column::CreatedAt)
+        .all(pool)
+        .await?;
+
+    with_owner_wallets(pool, jobs).await
+}
+
+/// Jobs sent in any of the given transactions, with their owners' wallet addresses
+pub async fn find_mint_jobs_by_transaction_hashes(pool: &DatabaseConnection, hashes: &[String]) -> Result<Vec<(MintJobModel, String)>> {
+    let jobs = MintJob::find()
+        .filter(mint_job::Column::TransactionHash.is_in(hashes.iter().cloned()))
+        .order_by_asc(mint_job::Column::CreatedAt)
+        .all(pool)
+        .await?;
+
+    with_owner_wallets(pool, jobs).await
+}
+
+/// Jobs minted by a batch request, in the order they were queued
+pub async fn find_mint_jobs_by_batch_id(pool: &DatabaseConnection, batch_id: &str) -> Result<Vec<MintJobModel>> {
+    let jobs = MintJob::find()
+        .filter(mint_job::Column::BatchId.eq(batch_id))
+        .order_by_asc(mint_job::Column::CreatedAt)
+        .order_by_asc(mint_job::Column::Id)
+        .all(pool)
+        .await?;
+
+    Ok(jobs)
+}
+
+/// Pair each job with its NFT owner's wallet, skipping jobs whose NFT or owner is gone
+async fn with_owner_wallets(pool: &DatabaseConnection, jobs: Vec<MintJobModel>) -> Result<Vec<(MintJobModel, String)>> {
+    let nft_ids: Vec<String> = jobs.iter().map(|job| job.nft_id.clone()).collect();
+    let owners: std::collections::HashMap<String, String> = Nft::find()
+        .filter(nft::Column::Id.is_in(nft_ids))
+        .find_also_related(User)
+        .all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|(nft, user)| user.map(|user| (nft.id, user.public_key)))
+        .collect();
+
+    let results = jobs
+        .into_iter()
+        .filter_map(|job| {
+            let wallet = owners.get(&job.nft_id)?.clone();
+            Some((job, wallet))
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Persist a mint's progress, keeping its NFT's mint status in step
+pub async fn update_mint_job(pool: &DatabaseConnection, mint: &MintingStatus) -> Result<()> {
+    let tx = mint.transaction_details.as_ref();
+    let confirmed_at = mint
+        .confirmed_at
+        .and_then(|at| chrono::DateTime::from_timestamp(at as i64, 0))
+        .map(|at| at.naive_utc());
+    let mut job = mint_job::ActiveModel {
+        id: Set(mint.mint_id.clone()),
+        status: Set(mint.status.as_str().to_string()),
+        block_number: Set(tx.and_then(|tx| tx.block_number).map(|block| block as i64)),
+        confirmations: Set(tx.map_or(0, |tx| tx.confirmations as i32)),
+        confirmed_at: Set(confirmed_at),
+        revert_reason: Set(tx.and_then(|tx| tx.revert_reason.clone())),
+        updated_at: Set(chrono::Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    // A replacement changes which transaction the job follows
+    if let Some(tx) = tx {
+        job.transaction_hash = Set(tx.transaction_hash.clone());
+        job.gas_used = Set(tx.gas_used as i64);
+        job.gas_price = Set(tx.gas_price);
+        job.max_fee_per_gas = Set(tx.max_fee_per_gas);
+        job.max_priority_fee_per_gas = Set(tx.max_priority_fee_per_gas);
+        job.simulated_failure = Set(tx.simulated_failure.map(|failure| failure.as_str().to_string()));
+        job.cancelled = Set(tx.cancellation);
+    }
+    let job = job.update(pool).await?;
+
+    let mut nft_update = Nft::update_many().col_expr(nft::Column::MintStatus, Expr::value(mint.status.as_str()));
+    // The NFT points at the transaction that mints it, which a cancellation doesn't
+    if let Some(tx) = tx.filter(|tx| !tx.cancellation) {
+        nft_update = nft_update.col_expr(nft::Column::TransactionHash, Expr::value(tx.transaction_hash.clone()));
+    }
+    nft_update
+        .filter(nft::Column::Id.eq(job.nft_id))
+        .exec(pool)
+        .await?;
+
+    Ok(())
+}
@@ -1,5 +1,19 @@
 pub mod user_ops;
 pub mod nft_ops;
+pub mod mint_job_ops;
+pub mod session_ops;
+pub mod stats_ops;
+pub mod collection_ops;
+pub mod transaction_ops;
+pub mod transfer_ops;
+pub mod idempotency_ops;
 
 pub use user_ops::*;
-pub use nft_ops::*; 
\ No newline at end of file
+pub use nft_ops::*;
+pub use mint_job_ops::*;
+pub use session_ops::*;
+pub use stats_ops::*;
+pub use collection_ops::*;
+pub use transaction_ops::*;
+pub use transfer_ops::*;
+pub use idempotency_ops::*;
@@ -1,103 +1,288 @@
 use sea_orm::*;
-use crate::entities::{Nft, NftModel, nft};
-use crate::database::DbPool;
-use anyhow::Result;
-
-pub async fn create_nft(
-    pool: &DbPool,
-    token_id: String,
-    name: String,
-    description: Option<String>,
-    image: String,
-    owner_id: String,
-    transaction_hash: Option<String>,
-    attributes: Option<serde_json::Value>,
-    collection_name: Option<String>,
-) -> Result<NftModel> {
-    let conn = pool.lock().await;
-    
+use sea_orm::sea_query::{Expr, Func, Query};
+use crate::entities::{Collection, CollectionModel, Nft, NftModel, User, UserModel, collection, nft, user};
+use crate::blockchain_sim::MintStatus;
+use crate::pagination::Cursor;
+use anyhow::{bail, Result};
+
+pub struct NewNft {
+    pub token_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub image: String,
+    pub owner_id: String,
+    pub transaction_hash: Option<String>,
+    pub attributes: Option<serde_json::Value>,
+    pub collection: Option<CollectionModel>,
+}
+
+pub async fn create_nft<C: ConnectionTrait>(db: &C, new_nft: NewNft) -> Result<NftModel> {
     let nft = NftModel {
         id: cuid::cuid2(),
-        token_id,
-        name,
-        description,
-        image,
+        token_id: new_nft.token_id,
+        name: new_nft.name,
+        description: new_nft.description,
+        image: new_nft.image,
         minted_at: chrono::Utc::now().naive_utc(),
-        transaction_hash,
-        owner_id,
-        attributes,
-        collection_name,
+        transaction_hash: new_nft.transaction_hash,
+        owner_id: new_nft.owner_id,
+        attributes: new_nft.attributes,
+        collection_name: new_nft.collection.as_ref().map(|collection| collection.name.clone()),
+        collection_id: new_nft.collection.map(|collection| collection.id),
+        mint_status: MintStatus::Pending.as_str().to_string(),
+        burned_at: None,
     };
 
     let nft_active = nft.clone().into_active_model();
-    let result = nft_active.insert(&*conn).await?;
+    let result = nft_active.insert(db).await?;
     
     Ok(result)
 }
 
-pub async fn find_nft_by_id(pool: &DbPool, id: &str) -> Result<Option<NftModel>> {
-    let conn = pool.lock().await;
-    
+pub async fn find_nft_by_id(pool: &DatabaseConnection, id: &str) -> Result<Option<NftModel>> {
     let nft = Nft::find()
         .filter(nft::Column::Id.eq(id))
-        .one(&*conn)
+        .one(pool)
         .await?;
     
     Ok(nft)
 }
 
-pub async fn get_nfts_with_owner(
-    pool: &DbPool,
-    limit: Option<u64>,
-    offset: Option<u64>,
-) -> Result<Vec<(NftModel, crate::entities::UserModel)>> {
-    let conn = pool.lock().await;
-    
-    let mut query = Nft::find()
-        .inner_join(crate::entities::User);
-    
-    if let Some(limit) = limit {
-        query = query.limit(limit);
+/// The NFT holding `token_id` in a collection
+pub async fn find_nft_by_token_id(pool: &DatabaseConnection, collection_id: &str, token_id: &str) -> Result<Option<NftModel>> {
+    let nft = Nft::find()
+        .filter(nft::Column::CollectionId.eq(collection_id))
+        .filter(nft::Column::TokenId.eq(token_id))
+        .one(pool)
+        .await?;
+
+    Ok(nft)
+}
+
+/// Make `wallet` the NFT's owner, registering the wallet if it has never signed in
+pub async fn set_nft_owner<C: ConnectionTrait>(db: &C, nft_id: &str, wallet: &str) -> Result<()> {
+    let owner = match super::find_user_by_public_key(db, wallet).await? {
+        Some(owner) => owner,
+        None => super::create_user(db, wallet.to_string()).await?,
+    };
+
+    Nft::update_many()
+        .col_expr(nft::Column::OwnerId, Expr::value(owner.id))
+        .filter(nft::Column::Id.eq(nft_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Record when an NFT's burn was confirmed, or clear it when a reorg undid the burn
+pub async fn set_nft_burned<C: ConnectionTrait>(db: &C, nft_id: &str, burned_at: Option<chrono::NaiveDateTime>) -> Result<()> {
+    Nft::update_many()
+        .col_expr(nft::Column::BurnedAt, Expr::value(burned_at))
+        .filter(nft::Column::Id.eq(nft_id))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum NftSort {
+    #[default]
+    Newest,
+    Oldest,
+    Name,
+}
+
+impl NftSort {
+    /// Whether results in this order can be resumed from a `(minted_at, id)` cursor
+    pub fn supports_cursor(&self) -> bool {
+        matches!(self, NftSort::Newest | NftSort::Oldest)
     }
-    
-    if let Some(offset) = offset {
-        query = query.offset(offset);
+}
+
+impl std::str::FromStr for NftSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "newest" => Ok(NftSort::Newest),
+            "oldest" => Ok(NftSort::Oldest),
+            "name" => Ok(NftSort::Name),
+            other => Err(format!("Unknown sort '{}'; expected newest, oldest or name", other)),
+        }
     }
-    
-    let nfts = query.all(&*conn).await?;
-    
-    // For now, let's return just the NFTs and fetch owners separately
-    // This is a simplified approach - in production you'd want to optimize this
-    let mut results = Vec::new();
-    for nft in nfts {
-        if let Some(owner) = crate::entities::User::find_by_id(&nft.owner_id).one(&*conn).await? {
-            results.push((nft, owner));
+}
+
+/// Inclusive bounds on a numeric trait; dates compare as unix seconds
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitRange {
+    pub trait_type: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl TraitRange {
+    /// SQL/JSON path matching an attribute of this trait within the bounds.
+    ///
+    /// `.double()` also reads numbers stored as text by older mints; values that aren't numbers
+    /// raise errors that `@?` treats as no match.
+    fn json_path(&self) -> String {
+        let mut filter = format!("@.trait_type == {}", serde_json::Value::String(self.trait_type.clone()));
+        if let Some(min) = self.min {
+            filter.push_str(&format!(" && @.value.double() >= {}", min));
+        }
+        if let Some(max) = self.max {
+            filter.push_str(&format!(" && @.value.double() <= {}", max));
         }
+        format!("$[*] ? ({})", filter)
     }
-    
-    Ok(results)
 }
 
-pub async fn get_nfts_by_owner(
-    pool: &DbPool,
-    owner_id: &str,
-    limit: Option<u64>,
-    offset: Option<u64>,
-) -> Result<Vec<NftModel>> {
-    let conn = pool.lock().await;
-    
-    let mut query = Nft::find()
-        .filter(nft::Column::OwnerId.eq(owner_id))
-        .order_by_desc(nft::Column::MintedAt);
-    
-    if let Some(limit) = limit {
-        query = query.limit(limit);
+/// The stored forms a trait filter value can match: always the text, and the number or boolean it spells
+fn trait_values(value: &str) -> Vec<serde_json::Value> {
+    let mut values = vec![serde_json::Value::String(value.to_string())];
+    if let Ok(number) = value.parse::<serde_json::Number>() {
+        values.push(serde_json::Value::Number(number));
     }
-    
-    if let Some(offset) = offset {
-        query = query.offset(offset);
+    if let Ok(boolean) = value.parse::<bool>() {
+        values.push(serde_json::Value::Bool(boolean));
     }
-    
-    let results = query.all(&*conn).await?;
-    Ok(results)
-} 
\ No newline at end of file
+    values
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct NftSearch {
+    /// Full-text query over name and description
+    pub text: Option<String>,
+    /// Trait type with the accepted values for it
+    pub traits: Vec<(String, Vec<String>)>,
+    /// Bounds on numeric or date traits
+    pub trait_ranges: Vec<TraitRange>,
+    /// Collection id or slug
+    pub collection: Option<String>,
+    /// Owner wallet address
+    pub owner: Option<String>,
+    pub sort: NftSort,
+    /// List burned NFTs too; they are left out by default
+    pub include_burned: bool,
+}
+
+impl NftSearch {
+    pub fn add_trait(&mut self, trait_type: &str, value: String) {
+        match self.traits.iter_mut().find(|(existing, _)| existing == trait_type) {
+            Some((_, values)) => values.push(value),
+            None => self.traits.push((trait_type.to_string(), vec![value])),
+        }
+    }
+
+    /// Narrow a trait to `min..=max`; a bound left unset keeps any earlier one
+    pub fn add_trait_range(&mut self, trait_type: &str, min: Option<f64>, max: Option<f64>) {
+        match self.trait_ranges.iter_mut().find(|range| range.trait_type == trait_type) {
+            Some(range) => {
+                range.min = min.or(range.min);
+                range.max = max.or(range.max);
+            }
+            None => self.trait_ranges.push(TraitRange { trait_type: trait_type.to_string(), min, max }),
+        }
+    }
+
+    fn condition(&self) -> Condition {
+        // NFTs whose mint failed never made it on chain
+        let mut condition = Condition::all().add(nft::Column::MintStatus.ne(MintStatus::Failed.as_str()));
+        if !self.include_burned {
+            condition = condition.add(nft::Column::BurnedAt.is_null());
+        }
+
+        if let Some(text) = &self.text {
+            // Must match the expression behind idx_nfts_search
+            condition = condition.add(Expr::cust_with_values(
+                r#"to_tsvector('english', "nfts"."name" || ' ' || coalesce("nfts"."description", '')) @@ websearch_to_tsquery('english', $1)"#,
+                [text.clone()],
+            ));
+        }
+
+        for (trait_type, values) in &self.traits {
+            let mut any_value = Condition::any();
+            for value in values.iter().flat_map(|value| trait_values(value)) {
+                let attribute = serde_json::json!([{ "trait_type": trait_type, "value": value }]);
+                any_value = any_value.add(Expr::cust_with_values(r#""nfts"."attributes" @> $1"#, [attribute]));
+            }
+            condition = condition.add(any_value);
+        }
+
+        for range in &self.trait_ranges {
+            condition = condition.add(Expr::cust_with_values(r#""nfts"."attributes" @? $1::jsonpath"#, [range.json_path()]));
+        }
+
+        if let Some(collection) = &self.collection {
+            condition = condition.add(
+                nft::Column::CollectionId.in_subquery(
+                    Query::select()
+                        .column(collection::Column::Id)
+                        .from(Collection)
+                        .cond_where(
+                            Condition::any()
+                                .add(collection::Column::Id.eq(collection.as_str()))
+                                .add(collection::Column::Slug.eq(collection.as_str())),
+                        )
+                        .to_owned(),
+                ),
+            );
+        }
+
+        if let Some(owner) = &self.owner {
+            condition = condition.add(
+                Expr::expr(Func::lower(Expr::col((User, user::Column::PublicKey)))).eq(owner.to_lowercase()),
+            );
+        }
+
+        condition
+    }
+}
+
+/// Search NFTs with their owners, returning up to `limit` rows and the total number of matches.
+///
+/// `after` continues keyset pagination from a row returned by a previous call with the same search.
+pub async fn search_nfts(
+    pool: &DatabaseConnection,
+    search: &NftSearch,
+    limit: u64,
+    offset: u64,
+    after: Option<&Cursor>,
+) -> Result<(Vec<(NftModel, UserModel)>, u64)> {
+    let query = Nft::find()
+        .find_also_related(User)
+        .filter(search.condition());
+    let total = query.clone().count(pool).await?;
+
+    let query = match after {
+        Some(cursor) => {
+            if !search.sort.supports_cursor() {
+                bail!("Cursor pagination is only supported when sorting by mint time");
+            }
+            let comparison = if search.sort == NftSort::Oldest { ">" } else { "<" };
+            query.filter(Expr::cust_with_values(
+                format!(r#"("nfts"."minted_at", "nfts"."id") {} ($1, $2)"#, comparison),
+                [Value::from(cursor.minted_at), Value::from(cursor.id.clone())],
+            ))
+        }
+        None => query,
+    };
+
+    let query = match search.sort {
+        NftSort::Newest => query.order_by_desc(nft::Column::MintedAt).order_by_desc(nft::Column::Id),
+        NftSort::Oldest => query.order_by_asc(nft::Column::MintedAt).order_by_asc(nft::Column::Id),
+        NftSort::Name => query.order_by_asc(nft::Column::Name).order_by_asc(nft::Column::Id),
+    };
+    let results = query
+        .limit(limit)
+        .offset(offset)
+        .all(pool)
+        .await?
+        .into_iter()
+        .filter_map(|(nft, owner)| owner.map(|owner| (nft, owner)))
+        .collect();
+
+    Ok((results, total))
+}
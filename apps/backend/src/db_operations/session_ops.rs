@@ -0,0 +1,76 @@
+use sea_orm::*;
+use crate::entities::{AuthNonce, AuthNonceModel, Session, SessionModel, User, UserModel, auth_nonce, session};
+use anyhow::Result;
+
+pub async fn create_nonce(pool: &DatabaseConnection, nonce: String, expires_at: chrono::NaiveDateTime) -> Result<AuthNonceModel> {
+    let now = chrono::Utc::now().naive_utc();
+
+    // Drop nonces nobody redeemed in time
+    AuthNonce::delete_many()
+        .filter(auth_nonce::Column::ExpiresAt.lte(now))
+        .exec(pool)
+        .await?;
+
+    let auth_nonce = AuthNonceModel {
+        nonce,
+        created_at: now,
+        expires_at,
+    };
+
+    let nonce_active = auth_nonce.clone().into_active_model();
+    let result = nonce_active.insert(pool).await?;
+
+    Ok(result)
+}
+
+/// Redeem a nonce; returns false when it is unknown, expired or already used
+pub async fn consume_nonce(pool: &DatabaseConnection, nonce: &str) -> Result<bool> {
+    let result = AuthNonce::delete_many()
+        .filter(auth_nonce::Column::Nonce.eq(nonce))
+        .filter(auth_nonce::Column::ExpiresAt.gt(chrono::Utc::now().naive_utc()))
+        .exec(pool)
+        .await?;
+
+    Ok(result.rows_affected == 1)
+}
+
+pub async fn create_session(
+    pool: &DatabaseConnection,
+    token_hash: String,
+    user_id: String,
+    wallet_address: String,
+    expires_at: chrono::NaiveDateTime,
+) -> Result<SessionModel> {
+    let session = SessionModel {
+        token_hash,
+        user_id,
+        wallet_address,
+        created_at: chrono::Utc::now().naive_utc(),
+        expires_at,
+    };
+
+    let session_active = session.clone().into_active_model();
+    let result = session_active.insert(pool).await?;
+
+    Ok(result)
+}
+
+/// Find an unexpired session together with the user it belongs to
+pub async fn find_session_with_user(pool: &DatabaseConnection, token_hash: &str) -> Result<Option<(SessionModel, UserModel)>> {
+    let result = Session::find()
+        .filter(session::Column::TokenHash.eq(token_hash))
+        .filter(session::Column::ExpiresAt.gt(chrono::Utc::now().naive_utc()))
+        .find_also_related(User)
+        .one(pool)
+        .await?;
+
+    Ok(result.and_then(|(session, user)| user.map(|user| (session, user))))
+}
+
+pub async fn delete_session(pool: &DatabaseConnection, token_hash: &str) -> Result<()> {
+    Session::delete_by_id(token_hash)
+        .exec(pool)
+        .await?;
+
+    Ok(())
+}
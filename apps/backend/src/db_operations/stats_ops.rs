@@ -0,0 +1,170 @@
+use sea_orm::*;
+use crate::entities::{Collection, MintJob, Nft, User, nft};
+use crate::blockchain_sim::MintStatus;
+use crate::wei::Wei;
+use anyhow::Result;
+
+/// Mint gas fees in wei for an `nfts n LEFT JOIN mint_jobs j` row set, as decimal text. The fee of
+/// a transaction several jobs share, as in a batch mint, is split between them, so it counts once.
+pub(crate) const FEE_VOLUME_WEI: &str =
+    "ROUND(COALESCE(SUM(j.gas_used::numeric * j.gas_price::numeric \
+     / (SELECT COUNT(*) FROM mint_jobs s WHERE s.transaction_hash = j.transaction_hash)), 0))::text";
+
+#[derive(Debug, FromQueryResult)]
+pub struct PlatformTotals {
+    pub total_users: i64,
+    pub total_nfts: i64,
+    pub total_collections: i64,
+    pub total_transactions: i64,
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct DailyMintRow {
+    pub date: String,
+    pub count: i64,
+    pub unique_users: i64,
+    pub volume: Wei,
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct CollectionRow {
+    pub id: String,
+    pub name: String,
+    pub nft_count: i64,
+    pub unique_owners: i64,
+    pub volume: Wei,
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct UserActivityRow {
+    pub user_id: String,
+    pub wallet_address: String,
+    pub nft_count: i64,
+    pub last_mint: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct CountRow {
+    count: i64,
+}
+
+pub async fn get_platform_totals(pool: &DatabaseConnection) -> Result<PlatformTotals> {
+    let total_users = User::find().count(pool).await? as i64;
+    let total_nfts = Nft::find()
+        .filter(nft::Column::MintStatus.ne(MintStatus::Failed.as_str()))
+        .count(pool)
+        .await? as i64;
+    let total_transactions = MintJob::find().count(pool).await? as i64;
+    let total_collections = Collection::find().count(pool).await? as i64;
+
+    Ok(PlatformTotals {
+        total_users,
+        total_nfts,
+        total_collections,
+        total_transactions,
+    })
+}
+
+/// Per-day mint counts for the last `days` days (UTC), including days without mints
+pub async fn get_daily_mints(pool: &DatabaseConnection, days: i64) -> Result<Vec<DailyMintRow>> {
+    let today = chrono::Utc::now().date_naive();
+    let first_day = today - chrono::Duration::days(days - 1);
+    let sql = format!(
+        r#"SELECT to_char(d.day, 'YYYY-MM-DD') AS date,
+                  COUNT(n.id) AS count,
+                  COUNT(DISTINCT n.owner_id) AS unique_users,
+                  {volume} AS volume
+           FROM generate_series($1::date, $2::date, interval '1 day') AS d(day)
+           LEFT JOIN nfts n ON n.minted_at >= d.day AND n.minted_at < d.day + interval '1 day'
+                             AND n.mint_status <> 'Failed'
+           LEFT JOIN mint_jobs j ON j.nft_id = n.id
+           GROUP BY d.day
+           ORDER BY d.day"#,
+        volume = FEE_VOLUME_WEI,
+    );
+
+    let rows = DailyMintRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        [first_day.into(), today.into()],
+    ))
+    .all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Collections ranked by number of NFTs minted into them
+pub async fn get_top_collections(pool: &DatabaseConnection, limit: u64) -> Result<Vec<CollectionRow>> {
+    let sql = format!(
+        r#"SELECT c.id, c.name,
+                  COUNT(n.id) AS nft_count,
+                  COUNT(DISTINCT n.owner_id) AS unique_owners,
+                  {volume} AS volume
+           FROM collections c
+           JOIN nfts n ON n.collection_id = c.id AND n.mint_status <> 'Failed'
+           LEFT JOIN mint_jobs j ON j.nft_id = n.id
+           GROUP BY c.id, c.name
+           ORDER BY nft_count DESC, c.name
+           LIMIT $1"#,
+        volume = FEE_VOLUME_WEI,
+    );
+
+    let rows = CollectionRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        [(limit as i64).into()],
+    ))
+    .all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn count_new_users_since(pool: &DatabaseConnection, since: chrono::NaiveDateTime) -> Result<i64> {
+    let count = User::find()
+        .filter(crate::entities::user::Column::CreatedAt.gte(since))
+        .count(pool)
+        .await?;
+
+    Ok(count as i64)
+}
+
+/// Users who minted or signed in since `since`
+pub async fn count_active_users_since(pool: &DatabaseConnection, since: chrono::NaiveDateTime) -> Result<i64> {
+    let count = CountRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT COUNT(DISTINCT user_id) AS count FROM (
+               SELECT owner_id AS user_id FROM nfts WHERE minted_at >= $1
+               UNION
+               SELECT user_id FROM sessions WHERE created_at >= $1
+           ) AS active"#,
+        [since.into()],
+    ))
+    .one(pool)
+    .await?
+    .map_or(0, |row| row.count);
+
+    Ok(count)
+}
+
+/// Users ranked by how many NFTs they own, with their latest mint
+pub async fn get_most_active_users(pool: &DatabaseConnection, limit: u64) -> Result<Vec<UserActivityRow>> {
+    let rows = UserActivityRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT u.id AS user_id,
+                  u.public_key AS wallet_address,
+                  COUNT(n.id) AS nft_count,
+                  MAX(n.minted_at) AS last_mint
+           FROM users u
+           JOIN nfts n ON n.owner_id = u.id AND n.mint_status <> 'Failed' AND n.burned_at IS NULL
+           GROUP BY u.id, u.public_key
+           ORDER BY nft_count DESC, last_mint DESC
+           LIMIT $1"#,
+        [(limit as i64).into()],
+    ))
+    .all(pool)
+    .await?;
+
+    Ok(rows)
+}
@@ -0,0 +1,285 @@
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use crate::blockchain_sim::{Block, TransactionDetails, TransactionLog, TransactionStatus, ZERO_ADDRESS};
+use crate::entities::{ChainTransaction, ChainTransactionModel, Nft, NftModel, transaction};
+use crate::wei::Wei;
+use anyhow::Result;
+
+pub struct NewTransaction {
+    pub hash: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub nft_id: Option<String>,
+    pub gas_used: i64,
+    pub gas_price: Wei,
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+    pub nonce: Option<i64>,
+    pub logs: Vec<TransactionLog>,
+}
+
+impl NewTransaction {
+    /// The transaction minting `nft` to `owner`, sent by the owner to the NFT contract
+    pub fn mint(transaction: &TransactionDetails, contract: &str, owner: &str, nft: &NftModel) -> Self {
+        Self {
+            hash: transaction.transaction_hash.clone(),
+            from_address: owner.to_string(),
+            to_address: contract.to_string(),
+            nft_id: Some(nft.id.clone()),
+            gas_used: transaction.gas_used as i64,
+            gas_price: transaction.gas_price,
+            max_fee_per_gas: transaction.max_fee_per_gas,
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+            nonce: transaction.nonce.map(|nonce| nonce as i64),
+            logs: vec![TransactionLog::transfer(0, contract, ZERO_ADDRESS, owner, &nft.token_id)],
+        }
+    }
+
+    /// The transaction minting every one of `nfts` to `owner` in a single call, with a `Transfer`
+    /// log per token. It belongs to no single NFT.
+    pub fn batch_mint(transaction: &TransactionDetails, contract: &str, owner: &str, nfts: &[NftModel]) -> Self {
+        Self {
+            hash: transaction.transaction_hash.clone(),
+            from_address: owner.to_string(),
+            to_address: contract.to_string(),
+            nft_id: None,
+            gas_used: transaction.gas_used as i64,
+            gas_price: transaction.gas_price,
+            max_fee_per_gas: transaction.max_fee_per_gas,
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+            nonce: transaction.nonce.map(|nonce| nonce as i64),
+            logs: nfts
+                .iter()
+                .enumerate()
+                .map(|(index, nft)| TransactionLog::transfer(index as u32, contract, ZERO_ADDRESS, owner, &nft.token_id))
+                .collect(),
+        }
+    }
+
+    /// The transaction moving `nft` from `from` to `to`, sent by the current owner to the NFT contract
+    pub fn transfer(transaction: &TransactionDetails, contract: &str, from: &str, to: &str, nft: &NftModel) -> Self {
+        Self {
+            hash: transaction.transaction_hash.clone(),
+            from_address: from.to_string(),
+            to_address: contract.to_string(),
+            nft_id: Some(nft.id.clone()),
+            gas_used: transaction.gas_used as i64,
+            gas_price: transaction.gas_price,
+            max_fee_per_gas: transaction.max_fee_per_gas,
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+            nonce: transaction.nonce.map(|nonce| nonce as i64),
+            logs: vec![TransactionLog::transfer(0, contract, from, to, &nft.token_id)],
+        }
+    }
+
+    /// A transaction taking `original`'s nonce: the same call with new fees, or an empty
+    /// self-transfer when it cancels the original
+    pub fn replacement(original: &ChainTransactionModel, transaction: &TransactionDetails) -> Self {
+        let cancel = transaction.cancellation;
+        Self {
+            hash: transaction.transaction_hash.clone(),
+            from_address: original.from_address.clone(),
+            to_address: if cancel { original.from_address.clone() } else { original.to_address.clone() },
+            nft_id: if cancel { None } else { original.nft_id.clone() },
+            gas_used: transaction.gas_used as i64,
+            gas_price: transaction.gas_price,
+            max_fee_per_gas: transaction.max_fee_per_gas,
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+            nonce: transaction.nonce.map(|nonce| nonce as i64),
+            logs: if cancel { Vec::new() } else { serde_json::from_value(original.logs.clone()).unwrap_or_default() },
+        }
+    }
+}
+
+pub async fn create_transaction<C: ConnectionTrait>(db: &C, new_transaction: NewTransaction) -> Result<ChainTransactionModel> {
+    let now = chrono::Utc::now().naive_utc();
+    let transaction = transaction::ActiveModel {
+        hash: Set(new_transaction.hash),
+        from_address: Set(new_transaction.from_address),
+        to_address: Set(new_transaction.to_address),
+        nft_id: Set(new_transaction.nft_id),
+        status: Set(TransactionStatus::Pending.as_str().to_string()),
+        block_number: Set(None),
+        block_timestamp: Set(None),
+        gas_used: Set(new_transaction.gas_used),
+        gas_price: Set(new_transaction.gas_price),
+        logs: Set(serde_json::to_value(new_transaction.logs)?),
+        revert_reason: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+        max_fee_per_gas: Set(new_transaction.max_fee_per_gas),
+        max_priority_fee_per_gas: Set(new_transaction.max_priority_fee_per_gas),
+        base_fee_per_gas: Set(None),
+        block_hash: Set(None),
+        nonce: Set(new_transaction.nonce),
+        replaced_by: Set(None),
+    };
+
+    Ok(transaction.insert(db).await?)
+}
+
+/// Record the block that included each of its transactions, the price each paid and why any failed
+pub async fn record_block_inclusion(pool: &DatabaseConnection, block: &Block) -> Result<()> {
+    let block_timestamp = chrono::DateTime::from_timestamp(block.timestamp as i64, 0)
+        .unwrap_or_default()
+        .naive_utc();
+
+    for tx in &block.transactions {
+        let mut update = ChainTransaction::update_many()
+            .col_expr(transaction::Column::BlockNumber, Expr::value(block.number as i64))
+            .col_expr(transaction::Column::BlockHash, Expr::value(block.hash.clone()))
+            .col_expr(transaction::Column::BlockTimestamp, Expr::value(block_timestamp))
+            .col_expr(transaction::Column::BaseFeePerGas, Expr::value(block.base_fee_per_gas))
+            .col_expr(transaction::Column::GasPrice, Expr::value(tx.effective_gas_price))
+            .col_expr(transaction::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()));
+        if let Some(ref revert_reason) = tx.revert_reason {
+            update = update
+                .col_expr(transaction::Column::Status, Expr::value(TransactionStatus::Failed.as_str()))
+                .col_expr(transaction::Column::RevertReason, Expr::value(revert_reason.clone()));
+        }
+        update
+            .filter(transaction::Column::Hash.eq(tx.hash.as_str()))
+            .exec(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Put the transactions of blocks a reorg orphaned back to pending, clearing what their block decided
+pub async fn record_orphaned_blocks(pool: &DatabaseConnection, blocks: &[Block]) -> Result<()> {
+    let hashes: Vec<String> = blocks
+        .iter()
+        .flat_map(|block| &block.transactions)
+        .map(|tx| tx.hash.clone())
+        .collect();
+    if hashes.is_empty() {
+        return Ok(());
+    }
+
+    ChainTransaction::update_many()
+        .col_expr(transaction::Column::Status, Expr::value(TransactionStatus::Pending.as_str()))
+        .col_expr(transaction::Column::BlockNumber, Expr::value(Option::<i64>::None))
+        .col_expr(transaction::Column::BlockHash, Expr::value(Option::<String>::None))
+        .col_expr(transaction::Column::BlockTimestamp, Expr::value(Option::<chrono::NaiveDateTime>::None))
+        .col_expr(transaction::Column::BaseFeePerGas, Expr::value(Option::<i64>::None))
+        .col_expr(transaction::Column::RevertReason, Expr::value(Option::<String>::None))
+        .col_expr(transaction::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()))
+        .filter(transaction::Column::Hash.is_in(hashes))
+        .exec(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Record `replacement` and mark the transaction it replaced
+pub async fn record_replacement_transaction(
+    pool: &DatabaseConnection,
+    replaced_hash: &str,
+    replacement: &TransactionDetails,
+) -> Result<ChainTransactionModel> {
+    let original = ChainTransaction::find_by_id(replaced_hash)
+        .one(pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Transaction {} not found", replaced_hash))?;
+    let transaction = create_transaction(pool, NewTransaction::replacement(&original, replacement)).await?;
+
+    ChainTransaction::update_many()
+        .col_expr(transaction::Column::Status, Expr::value(TransactionStatus::Replaced.as_str()))
+        .col_expr(transaction::Column::ReplacedBy, Expr::value(replacement.transaction_hash.clone()))
+        .col_expr(transaction::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()))
+        .filter(transaction::Column::Hash.eq(replaced_hash))
+        .exec(pool)
+        .await?;
+
+    Ok(transaction)
+}
+
+/// Highest block any transaction was included in, where the simulated chain resumes after a restart
+pub async fn find_latest_block_number(pool: &DatabaseConnection) -> Result<Option<u64>> {
+    let latest = ChainTransaction::find()
+        .select_only()
+        .column_as(transaction::Column::BlockNumber.max(), "block_number")
+        .into_tuple::<Option<i64>>()
+        .one(pool)
+        .await?
+        .flatten();
+
+    Ok(latest.map(|block| block as u64))
+}
+
+/// Next nonce of every sender with a mined transaction, where the simulated chain resumes after a restart
+pub async fn find_account_nonces(pool: &DatabaseConnection) -> Result<Vec<(String, u64)>> {
+    let nonces: Vec<(String, Option<i64>)> = ChainTransaction::find()
+        .select_only()
+        .column(transaction::Column::FromAddress)
+        .column_as(transaction::Column::Nonce.max(), "nonce")
+        .filter(transaction::Column::BlockNumber.is_not_null())
+        .group_by(transaction::Column::FromAddress)
+        .into_tuple()
+        .all(pool)
+        .await?;
+
+    // Addresses are compared without case, as the simulated chain does
+    let mut next_nonces: std::collections::HashMap<String, u64> = std::collections::HashMap::new();
+    for (address, nonce) in nonces {
+        if let Some(nonce) = nonce {
+            let next = next_nonces.entry(address.to_lowercase()).or_default();
+            *next = (*next).max(nonce as u64 + 1);
+        }
+    }
+
+    Ok(next_nonces.into_iter().collect())
+}
+
+/// Mark transactions that left the mempool without being mined
+pub async fn record_dropped_transactions(pool: &DatabaseConnection, hashes: &[String]) -> Result<()> {
+    if hashes.is_empty() {
+        return Ok(());
+    }
+
+    ChainTransaction::update_many()
+        .col_expr(transaction::Column::Status, Expr::value(TransactionStatus::Dropped.as_str()))
+        .col_expr(transaction::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()))
+        .filter(transaction::Column::Hash.is_in(hashes.iter().cloned()))
+        .exec(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_transaction_status<C: ConnectionTrait>(db: &C, hash: &str, status: TransactionStatus) -> Result<()> {
+    ChainTransaction::update_many()
+        .col_expr(transaction::Column::Status, Expr::value(status.as_str()))
+        .col_expr(transaction::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()))
+        .filter(transaction::Column::Hash.eq(hash))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// A transaction by hash, with the NFT it transferred if that still exists
+pub async fn find_transaction_with_nft(
+    pool: &DatabaseConnection,
+    hash: &str,
+) -> Result<Option<(ChainTransactionModel, Option<NftModel>)>> {
+    let transaction = ChainTransaction::find_by_id(hash)
+        .find_also_related(Nft)
+        .one(pool)
+        .await?;
+
+    Ok(transaction)
+}
+
+/// Transactions included in a block, in inclusion order
+pub async fn find_transactions_in_block(pool: &DatabaseConnection, block_number: u64) -> Result<Vec<ChainTransactionModel>> {
+    let transactions = ChainTransaction::find()
+        .filter(transaction::Column::BlockNumber.eq(block_number as i64))
+        .order_by_asc(transaction::Column::CreatedAt)
+        .order_by_asc(transaction::Column::Hash)
+        .all(pool)
+        .await?;
+
+    Ok(transactions)
+}
@@ -0,0 +1,110 @@
+use sea_orm::*;
+use sea_orm::sea_query::Expr;
+use crate::blockchain_sim::MintStatus;
+use crate::entities::{ChainTransaction, ChainTransactionModel, Transfer, TransferModel, transfer};
+use anyhow::Result;
+
+/// A transfer that can't be sent: the NFT already has one waiting to be confirmed, or it changed
+/// hands since it was read
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferConflict {
+    /// Another transfer of the NFT is pending, in the given transaction when it is known
+    Pending(Option<String>),
+    Moved,
+}
+
+impl std::fmt::Display for TransferConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferConflict::Pending(Some(transaction_hash)) => {
+                write!(f, "NFT already has a transfer pending in transaction {}", transaction_hash)
+            }
+            TransferConflict::Pending(None) => write!(f, "NFT already has a transfer pending"),
+            TransferConflict::Moved => write!(f, "NFT changed hands while the transfer was being sent"),
+        }
+    }
+}
+
+impl std::error::Error for TransferConflict {}
+
+pub struct NewTransfer {
+    pub nft_id: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub transaction_hash: String,
+}
+
+/// Record a pending transfer. Fails with [`TransferConflict`] when the NFT already has one that
+/// isn't final, which its partial unique index rules out.
+pub async fn create_transfer<C: ConnectionTrait>(db: &C, new_transfer: NewTransfer) -> Result<TransferModel> {
+    let now = chrono::Utc::now().naive_utc();
+    let transfer = TransferModel {
+        id: cuid::cuid2(),
+        nft_id: new_transfer.nft_id,
+        from_address: new_transfer.from_address,
+        to_address: new_transfer.to_address,
+        transaction_hash: new_transfer.transaction_hash,
+        status: MintStatus::Pending.as_str().to_string(),
+        block_number: None,
+        created_at: now,
+        updated_at: now,
+        confirmed_at: None,
+    };
+
+    match transfer.into_active_model().insert(db).await {
+        Ok(transfer) => Ok(transfer),
+        Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => Err(TransferConflict::Pending(None).into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Persist a transfer's status, block and confirmation time
+pub async fn update_transfer<C: ConnectionTrait>(db: &C, transfer: &TransferModel) -> Result<()> {
+    Transfer::update_many()
+        .col_expr(transfer::Column::Status, Expr::value(transfer.status.clone()))
+        .col_expr(transfer::Column::BlockNumber, Expr::value(transfer.block_number))
+        .col_expr(transfer::Column::ConfirmedAt, Expr::value(transfer.confirmed_at))
+        .col_expr(transfer::Column::UpdatedAt, Expr::value(chrono::Utc::now().naive_utc()))
+        .filter(transfer::Column::Id.eq(transfer.id.as_str()))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Transfers the confirmation worker still has to drive to a final state, with their transactions
+pub async fn find_unfinished_transfers(pool: &DatabaseConnection) -> Result<Vec<(TransferModel, Option<ChainTransactionModel>)>> {
+    let transfers = Transfer::find()
+        .filter(transfer::Column::Status.is_in([MintStatus::Pending.as_str(), MintStatus::Confirming.as_str()]))
+        .find_also_related(ChainTransaction)
+        .order_by_asc(transfer::Column::CreatedAt)
+        .all(pool)
+        .await?;
+
+    Ok(transfers)
+}
+
+/// Transfers sent in any of the given transactions
+pub async fn find_transfers_by_transaction_hashes(pool: &DatabaseConnection, hashes: &[String]) -> Result<Vec<TransferModel>> {
+    let transfers = Transfer::find()
+        .filter(transfer::Column::TransactionHash.is_in(hashes.iter().cloned()))
+        .order_by_asc(transfer::Column::CreatedAt)
+        .all(pool)
+        .await?;
+
+    Ok(transfers)
+}
+
+/// Every transfer of an NFT that hasn't failed, oldest first, with the transaction that carries it
+pub async fn find_nft_transfers(pool: &DatabaseConnection, nft_id: &str) -> Result<Vec<(TransferModel, Option<ChainTransactionModel>)>> {
+    let transfers = Transfer::find()
+        .filter(transfer::Column::NftId.eq(nft_id))
+        .filter(transfer::Column::Status.ne(MintStatus::Failed.as_str()))
+        .find_also_related(ChainTransaction)
+        .order_by_asc(transfer::Column::CreatedAt)
+        .order_by_asc(transfer::Column::Id)
+        .all(pool)
+        .await?;
+
+    Ok(transfers)
+}
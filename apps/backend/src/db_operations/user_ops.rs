@@ -1,52 +1,103 @@
 use sea_orm::*;
+use sea_orm::sea_query::{Expr, Func, SimpleExpr};
 use crate::entities::{User, UserModel, user};
-use crate::database::DbPool;
+use crate::auth::roles::Role;
+use crate::blockchain_sim::MintStatus;
 use anyhow::Result;
 
-pub async fn create_user(pool: &DbPool, public_key: String) -> Result<UserModel> {
-    let conn = pool.lock().await;
-    
+pub async fn create_user<C: ConnectionTrait>(db: &C, public_key: String) -> Result<UserModel> {
     let user = UserModel {
         id: cuid::cuid2(),
         public_key: public_key.clone(),
         created_at: chrono::Utc::now().naive_utc(),
+        role: Role::User.as_str().to_string(),
     };
 
     let user_active = user.clone().into_active_model();
-    let result = user_active.insert(&*conn).await?;
+    let result = user_active.insert(db).await?;
     
     Ok(result)
 }
 
-pub async fn find_user_by_public_key(pool: &DbPool, public_key: &str) -> Result<Option<UserModel>> {
-    let conn = pool.lock().await;
-    
+pub async fn update_user_role(pool: &DatabaseConnection, user_id: &str, role: Role) -> Result<Option<UserModel>> {
+    let Some(user) = User::find_by_id(user_id).one(pool).await? else {
+        return Ok(None);
+    };
+
+    let mut user_active = user.into_active_model();
+    user_active.role = Set(role.as_str().to_string());
+    let result = user_active.update(pool).await?;
+
+    Ok(Some(result))
+}
+
+/// Wallet addresses are case-insensitive; checksummed and lowercase forms match the same user
+fn public_key_matches(public_key: &str) -> SimpleExpr {
+    Expr::expr(Func::lower(Expr::col(user::Column::PublicKey))).eq(public_key.to_lowercase())
+}
+
+pub async fn find_user_by_public_key<C: ConnectionTrait>(db: &C, public_key: &str) -> Result<Option<UserModel>> {
     let user = User::find()
-        .filter(user::Column::PublicKey.eq(public_key))
-        .one(&*conn)
+        .filter(public_key_matches(public_key))
+        .one(db)
         .await?;
     
     Ok(user)
 }
 
-pub async fn get_user_with_nft_count(pool: &DbPool, public_key: &str) -> Result<Option<(UserModel, i64)>> {
-    let conn = pool.lock().await;
-    
+pub async fn get_user_with_nft_count(pool: &DatabaseConnection, public_key: &str) -> Result<Option<(UserModel, i64)>> {
     // First get the user
     let user = User::find()
-        .filter(user::Column::PublicKey.eq(public_key))
-        .one(&*conn)
+        .filter(public_key_matches(public_key))
+        .one(pool)
         .await?;
     
     if let Some(user) = user {
         // Then count their NFTs
         let nft_count = crate::entities::Nft::find()
             .filter(crate::entities::nft::Column::OwnerId.eq(&user.id))
-            .count(&*conn)
+            .filter(crate::entities::nft::Column::MintStatus.ne(MintStatus::Failed.as_str()))
+            .filter(crate::entities::nft::Column::BurnedAt.is_null())
+            .count(pool)
             .await?;
         
         Ok(Some((user, nft_count as i64)))
     } else {
         Ok(None)
     }
-} 
\ No newline at end of file
+} 
+/// A user with how many NFTs they own and when they last minted or signed in
+#[derive(Debug, FromQueryResult)]
+pub struct UserWithActivity {
+    pub id: String,
+    pub public_key: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub nft_count: i64,
+    pub last_active: Option<chrono::NaiveDateTime>,
+}
+
+pub async fn get_users_with_activity(
+    pool: &DatabaseConnection,
+    limit: u64,
+    offset: u64,
+) -> Result<(Vec<UserWithActivity>, u64)> {
+    let users = UserWithActivity::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"SELECT u.id, u.public_key, u.created_at,
+                  (SELECT COUNT(*) FROM nfts n WHERE n.owner_id = u.id AND n.mint_status <> 'Failed' AND n.burned_at IS NULL) AS nft_count,
+                  GREATEST(
+                      (SELECT MAX(n.minted_at) FROM nfts n WHERE n.owner_id = u.id),
+                      (SELECT MAX(s.created_at) FROM sessions s WHERE s.user_id = u.id)
+                  ) AS last_active
+           FROM users u
+           ORDER BY u.created_at DESC, u.id
+           LIMIT $1 OFFSET $2"#,
+        [(limit as i64).into(), (offset as i64).into()],
+    ))
+    .all(pool)
+    .await?;
+
+    let total = User::find().count(pool).await?;
+
+    Ok((users, total))
+}
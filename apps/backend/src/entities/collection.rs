@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "collections")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub creator_id: String,
+    pub name: String,
+    #[sea_orm(unique)]
+    pub slug: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub max_supply: Option<i64>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    /// Token ID the collection's next mint takes
+    pub next_token_id: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::CreatorId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+    #[sea_orm(has_many = "super::nft::Entity")]
+    Nft,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::nft::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Nft.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::wei::Wei;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "mint_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub nft_id: String,
+    pub status: String,
+    pub transaction_hash: String,
+    pub block_number: Option<i64>,
+    pub gas_used: i64,
+    pub gas_price: Wei,
+    pub confirmations: i32,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    pub confirmed_at: Option<DateTime>,
+    pub simulated_failure: Option<String>,
+    pub revert_reason: Option<String>,
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+    pub from_address: Option<String>,
+    pub nonce: Option<i64>,
+    pub cancelled: bool,
+    /// Set when the job was minted by a batch request, shared by every job in the batch
+    pub batch_id: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::nft::Entity",
+        from = "Column::NftId",
+        to = "super::nft::Column::Id"
+    )]
+    Nft,
+}
+
+impl Related<super::nft::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Nft.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
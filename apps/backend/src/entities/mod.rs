@@ -1,7 +1,28 @@
 pub mod user;
 pub mod nft;
+pub mod mint_job;
+pub mod auth_nonce;
+pub mod session;
+pub mod collection;
+pub mod transaction;
+pub mod transfer;
+pub mod idempotency_key;
 
 pub use user::Entity as User;
 pub use nft::Entity as Nft;
+pub use mint_job::Entity as MintJob;
+pub use auth_nonce::Entity as AuthNonce;
+pub use session::Entity as Session;
+pub use collection::Entity as Collection;
+pub use transaction::Entity as ChainTransaction;
+pub use transfer::Entity as Transfer;
+pub use idempotency_key::Entity as IdempotencyKey;
 pub use user::Model as UserModel;
-pub use nft::Model as NftModel; 
\ No newline at end of file
+pub use nft::Model as NftModel;
+pub use mint_job::Model as MintJobModel;
+pub use auth_nonce::Model as AuthNonceModel;
+pub use session::Model as SessionModel;
+pub use collection::Model as CollectionModel;
+pub use transaction::Model as ChainTransactionModel;
+pub use transfer::Model as TransferModel;
+pub use idempotency_key::Model as IdempotencyKeyModel;
@@ -13,8 +13,13 @@ pub struct Model {
     pub minted_at: DateTime,
     pub transaction_hash: Option<String>,
     pub owner_id: String,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
     pub attributes: Option<Json>,
     pub collection_name: Option<String>,
+    pub collection_id: Option<String>,
+    pub mint_status: String,
+    /// Set once a burn is confirmed; the row stays for provenance
+    pub burned_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -25,6 +30,12 @@ pub enum Relation {
         to = "super::user::Column::Id"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::collection::Entity",
+        from = "Column::CollectionId",
+        to = "super::collection::Column::Id"
+    )]
+    Collection,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -33,4 +44,10 @@ impl Related<super::user::Entity> for Entity {
     }
 }
 
+impl Related<super::collection::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Collection.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {} 
\ No newline at end of file
@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::wei::Wei;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "transactions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub hash: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub nft_id: Option<String>,
+    pub status: String,
+    pub block_number: Option<i64>,
+    pub block_timestamp: Option<DateTime>,
+    pub gas_used: i64,
+    pub gas_price: Wei,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub logs: Json,
+    pub revert_reason: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+    pub base_fee_per_gas: Option<Wei>,
+    pub block_hash: Option<String>,
+    pub nonce: Option<i64>,
+    pub replaced_by: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::nft::Entity",
+        from = "Column::NftId",
+        to = "super::nft::Column::Id"
+    )]
+    Nft,
+}
+
+impl Related<super::nft::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Nft.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
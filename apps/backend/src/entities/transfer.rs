@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "transfers")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub nft_id: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub transaction_hash: String,
+    pub status: String,
+    pub block_number: Option<i64>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    pub confirmed_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::nft::Entity",
+        from = "Column::NftId",
+        to = "super::nft::Column::Id"
+    )]
+    Nft,
+    #[sea_orm(
+        belongs_to = "super::transaction::Entity",
+        from = "Column::TransactionHash",
+        to = "super::transaction::Column::Hash"
+    )]
+    Transaction,
+}
+
+impl Related<super::nft::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Nft.def()
+    }
+}
+
+impl Related<super::transaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Transaction.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -9,6 +9,7 @@ pub struct Model {
     #[sea_orm(unique)]
     pub public_key: String,
     pub created_at: DateTime,
+    pub role: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,7 @@
+/// Read and parse an environment variable, falling back to `default` when it is unset or invalid
+pub fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
@@ -0,0 +1,20 @@
+use axum::{
+    extract::State,
+    Json,
+    response::IntoResponse,
+    http::StatusCode,
+};
+use crate::{
+    auth::types::ApiResponse,
+    blockchain_sim::SharedSimulator,
+};
+
+/// Slow, standard and fast EIP-1559 fee suggestions for the next simulated block
+pub async fn gas_estimate_handler(State(simulator): State<SharedSimulator>) -> impl IntoResponse {
+    let response = ApiResponse {
+        success: true,
+        data: Some(simulator.gas_estimate()),
+        message: "Gas estimate retrieved successfully".to_string(),
+    };
+    (StatusCode::OK, Json(response))
+}
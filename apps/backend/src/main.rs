@@ -1,18 +1,21 @@
 
 use axum::{
     Router,
-    routing::{post, get},
+    routing::{post, get, put},
+    middleware,
     extract::{Path, State},
     response::IntoResponse,
     http::StatusCode,
     Json,
 };
-use reqwest;
 use tower_http::cors::{CorsLayer, Any};
 use migration::{Migrator, MigratorTrait};
+use std::sync::Arc;
 
 mod auth;
+mod crypto;
 mod database;
+mod env;
 mod entities;
 mod db_operations;
 mod nft;
@@ -20,12 +23,31 @@ mod blockchain_sim;
 mod minting_queue;
 mod admin;
 mod collections;
+mod metadata;
+mod realtime;
+mod state;
+mod pagination;
+mod transactions;
+mod rpc;
+mod gas;
+mod wei;
 
 use auth::{signup_handler, user::get_user_handler};
+use auth::config::AuthConfig;
+use auth::roles::{require_admin, require_moderator};
+use auth::session::{nonce_handler, verify_handler, current_session_handler, logout_handler};
 use nft::handlers::*;
 use admin::handlers::*;
 use collections::handlers::*;
-use database::{DbPool, health_check};
+use metadata::{config::MetadataConfig, handlers::{get_contract_metadata_handler, get_token_metadata_handler}};
+use transactions::handlers::get_transaction_handler;
+use rpc::handlers::rpc_handler;
+use gas::handlers::gas_estimate_handler;
+use realtime::handlers::{mint_status_stream_handler, ws_handler};
+use database::{DbPool, DatabaseConfig, health_check, pool_stats};
+use blockchain_sim::BlockchainSimulator;
+use minting_queue::MintingQueue;
+use state::AppState;
 
 
 
@@ -42,9 +64,10 @@ async fn main() {
     println!("Backend server starting...");
     
     // Initialize database connection
-    let db_pool = match database::create_connection_pool().await {
+    let db_config = DatabaseConfig::from_env();
+    let db_pool = match database::create_connection_pool(&db_config).await {
         Ok(pool) => {
-            println!("Database connection established");
+            println!("Database pool established (max {} connections)", db_config.max_connections);
             pool
         }
         Err(e) => {
@@ -54,15 +77,38 @@ async fn main() {
     };
     
     // Run migrations
-    let conn = db_pool.lock().await;
-    if let Err(e) = Migrator::up(&*conn, None).await {
+    if let Err(e) = Migrator::up(&*db_pool, None).await {
         eprintln!("Failed to run migrations: {}", e);
         std::process::exit(1);
     }
-    drop(conn);
     println!("Database migrations completed");
 
-    run_server(db_pool).await;
+    // Seeded from SIM_SEED when set so simulated transactions are reproducible
+    let simulator = Arc::new(BlockchainSimulator::from_env());
+    if let Some(seed) = simulator.seed() {
+        println!("Blockchain simulator seeded with {}", seed);
+    }
+
+    // Resume mints that were still in flight when the server last stopped
+    let minting_queue = MintingQueue::new(db_pool.clone(), simulator.clone());
+    match minting_queue.resume_pending().await {
+        Ok(resumed) => println!("Resumed {} pending mint jobs and transfers", resumed),
+        Err(e) => {
+            eprintln!("Failed to resume pending mint jobs and transfers: {}", e);
+            std::process::exit(1);
+        }
+    }
+    minting_queue.start_confirmation_worker();
+
+    let state = AppState {
+        db: db_pool,
+        minting_queue,
+        auth: AuthConfig::from_env(),
+        simulator,
+        metadata: MetadataConfig::from_env(),
+    };
+
+    run_server(state).await;
 }
 
 
@@ -73,7 +119,8 @@ async fn health_handler(State(pool): State<DbPool>) -> impl IntoResponse {
             StatusCode::OK,
             Json(serde_json::json!({
                 "status": "healthy",
-                "database": "connected"
+                "database": "connected",
+                "pool": pool_stats(&pool)
             }))
         ),
         Err(e) => (
@@ -81,7 +128,8 @@ async fn health_handler(State(pool): State<DbPool>) -> impl IntoResponse {
             Json(serde_json::json!({
                 "status": "unhealthy",
                 "database": "disconnected",
-                "error": e.to_string()
+                "error": e.to_string(),
+                "pool": pool_stats(&pool)
             }))
         )
     }
@@ -115,7 +163,7 @@ async fn collection_metrics_handler(Path(collection_id): Path<String>) -> impl I
 }
 
 
-async fn run_server(db_pool: DbPool) {
+async fn run_server(state: AppState) {
     println!("Server is ready!");
 
     // Configure CORS
@@ -124,10 +172,31 @@ async fn run_server(db_pool: DbPool) {
         .allow_methods(Any)
         .allow_headers(Any);
 
+    // Admin routes require a moderator session; destructive ones require an admin
+    let admin_only_routes = Router::new()
+        .route("/api/admin/users/{id}/role", put(update_user_role_handler))
+        .route("/api/admin/demo/reset", post(reset_demo_data_handler))
+        .route("/api/admin/sim/advance", post(advance_simulator_clock_handler))
+        .route("/api/admin/sim/reorg", post(reorg_simulator_handler))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin));
+
+    let admin_routes = Router::new()
+        .route("/api/admin/stats", get(get_admin_stats_handler))
+        .route("/api/admin/users", get(get_admin_users_handler))
+        .route("/api/admin/nfts", get(get_admin_nfts_handler))
+        .route("/api/admin/analytics", get(get_admin_analytics_handler))
+        .route("/api/admin/featured", post(set_featured_nfts_handler))
+        .merge(admin_only_routes)
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_moderator));
+
     let app = Router::new()
         // Health check
         .route("/api/health", get(health_handler))
         // Auth routes
+        .route("/api/auth/nonce", get(nonce_handler))
+        .route("/api/auth/verify", post(verify_handler))
+        .route("/api/auth/session", get(current_session_handler))
+        .route("/api/auth/logout", post(logout_handler))
         .route("/api/auth/signup", post(signup_handler))
         .route("/api/auth/user/{wallet_address}", get(get_user_handler))
         // NFT routes
@@ -135,24 +204,41 @@ async fn run_server(db_pool: DbPool) {
         .route("/api/nfts/search", get(search_nfts_handler))
         .route("/api/nfts/{id}", get(get_nft_by_id_handler))
         .route("/api/nfts/mint", post(mint_nft_handler))
+        .route("/api/nfts/mint/batch", post(batch_mint_handler))
+        .route("/api/nfts/mint/batch/{batch_id}", get(get_batch_status_handler))
+        .route("/api/nfts/mint/{mint_id}/speed-up", post(speed_up_mint_handler))
+        .route("/api/nfts/mint/{mint_id}/cancel", post(cancel_mint_handler))
         .route("/api/nfts/mint-status/{mint_id}", get(get_mint_status_handler))
+        .route("/api/nfts/{id}/transfer", post(transfer_nft_handler))
+        .route("/api/nfts/{id}/burn", post(burn_nft_handler))
+        .route("/api/nfts/{id}/provenance", get(get_nft_provenance_handler))
+        .route("/api/nfts/mint-status/{mint_id}/stream", get(mint_status_stream_handler))
         .route("/api/users/{wallet_address}/nfts", get(get_user_nfts_handler))
         // Collection routes
-        .route("/api/collections", get(get_collections_handler))
-        .route("/api/collections/{id}", get(get_collection_by_id_handler))
+        .route("/api/collections", get(get_collections_handler).post(create_collection_handler))
+        .route(
+            "/api/collections/{id}",
+            get(get_collection_by_id_handler)
+                .put(update_collection_handler)
+                .delete(delete_collection_handler),
+        )
         .route("/api/collections/{id}/nfts", get(get_collection_nfts_handler))
-        .route("/api/collections", post(create_collection_handler))
+        // ERC-721 metadata for tokenURI and contractURI
+        .route("/api/metadata/{collection}", get(get_contract_metadata_handler))
+        .route("/api/metadata/{collection}/{token_id}", get(get_token_metadata_handler))
+        // Transaction routes
+        .route("/api/tx/{hash}", get(get_transaction_handler))
+        .route("/api/gas/estimate", get(gas_estimate_handler))
+        // Ethereum JSON-RPC over the simulated chain
+        .route("/rpc", post(rpc_handler))
         // Admin routes
-        .route("/api/admin/stats", get(get_admin_stats_handler))
-        .route("/api/admin/users", get(get_admin_users_handler))
-        .route("/api/admin/nfts", get(get_admin_nfts_handler))
-        .route("/api/admin/analytics", get(get_admin_analytics_handler))
-        .route("/api/admin/featured", post(set_featured_nfts_handler))
-        .route("/api/admin/demo/reset", post(reset_demo_data_handler))
+        .merge(admin_routes)
+        // Realtime routes
+        .route("/api/ws", get(ws_handler))
         // Legacy route
         .route("/api/collections/{collection_id}/metrics", get(collection_metrics_handler))
         .layer(cors)
-        .with_state(db_pool);
+        .with_state(state);
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:8000")
         .await
@@ -0,0 +1,17 @@
+// metadata/config.rs
+
+/// Settings for the token metadata marketplaces read
+#[derive(Debug, Clone)]
+pub struct MetadataConfig {
+    /// Where the web app is served; metadata links back to it
+    pub web_base_url: String,
+}
+
+impl MetadataConfig {
+    pub fn from_env() -> Self {
+        let web_base_url = std::env::var("WEB_BASE_URL").unwrap_or_else(|_| "http://localhost:3000".to_string());
+        Self {
+            web_base_url: web_base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
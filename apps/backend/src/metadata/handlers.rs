@@ -0,0 +1,68 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+    response::{IntoResponse, Response},
+    http::StatusCode,
+};
+use crate::{
+    database::DbPool,
+    auth::types::ApiResponse,
+    blockchain_sim::MintStatus,
+    db_operations::{find_collection, find_nft_by_token_id},
+    entities::CollectionModel,
+    metadata::{config::MetadataConfig, types::*},
+};
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    let response = ApiResponse::<()> {
+        success: false,
+        data: None,
+        message: message.into(),
+    };
+    (status, Json(response)).into_response()
+}
+
+async fn load_collection(pool: &DbPool, id_or_slug: &str) -> Result<CollectionModel, Response> {
+    match find_collection(pool, id_or_slug).await {
+        Ok(Some(collection)) => Ok(collection),
+        Ok(None) => Err(error_response(StatusCode::NOT_FOUND, "Collection not found")),
+        Err(e) => Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to find collection: {}", e))),
+    }
+}
+
+/// Token metadata as marketplaces fetch it from `tokenURI`, served bare rather than wrapped in an
+/// API response. Tokens whose mint failed or that were burned don't exist.
+pub async fn get_token_metadata_handler(
+    State(pool): State<DbPool>,
+    State(config): State<MetadataConfig>,
+    Path((collection, token_id)): Path<(String, String)>,
+) -> Response {
+    let collection = match load_collection(&pool, &collection).await {
+        Ok(collection) => collection,
+        Err(response) => return response,
+    };
+    // Token IDs are decimal; leading zeros name the same token
+    let Ok(token_id) = token_id.parse::<u64>() else {
+        return error_response(StatusCode::NOT_FOUND, "Token not found");
+    };
+
+    match find_nft_by_token_id(&pool, &collection.id, &token_id.to_string()).await {
+        Ok(Some(nft)) if nft.burned_at.is_none() && nft.mint_status != MintStatus::Failed.as_str() => {
+            Json(TokenMetadata::new(nft, &config.web_base_url)).into_response()
+        }
+        Ok(_) => error_response(StatusCode::NOT_FOUND, "Token not found"),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to retrieve token: {}", e)),
+    }
+}
+
+/// Collection-level metadata as marketplaces fetch it from `contractURI`
+pub async fn get_contract_metadata_handler(
+    State(pool): State<DbPool>,
+    State(config): State<MetadataConfig>,
+    Path(collection): Path<String>,
+) -> Response {
+    match load_collection(&pool, &collection).await {
+        Ok(collection) => Json(ContractMetadata::new(collection, &config.web_base_url)).into_response(),
+        Err(response) => response,
+    }
+}
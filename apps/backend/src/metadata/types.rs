@@ -0,0 +1,139 @@
+use serde::Serialize;
+use crate::entities::{CollectionModel, NftModel};
+use crate::nft::attributes::{AttributeValue, DisplayType};
+use crate::nft::types::NftAttribute;
+
+/// A trait as marketplaces display it
+#[derive(Debug, Serialize)]
+pub struct MetadataAttribute {
+    pub trait_type: String,
+    pub value: AttributeValue,
+    /// How a marketplace renders the value; plain text when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_type: Option<DisplayType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<serde_json::Number>,
+}
+
+impl From<NftAttribute> for MetadataAttribute {
+    fn from(attribute: NftAttribute) -> Self {
+        MetadataAttribute {
+            trait_type: attribute.trait_type,
+            value: attribute.value,
+            display_type: attribute.display_type,
+            max_value: attribute.max_value,
+        }
+    }
+}
+
+/// The ERC-721 metadata JSON a token's `tokenURI` points at, with OpenSea's extensions
+#[derive(Debug, Serialize)]
+pub struct TokenMetadata {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub image: String,
+    pub external_url: String,
+    pub attributes: Vec<MetadataAttribute>,
+}
+
+impl TokenMetadata {
+    pub fn new(nft: NftModel, web_base_url: &str) -> Self {
+        // A malformed entry only loses itself, not every other trait
+        let attributes: Vec<NftAttribute> = nft
+            .attributes
+            .and_then(|attrs| serde_json::from_value::<Vec<serde_json::Value>>(attrs).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|attribute| serde_json::from_value(attribute).ok())
+            .collect();
+
+        TokenMetadata {
+            name: nft.name,
+            description: nft.description,
+            image: nft.image,
+            external_url: format!("{}/nfts/{}", web_base_url, nft.id),
+            attributes: attributes.into_iter().map(MetadataAttribute::from).collect(),
+        }
+    }
+}
+
+/// The collection-level document a contract's `contractURI` points at
+#[derive(Debug, Serialize)]
+pub struct ContractMetadata {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner_image: Option<String>,
+    pub external_link: String,
+}
+
+impl ContractMetadata {
+    pub fn new(collection: CollectionModel, web_base_url: &str) -> Self {
+        ContractMetadata {
+            name: collection.name,
+            description: collection.description,
+            image: collection.image_url,
+            banner_image: collection.banner_url,
+            external_link: format!("{}/collections/{}", web_base_url, collection.slug),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_metadata_follows_erc721_json() {
+        let nft = NftModel {
+            id: "nft_1".to_string(),
+            token_id: "7".to_string(),
+            name: "Dream #7".to_string(),
+            description: None,
+            image: "https://example.com/7.png".to_string(),
+            minted_at: chrono::Utc::now().naive_utc(),
+            transaction_hash: None,
+            owner_id: "user_1".to_string(),
+            attributes: Some(serde_json::json!([
+                { "trait_type": "Rarity", "value": "Epic" },
+                { "trait_type": "Power", "value": 87, "display_type": "boost_number", "max_value": 100 },
+            ])),
+            collection_name: None,
+            collection_id: None,
+            mint_status: "Confirmed".to_string(),
+            burned_at: None,
+        };
+
+        let metadata = serde_json::to_value(TokenMetadata::new(nft.clone(), "https://mintverse.example")).unwrap();
+        assert_eq!(metadata, serde_json::json!({
+            "name": "Dream #7",
+            "image": "https://example.com/7.png",
+            "external_url": "https://mintverse.example/nfts/nft_1",
+            "attributes": [
+                { "trait_type": "Rarity", "value": "Epic" },
+                { "trait_type": "Power", "value": 87, "display_type": "boost_number", "max_value": 100 },
+            ],
+        }));
+
+        // Malformed entries are left out without losing the valid ones around them
+        let mixed = NftModel {
+            attributes: Some(serde_json::json!([
+                { "trait_type": "Rarity", "value": "Epic" },
+                { "trait_type": "Power" },
+                "Shiny",
+                { "trait_type": "Speed", "value": 12, "display_type": "sparkle" },
+                { "trait_type": "Level", "value": 3, "display_type": "number" },
+            ])),
+            ..nft
+        };
+        let metadata = serde_json::to_value(TokenMetadata::new(mixed, "https://mintverse.example")).unwrap();
+        assert_eq!(metadata["attributes"], serde_json::json!([
+            { "trait_type": "Rarity", "value": "Epic" },
+            { "trait_type": "Level", "value": 3, "display_type": "number" },
+        ]));
+    }
+}
@@ -1,177 +1,1574 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
 use tokio::time::{sleep, Duration};
-use crate::blockchain_sim::{BlockchainSimulator, MintingStatus, MintStatus, TransactionStatus};
+use anyhow::Result;
+use sea_orm::TransactionTrait;
+use crate::blockchain_sim::{
+    batch_mint_gas_used, Block, ChainUpdate, IncludedTransaction, MintingStatus, MintStatus, ReplacementError,
+    ReplacementRequest, SharedSimulator, TransactionDetails, TransactionRequest, TransactionStatus, TRANSFER_GAS_USED,
+    ZERO_ADDRESS,
+};
+use crate::database::DbPool;
+use crate::db_operations::{
+    allocate_token_ids, create_mint_job, create_nft, create_transaction, create_transfer, find_account_nonces, find_latest_block_number,
+    find_mint_job_by_id, find_mint_jobs_by_batch_id, find_nft_by_id, find_mint_jobs_by_transaction_hashes,
+    find_transfers_by_transaction_hashes, find_unfinished_mint_jobs, find_unfinished_transfers, record_block_inclusion,
+    record_dropped_transactions, record_orphaned_blocks, record_replacement_transaction, set_nft_burned, set_nft_owner,
+    update_mint_job, update_transaction_status, update_transfer, NewNft, NewTransaction, NewTransfer, TransferConflict,
+};
+use crate::entities::{ChainTransactionModel, MintJobModel, NftModel, TransferModel};
+use crate::wei::Wei;
+
+/// Capacity of the status event channel; slow subscribers beyond this lag and skip events
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+/// Failure reason of a mint whose cancellation was mined
+pub const MINT_CANCELLED_REASON: &str = "cancelled by a replacement transaction";
+
+/// A mint status transition published to realtime subscribers
+#[derive(Debug, Clone)]
+pub struct MintEvent {
+    pub wallet_address: String,
+    pub minting_status: MintingStatus,
+    /// Set when the transition is a rollback: a reorg orphaned this block, which had included the mint
+    pub orphaned_block: Option<u64>,
+}
 
+#[derive(Debug, Clone)]
+struct QueuedMint {
+    wallet_address: String,
+    minting_status: MintingStatus,
+}
+
+/// Tracks in-flight mints and transfers. Every job is persisted in `mint_jobs` and every transfer
+/// in `transfers`; the in-memory maps only cache what the confirmation worker is still driving.
 #[derive(Debug, Clone)]
 pub struct MintingQueue {
-    pending_mints: Arc<Mutex<HashMap<String, MintingStatus>>>,
+    pool: DbPool,
+    simulator: SharedSimulator,
+    pending_mints: Arc<Mutex<HashMap<String, QueuedMint>>>,
+    /// Unfinished transfers by ID; they leave as soon as they are confirmed or fail
+    pending_transfers: Arc<Mutex<HashMap<String, TransferModel>>>,
+    /// Transfers a reorg rolled back whose rollback couldn't be saved yet, by ID; true where their
+    /// NFT still has to be handed back to the sender, or unburned. Their next save writes it too.
+    unsaved_rollbacks: Arc<Mutex<HashMap<String, bool>>>,
+    events: broadcast::Sender<MintEvent>,
+    /// Held while blocks are applied, so a replacement can't be mined before its mint follows it
+    processing: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl MintingQueue {
-    pub fn new() -> Self {
-        let queue = Self {
+    pub fn new(pool: DbPool, simulator: SharedSimulator) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            pool,
+            simulator,
             pending_mints: Arc::new(Mutex::new(HashMap::new())),
-        };
-        
-        // Start the confirmation worker
-        queue.start_confirmation_worker();
-        
-        queue
+            pending_transfers: Arc::new(Mutex::new(HashMap::new())),
+            unsaved_rollbacks: Arc::new(Mutex::new(HashMap::new())),
+            events,
+            processing: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Receive every status transition from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<MintEvent> {
+        self.events.subscribe()
     }
 
-    /// Add a new mint to the queue
-    pub fn add_mint(&self, mint_id: String) -> MintingStatus {
-        let transaction_details = BlockchainSimulator::create_transaction_details();
-        
+    fn publish(&self, wallet_address: String, minting_status: MintingStatus, orphaned_block: Option<u64>) {
+        // Sending only fails when nobody is listening
+        let _ = self.events.send(MintEvent {
+            wallet_address,
+            minting_status,
+            orphaned_block,
+        });
+    }
+
+    /// Reload unfinished jobs and transfers from the database, e.g. after a restart, returning how many.
+    ///
+    /// The simulated chain continues from the highest block any transaction was included in, each
+    /// account continues from its last mined nonce, and transactions that never made it into a block
+    /// go back into the mempool in their original order.
+    pub async fn resume_pending(&self) -> Result<usize> {
+        if let Some(head) = find_latest_block_number(&self.pool).await? {
+            self.simulator.resume_from_block(head);
+        }
+        for (address, nonce) in find_account_nonces(&self.pool).await? {
+            self.simulator.resume_account_nonce(&address, nonce);
+        }
+
+        let statuses = [MintStatus::Pending.as_str(), MintStatus::Confirming.as_str()];
+        let jobs = find_unfinished_mint_jobs(&self.pool, &statuses).await?;
+        let mut resumed = jobs.len();
+
+        {
+            let mut pending = self.pending_mints.lock().unwrap();
+            // Jobs of a batch share a transaction, which goes back into the mempool once
+            let mut resubmitted = HashSet::new();
+            for (job, wallet_address) in jobs {
+                let minting_status = minting_status_from_job(&job)?;
+                if let Some(tx) = minting_status.transaction_details.as_ref().filter(|tx| tx.block_number.is_none()) {
+                    if resubmitted.insert(tx.transaction_hash.clone()) {
+                        self.simulator.send_transaction(tx);
+                    }
+                }
+                pending.insert(job.id, QueuedMint {
+                    wallet_address,
+                    minting_status,
+                });
+            }
+        }
+
+        // Transfers resume after the mints, as their transactions were sent later
+        let transfers = find_unfinished_transfers(&self.pool).await?;
+        resumed += transfers.len();
+        let mut pending_transfers = self.pending_transfers.lock().unwrap();
+        for (transfer, transaction) in transfers {
+            if let Some(tx) = transaction.filter(|tx| tx.block_number.is_none()) {
+                self.simulator.send_transaction(&transaction_details_from_row(&tx));
+            }
+            pending_transfers.insert(transfer.id.clone(), transfer);
+        }
+
+        Ok(resumed)
+    }
+
+    /// Mint `new_nft` to `owner` and track it until it is confirmed or fails. The NFT takes the next
+    /// token ID of its collection and is stored with its transaction and job in one database
+    /// transaction; the chain transaction is only sent once they are committed, so a mint that
+    /// fails to be stored never reaches the chain. Returns the NFT with its mint.
+    pub async fn add_mint(
+        &self,
+        mint_id: String,
+        owner: &str,
+        mut new_nft: NewNft,
+        request: TransactionRequest,
+    ) -> Result<(NftModel, MintingStatus)> {
+        // Held so nothing else is sent from the same account, and no block is produced, until the mint is sent
+        let _processing = self.processing.lock().await;
+        let txn = self.pool.begin().await?;
+
+        let collection_id = new_nft.collection.as_ref().map(|collection| collection.id.clone());
+        new_nft.token_id = allocate_token_ids(&txn, collection_id.as_deref(), 1).await?.remove(0);
+        let transaction = self.simulator.prepare_transactions(owner, &[request]).remove(0);
+        new_nft.transaction_hash = Some(transaction.transaction_hash.clone());
+        let nft = create_nft(&txn, new_nft).await?;
+        // Persisted so the receipt can be looked up by hash
+        let contract = self.simulator.contract_address();
+        create_transaction(&txn, NewTransaction::mint(&transaction, contract, owner, &nft)).await?;
+
         let minting_status = MintingStatus {
             mint_id: mint_id.clone(),
             status: MintStatus::Pending,
-            transaction_details: Some(transaction_details),
-            created_at: BlockchainSimulator::current_timestamp(),
+            transaction_details: Some(transaction.clone()),
+            created_at: self.simulator.current_timestamp(),
             confirmed_at: None,
         };
+        create_mint_job(&txn, mint_id, nft.id.clone(), minting_status.status.as_str().to_string(), &transaction, None).await?;
+        txn.commit().await?;
 
+        self.simulator.send_transaction(&transaction);
+        self.track(owner, minting_status.clone());
+        Ok((nft, minting_status))
+    }
+
+    /// Cache a newly stored mint for the confirmation worker and tell subscribers about it
+    fn track(&self, wallet_address: &str, minting_status: MintingStatus) {
         {
             let mut pending = self.pending_mints.lock().unwrap();
-            pending.insert(mint_id, minting_status.clone());
+            pending.insert(minting_status.mint_id.clone(), QueuedMint {
+                wallet_address: wallet_address.to_string(),
+                minting_status: minting_status.clone(),
+            });
+        }
+        self.publish(wallet_address.to_string(), minting_status, None);
+    }
+
+    /// Mint `nfts` to `owner` in one transaction per `chunk_size` of them. Every NFT, transaction
+    /// and job is recorded in a single database transaction, so a batch is stored whole or not at
+    /// all, and each NFT takes the next token ID of its collection. The chunk transactions are only
+    /// sent once the batch is committed. Returns each NFT with its mint, in the order given.
+    pub async fn add_batch_mint(
+        &self,
+        batch_id: &str,
+        owner: &str,
+        mut nfts: Vec<NewNft>,
+        chunk_size: usize,
+        request: TransactionRequest,
+    ) -> Result<Vec<(NftModel, MintingStatus)>> {
+        // Held so nothing else is sent from the same account, and no block is produced, until the batch is sent
+        let _processing = self.processing.lock().await;
+        let txn = self.pool.begin().await?;
+
+        // Token IDs are taken before anything is sent, so a batch past a max supply never reaches the
+        // chain. Collections are locked in a fixed order, so concurrent batches can't deadlock.
+        let mut by_collection: BTreeMap<Option<String>, Vec<usize>> = BTreeMap::new();
+        for (index, nft) in nfts.iter().enumerate() {
+            by_collection.entry(nft.collection.as_ref().map(|collection| collection.id.clone())).or_default().push(index);
+        }
+        for (collection_id, indexes) in by_collection {
+            let token_ids = allocate_token_ids(&txn, collection_id.as_deref(), indexes.len() as u64).await?;
+            for (index, token_id) in indexes.into_iter().zip(token_ids) {
+                nfts[index].token_id = token_id;
+            }
         }
 
-        minting_status
+        let mut chunks: Vec<Vec<NewNft>> = Vec::new();
+        let mut nfts = nfts.into_iter().peekable();
+        while nfts.peek().is_some() {
+            chunks.push(nfts.by_ref().take(chunk_size.max(1)).collect());
+        }
+        let requests: Vec<TransactionRequest> = chunks
+            .iter()
+            .map(|chunk| TransactionRequest { gas_used: Some(batch_mint_gas_used(chunk.len())), ..request })
+            .collect();
+        let transactions = self.simulator.prepare_transactions(owner, &requests);
+
+        let contract = self.simulator.contract_address();
+        let created_at = self.simulator.current_timestamp();
+        let mut minted = Vec::new();
+        for (transaction, chunk) in transactions.iter().zip(chunks) {
+            let mut chunk_nfts = Vec::with_capacity(chunk.len());
+            for new_nft in chunk {
+                let new_nft = NewNft { transaction_hash: Some(transaction.transaction_hash.clone()), ..new_nft };
+                chunk_nfts.push(create_nft(&txn, new_nft).await?);
+            }
+            create_transaction(&txn, NewTransaction::batch_mint(transaction, contract, owner, &chunk_nfts)).await?;
+
+            for nft in chunk_nfts {
+                let minting_status = MintingStatus {
+                    mint_id: cuid::cuid2(),
+                    status: MintStatus::Pending,
+                    transaction_details: Some(transaction.clone()),
+                    created_at,
+                    confirmed_at: None,
+                };
+                create_mint_job(
+                    &txn,
+                    minting_status.mint_id.clone(),
+                    nft.id.clone(),
+                    minting_status.status.as_str().to_string(),
+                    transaction,
+                    Some(batch_id.to_string()),
+                ).await?;
+                minted.push((nft, minting_status));
+            }
+        }
+        txn.commit().await?;
+
+        for transaction in &transactions {
+            self.simulator.send_transaction(transaction);
+        }
+        for (_, minting_status) in &minted {
+            self.track(owner, minting_status.clone());
+        }
+
+        Ok(minted)
     }
 
-    /// Get mint status by ID
-    pub fn get_mint_status(&self, mint_id: &str) -> Option<MintingStatus> {
+    /// Every mint of a batch with its NFT's ID, in the order they were queued
+    pub async fn get_batch_mints(&self, batch_id: &str) -> Result<Vec<(String, MintingStatus)>> {
+        find_mint_jobs_by_batch_id(&self.pool, batch_id)
+            .await?
+            .into_iter()
+            .map(|job| {
+                let minting_status = match self.get_cached(&job.id) {
+                    Some(queued) => queued.minting_status,
+                    None => minting_status_from_job(&job)?,
+                };
+                Ok((job.nft_id, minting_status))
+            })
+            .collect()
+    }
+
+    /// Get mint status by ID, falling back to the database for jobs no longer cached
+    pub async fn get_mint_status(&self, mint_id: &str) -> Result<Option<MintingStatus>> {
+        if let Some(queued) = self.get_cached(mint_id) {
+            return Ok(Some(queued.minting_status));
+        }
+
+        match find_mint_job_by_id(&self.pool, mint_id).await? {
+            Some(job) => Ok(Some(minting_status_from_job(&job)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether a mint can no longer change: it is final, and no simulated reorg can orphan the block
+    /// that included it. A confirmed mint may still roll back until then.
+    pub fn is_settled(&self, minting_status: &MintingStatus) -> bool {
+        let block_number = minting_status.transaction_details.as_ref().and_then(|tx| tx.block_number);
+        minting_status.status.is_final() && block_number.is_none_or(|block_number| !self.simulator.reorg_can_reach(block_number))
+    }
+
+    fn get_cached(&self, mint_id: &str) -> Option<QueuedMint> {
         let pending = self.pending_mints.lock().unwrap();
         pending.get(mint_id).cloned()
     }
 
-    /// Update mint status
-    pub fn update_mint_status(&self, mint_id: &str, status: MintStatus) {
+    /// Record a mint's inclusion block and confirmation count, persisting them before the cached
+    /// copy changes. The mint is confirmed once it has the required number of confirmations.
+    pub async fn update_confirmations(&self, mint_id: &str, block_number: u64, confirmations: u32) -> Result<MintStatus> {
+        let Some(QueuedMint { wallet_address, minting_status: mut mint }) = self.get_cached(mint_id) else {
+            return Ok(MintStatus::Pending);
+        };
+
+        let confirmed = confirmations >= self.simulator.required_confirmations();
+        mint.status = if confirmed { MintStatus::Confirmed } else { MintStatus::Confirming };
+        if confirmed {
+            mint.confirmed_at = Some(self.simulator.current_timestamp());
+        }
+        if let Some(ref mut tx) = mint.transaction_details {
+            tx.block_number = Some(block_number);
+            tx.confirmations = confirmations;
+            if confirmed {
+                tx.status = TransactionStatus::Confirmed;
+            }
+        }
+
+        if confirmed {
+            if let Some(ref tx) = mint.transaction_details {
+                update_transaction_status(self.pool.as_ref(), &tx.transaction_hash, TransactionStatus::Confirmed).await?;
+            }
+        }
+
+        self.save(wallet_address, mint, None).await
+    }
+
+    /// Mark a mint failed. A transaction that reverted carries its block and reason;
+    /// one dropped from the mempool has neither.
+    pub async fn fail_mint(&self, mint_id: &str, block_number: Option<u64>, revert_reason: Option<String>) -> Result<MintStatus> {
+        let Some(QueuedMint { wallet_address, minting_status: mut mint }) = self.get_cached(mint_id) else {
+            return Ok(MintStatus::Pending);
+        };
+
+        mint.status = MintStatus::Failed;
+        if let Some(ref mut tx) = mint.transaction_details {
+            tx.block_number = block_number;
+            tx.status = if block_number.is_some() { TransactionStatus::Failed } else { TransactionStatus::Dropped };
+            tx.revert_reason = revert_reason;
+        }
+
+        self.save(wallet_address, mint, None).await
+    }
+
+    /// Replace a mint's pending transaction: the same mint with higher fees, or a cancellation.
+    /// Every other mint of a batch sharing the transaction follows the replacement too.
+    /// Fails with a [`ReplacementError`] when the transaction was already mined or the fees are too low.
+    pub async fn replace_mint(&self, mint_id: &str, request: ReplacementRequest) -> Result<MintingStatus> {
+        let _processing = self.processing.lock().await;
+        let Some(QueuedMint { wallet_address, minting_status: mut mint }) = self.get_cached(mint_id) else {
+            return Err(ReplacementError::NotPending.into());
+        };
+        let original = match mint.transaction_details.as_ref() {
+            Some(tx) if mint.status == MintStatus::Pending && tx.block_number.is_none() => tx.clone(),
+            _ => return Err(ReplacementError::NotPending.into()),
+        };
+
+        let replacement = self.simulator.replace_transaction(&original, request)?;
+        record_replacement_transaction(&self.pool, &original.transaction_hash, &replacement).await?;
+        let batched: Vec<QueuedMint> = self
+            .get_queued_mints()
+            .into_iter()
+            .filter(|queued| queued.minting_status.mint_id != mint_id)
+            .filter(|queued| {
+                let tx = queued.minting_status.transaction_details.as_ref();
+                tx.is_some_and(|tx| tx.transaction_hash == original.transaction_hash)
+            })
+            .collect();
+        for QueuedMint { wallet_address, minting_status: mut batched } in batched {
+            batched.transaction_details = Some(replacement.clone());
+            self.save(wallet_address, batched, None).await?;
+        }
+        mint.transaction_details = Some(replacement);
+        self.save(wallet_address, mint.clone(), None).await?;
+
+        Ok(mint)
+    }
+
+    /// Move a mint whose block a reorg orphaned back to pending, whatever it had reached.
+    /// Its transaction is back in the mempool and will be included in another block.
+    pub async fn roll_back_mint(&self, mint_id: &str, orphaned_block: u64) -> Result<MintStatus> {
+        let Some(QueuedMint { wallet_address, minting_status: mut mint }) = self.get_cached(mint_id) else {
+            return Ok(MintStatus::Pending);
+        };
+
+        mint.status = MintStatus::Pending;
+        mint.confirmed_at = None;
+        if let Some(ref mut tx) = mint.transaction_details {
+            tx.block_number = None;
+            tx.confirmations = 0;
+            tx.status = TransactionStatus::Pending;
+            tx.revert_reason = None;
+        }
+
+        self.save(wallet_address, mint, Some(orphaned_block)).await
+    }
+
+    /// Note the price a newly included transaction paid; it is persisted with the mint's next update
+    fn record_effective_gas_price(&self, mint_id: &str, effective_gas_price: Wei) {
         let mut pending = self.pending_mints.lock().unwrap();
-        if let Some(mint) = pending.get_mut(mint_id) {
-            mint.status = status;
-            if status == MintStatus::Confirmed {
-                mint.confirmed_at = Some(BlockchainSimulator::current_timestamp());
-                if let Some(ref mut tx) = mint.transaction_details {
-                    tx.status = TransactionStatus::Confirmed;
-                    tx.confirmations = 12;
+        if let Some(tx) = pending.get_mut(mint_id).and_then(|queued| queued.minting_status.transaction_details.as_mut()) {
+            tx.gas_price = effective_gas_price;
+        }
+    }
+
+    /// Persist a changed mint before updating the cached copy and notifying subscribers
+    async fn save(&self, wallet_address: String, mint: MintingStatus, orphaned_block: Option<u64>) -> Result<MintStatus> {
+        update_mint_job(&self.pool, &mint).await?;
+
+        let status = mint.status;
+        {
+            let mut pending = self.pending_mints.lock().unwrap();
+            pending.insert(mint.mint_id.clone(), QueuedMint {
+                wallet_address: wallet_address.clone(),
+                minting_status: mint.clone(),
+            });
+        }
+        self.publish(wallet_address, mint, orphaned_block);
+
+        Ok(status)
+    }
+
+    /// Send `nft` from its owner `from` to `to` and track the transfer until it is confirmed or fails.
+    /// Ownership only changes once the transfer has the required confirmations; a transfer to the
+    /// zero address burns the NFT instead. Fails with [`TransferConflict`] when the NFT already has
+    /// a transfer pending or changed hands since it was read; the transaction is only sent once the
+    /// transfer is stored.
+    pub async fn transfer_nft(&self, nft: &NftModel, from: &str, to: &str, request: TransactionRequest) -> Result<TransferModel> {
+        // Held so nothing else is sent from the same account, and no block is produced, until the transfer is sent
+        let _processing = self.processing.lock().await;
+
+        // Checked again under the lock, as another request may have sent the NFT since it was read
+        if let Some(pending) = self.pending_transfer(&nft.id) {
+            return Err(TransferConflict::Pending(Some(pending.transaction_hash)).into());
+        }
+        let current = find_nft_by_id(&self.pool, &nft.id).await?;
+        if !current.is_some_and(|current| current.owner_id == nft.owner_id && current.burned_at.is_none()) {
+            return Err(TransferConflict::Moved.into());
+        }
+
+        let request = TransactionRequest { gas_used: Some(TRANSFER_GAS_USED), ..request };
+        let transaction = self.simulator.prepare_transactions(from, &[request]).remove(0);
+        let contract = self.simulator.contract_address();
+        let txn = self.pool.begin().await?;
+        create_transaction(&txn, NewTransaction::transfer(&transaction, contract, from, to, nft)).await?;
+        let transfer = create_transfer(&txn, NewTransfer {
+            nft_id: nft.id.clone(),
+            from_address: from.to_string(),
+            to_address: to.to_string(),
+            transaction_hash: transaction.transaction_hash.clone(),
+        }).await?;
+        txn.commit().await?;
+
+        self.simulator.send_transaction(&transaction);
+        let mut pending = self.pending_transfers.lock().unwrap();
+        pending.insert(transfer.id.clone(), transfer.clone());
+        Ok(transfer)
+    }
+
+    /// The NFT's transfer that is still waiting to be confirmed, if any
+    pub fn pending_transfer(&self, nft_id: &str) -> Option<TransferModel> {
+        let pending = self.pending_transfers.lock().unwrap();
+        pending.values().find(|transfer| transfer.nft_id == nft_id).cloned()
+    }
+
+    fn get_queued_transfers(&self) -> Vec<TransferModel> {
+        let pending = self.pending_transfers.lock().unwrap();
+        pending.values().cloned().collect()
+    }
+
+    /// Persist a transfer's new state, with any rollback a reorg left unsaved. Confirming one hands
+    /// the NFT to the recipient, or burns it, in the same database transaction, so a failure leaves
+    /// it cached and the next pass retries; finished transfers leave the cache.
+    async fn save_transfer(&self, mut transfer: TransferModel, status: MintStatus, block_number: Option<u64>) -> Result<()> {
+        let unsaved_rollback = self.unsaved_rollbacks.lock().unwrap().get(&transfer.id).copied();
+        let txn = self.pool.begin().await?;
+        if unsaved_rollback == Some(true) {
+            if transfer.to_address == ZERO_ADDRESS {
+                set_nft_burned(&txn, &transfer.nft_id, None).await?;
+            } else {
+                set_nft_owner(&txn, &transfer.nft_id, &transfer.from_address).await?;
+            }
+        }
+        if status == MintStatus::Confirmed {
+            let confirmed_at = chrono::DateTime::from_timestamp(self.simulator.current_timestamp() as i64, 0)
+                .unwrap_or_default()
+                .naive_utc();
+            if transfer.to_address == ZERO_ADDRESS {
+                set_nft_burned(&txn, &transfer.nft_id, Some(confirmed_at)).await?;
+            } else {
+                set_nft_owner(&txn, &transfer.nft_id, &transfer.to_address).await?;
+            }
+            update_transaction_status(&txn, &transfer.transaction_hash, TransactionStatus::Confirmed).await?;
+            transfer.confirmed_at = Some(confirmed_at);
+        }
+        transfer.status = status.as_str().to_string();
+        transfer.block_number = block_number.map(|block| block as i64);
+        update_transfer(&txn, &transfer).await?;
+        txn.commit().await?;
+        if unsaved_rollback.is_some() {
+            self.unsaved_rollbacks.lock().unwrap().remove(&transfer.id);
+        }
+
+        let mut pending = self.pending_transfers.lock().unwrap();
+        if status.is_final() {
+            pending.remove(&transfer.id);
+        } else {
+            pending.insert(transfer.id.clone(), transfer);
+        }
+        Ok(())
+    }
+
+    /// Move every cached transfer along with the new blocks: dropped and reverted transfers fail,
+    /// included ones gain confirmations until the NFT changes hands
+    async fn process_transfers(&self, included: &HashMap<&str, (u64, &IncludedTransaction)>, dropped: &HashSet<&str>) {
+        for transfer in self.get_queued_transfers() {
+            let hash = transfer.transaction_hash.as_str();
+            let known_block = transfer.block_number.map(|block| block as u64);
+            let (status, block_number) = if dropped.contains(hash) {
+                (MintStatus::Failed, None)
+            } else if let Some(&(block_number, inclusion)) = included.get(hash) {
+                if inclusion.revert_reason.is_some() {
+                    (MintStatus::Failed, Some(block_number))
+                } else {
+                    (self.transfer_status(block_number), Some(block_number))
+                }
+            } else if let Some(block_number) = known_block {
+                (self.transfer_status(block_number), Some(block_number))
+            } else {
+                (MintStatus::Pending, None)
+            };
+            let unchanged = status.as_str() == transfer.status && block_number == known_block;
+            if unchanged && !self.unsaved_rollbacks.lock().unwrap().contains_key(&transfer.id) {
+                continue;
+            }
+
+            let transfer_id = transfer.id.clone();
+            match self.save_transfer(transfer, status, block_number).await {
+                Ok(()) => tracing::info!("Transfer {} is {:?} in block {:?}", transfer_id, status, block_number),
+                Err(e) => {
+                    tracing::error!("Failed to persist transfer {}, retrying on the next pass: {}", transfer_id, e);
+                    // The block is only reported once, so the retry needs it to work out the status again
+                    if let Some(cached) = self.pending_transfers.lock().unwrap().get_mut(&transfer_id) {
+                        cached.block_number = block_number.map(|block| block as i64);
+                    }
                 }
             }
         }
     }
 
-    /// Remove confirmed mint from queue
-    pub fn remove_mint(&self, mint_id: &str) {
-        let mut pending = self.pending_mints.lock().unwrap();
-        pending.remove(mint_id);
+    fn transfer_status(&self, block_number: u64) -> MintStatus {
+        if self.simulator.confirmations(block_number) >= self.simulator.required_confirmations() {
+            MintStatus::Confirmed
+        } else {
+            MintStatus::Confirming
+        }
+    }
+
+    /// Put transfers included in orphaned blocks back to pending, newest first, handing each NFT
+    /// back to its sender, or unburning it, where the transfer had already been confirmed
+    async fn roll_back_transfers(&self, orphaned_in: &HashMap<&str, u64>, reloaded: Vec<TransferModel>) {
+        let mut transfers: Vec<TransferModel> = self
+            .get_queued_transfers()
+            .into_iter()
+            .chain(reloaded)
+            .filter(|transfer| transfer.block_number.is_some() && orphaned_in.contains_key(transfer.transaction_hash.as_str()))
+            .collect();
+        transfers.sort_by_key(|transfer| std::cmp::Reverse(transfer.created_at));
+
+        for transfer in transfers {
+            self.roll_back_transfer(transfer).await;
+        }
+    }
+
+    /// Put a transfer back to pending. A rollback that can't be saved is still applied to the
+    /// cached transfer, so it follows its transaction into a new block, and its next save retries it.
+    async fn roll_back_transfer(&self, transfer: TransferModel) {
+        *self.unsaved_rollbacks.lock().unwrap().entry(transfer.id.clone()).or_default() |=
+            transfer.status == MintStatus::Confirmed.as_str();
+        let rolled_back = TransferModel {
+            status: MintStatus::Pending.as_str().to_string(),
+            block_number: None,
+            confirmed_at: None,
+            ..transfer
+        };
+
+        let transfer_id = rolled_back.id.clone();
+        match self.save_transfer(rolled_back.clone(), MintStatus::Pending, None).await {
+            Ok(()) => tracing::warn!("Transfer {} is pending again: its block was orphaned", transfer_id),
+            Err(e) => {
+                tracing::error!("Failed to persist rollback of transfer {}, retrying on the next pass: {}", transfer_id, e);
+                self.pending_transfers.lock().unwrap().insert(transfer_id, rolled_back);
+            }
+        }
     }
 
-    /// Get all pending mints
+    /// Get all mints the worker is currently tracking
     pub fn get_pending_mints(&self) -> Vec<MintingStatus> {
         let pending = self.pending_mints.lock().unwrap();
-        pending.values().map(|v| v.clone()).collect()
+        pending.values().map(|queued| queued.minting_status.clone()).collect()
     }
 
-    /// Start the background worker that simulates transaction confirmations
-    fn start_confirmation_worker(&self) {
-        let pending_mints = Arc::clone(&self.pending_mints);
-        
+    fn get_queued_mints(&self) -> Vec<QueuedMint> {
+        let pending = self.pending_mints.lock().unwrap();
+        pending.values().cloned().collect()
+    }
+
+    /// Start the background worker that drives the simulated chain and confirmations
+    pub fn start_confirmation_worker(&self) {
+        let queue = self.clone();
+
         tokio::spawn(async move {
             loop {
                 sleep(Duration::from_secs(5)).await; // Check every 5 seconds
-                
-                let mut to_update = Vec::new();
-                let mut to_remove = Vec::new();
-                
-                {
-                    let mut pending = pending_mints.lock().unwrap();
-                    
-                    for (mint_id, mint_status) in pending.iter_mut() {
-                        match mint_status.status {
-                            MintStatus::Pending => {
-                                // Move to confirming after a short delay
-                                let elapsed = BlockchainSimulator::current_timestamp() - mint_status.created_at;
-                                if elapsed >= 3 {
-                                    mint_status.status = MintStatus::Confirming;
-                                    to_update.push((mint_id.clone(), MintStatus::Confirming));
-                                }
-                            }
-                            MintStatus::Confirming => {
-                                // Simulate confirmation after random delay
-                                let elapsed = BlockchainSimulator::current_timestamp() - mint_status.created_at;
-                                let confirmation_delay = BlockchainSimulator::get_confirmation_delay();
-                                
-                                if elapsed >= confirmation_delay {
-                                    mint_status.status = MintStatus::Confirmed;
-                                    mint_status.confirmed_at = Some(BlockchainSimulator::current_timestamp());
-                                    
-                                    if let Some(ref mut tx) = mint_status.transaction_details {
-                                        tx.status = TransactionStatus::Confirmed;
-                                        tx.confirmations = 12;
-                                    }
-                                    
-                                    to_update.push((mint_id.clone(), MintStatus::Confirmed));
-                                    to_remove.push(mint_id.clone());
-                                }
-                            }
-                            MintStatus::Confirmed => {
-                                // Keep confirmed mints for a while before removing
-                                if let Some(confirmed_at) = mint_status.confirmed_at {
-                                    let elapsed = BlockchainSimulator::current_timestamp() - confirmed_at;
-                                    if elapsed >= 300 { // Remove after 5 minutes
-                                        to_remove.push(mint_id.clone());
-                                    }
-                                }
-                            }
-                            MintStatus::Failed => {
-                                // Remove failed mints after a delay
-                                let elapsed = BlockchainSimulator::current_timestamp() - mint_status.created_at;
-                                if elapsed >= 60 { // Remove after 1 minute
-                                    to_remove.push(mint_id.clone());
-                                }
+                queue.process_pending().await;
+            }
+        });
+    }
+
+    /// Roll back every mint included in a block a reorg orphaned. Mints that already left the
+    /// cache are reloaded from the database first, so a confirmed mint can't miss its rollback.
+    async fn roll_back_orphaned(&self, orphaned: &[Block]) {
+        for block in orphaned {
+            tracing::warn!(
+                "Block {} ({}) was orphaned by a reorg with {} transactions",
+                block.number, block.hash, block.transactions.len()
+            );
+        }
+        if let Err(e) = record_orphaned_blocks(&self.pool, orphaned).await {
+            tracing::error!("Failed to record transactions of {} orphaned blocks: {}", orphaned.len(), e);
+        }
+
+        let orphaned_in: HashMap<&str, u64> = orphaned
+            .iter()
+            .flat_map(|block| block.transactions.iter().map(move |tx| (tx.hash.as_str(), block.number)))
+            .collect();
+        if orphaned_in.is_empty() {
+            return;
+        }
+
+        let queued = self.get_queued_mints();
+        let queued_transfers = self.get_queued_transfers();
+        let cached: HashSet<&str> = queued
+            .iter()
+            .filter_map(|queued| queued.minting_status.transaction_details.as_ref())
+            .map(|tx| tx.transaction_hash.as_str())
+            .chain(queued_transfers.iter().map(|transfer| transfer.transaction_hash.as_str()))
+            .collect();
+        let uncached: Vec<String> = orphaned_in
+            .keys()
+            .filter(|hash| !cached.contains(*hash))
+            .map(|hash| hash.to_string())
+            .collect();
+        let mut reloaded_transfers = Vec::new();
+        if !uncached.is_empty() {
+            let mut remaining = uncached.clone();
+            match find_mint_jobs_by_transaction_hashes(&self.pool, &uncached).await {
+                Ok(jobs) => {
+                    remaining.retain(|hash| !jobs.iter().any(|(job, _)| &job.transaction_hash == hash));
+                    let mut pending = self.pending_mints.lock().unwrap();
+                    for (job, wallet_address) in jobs {
+                        match minting_status_from_job(&job) {
+                            Ok(minting_status) => {
+                                pending.insert(job.id, QueuedMint { wallet_address, minting_status });
                             }
+                            Err(e) => tracing::error!("Failed to reload mint {} for rollback: {}", job.id, e),
                         }
                     }
-                    
-                    // Remove completed mints
-                    for mint_id in to_remove {
-                        pending.remove(&mint_id);
+                }
+                Err(e) => tracing::error!("Failed to load mints of {} orphaned transactions: {}", uncached.len(), e),
+            }
+
+            // Whatever isn't a mint may be a transfer that was already confirmed
+            if !remaining.is_empty() {
+                match find_transfers_by_transaction_hashes(&self.pool, &remaining).await {
+                    Ok(transfers) => reloaded_transfers = transfers,
+                    Err(e) => tracing::error!("Failed to load transfers of {} orphaned transactions: {}", remaining.len(), e),
+                }
+            }
+        }
+        self.roll_back_transfers(&orphaned_in, reloaded_transfers).await;
+
+        for QueuedMint { minting_status: mint_status, .. } in self.get_queued_mints() {
+            let Some(tx) = mint_status.transaction_details.filter(|tx| tx.block_number.is_some()) else { continue };
+            let Some(&block_number) = orphaned_in.get(tx.transaction_hash.as_str()) else { continue };
+            match self.roll_back_mint(&mint_status.mint_id, block_number).await {
+                Ok(_) => tracing::warn!("Mint {} is pending again: block {} was orphaned", mint_status.mint_id, block_number),
+                Err(e) => tracing::error!("Failed to persist rollback of mint {}: {}", mint_status.mint_id, e),
+            }
+        }
+    }
+
+    /// Produce any blocks that are due, then bring every cached mint up to date with the new head.
+    /// Mints in blocks a reorg orphaned are rolled back before the new blocks are applied.
+    pub async fn process_pending(&self) {
+        let _processing = self.processing.lock().await;
+        let ChainUpdate { blocks, orphaned, dropped } = self.simulator.produce_blocks();
+        if !orphaned.is_empty() {
+            self.roll_back_orphaned(&orphaned).await;
+        }
+        for block in &blocks {
+            tracing::debug!(
+                "Produced block {} at {} with {} transactions ({} gas)",
+                block.number, block.timestamp, block.transactions.len(), block.gas_used
+            );
+            if let Err(e) = record_block_inclusion(&self.pool, block).await {
+                tracing::error!("Failed to record transactions included in block {}: {}", block.number, e);
+            }
+        }
+        if let Err(e) = record_dropped_transactions(&self.pool, &dropped).await {
+            tracing::error!("Failed to record {} dropped transactions: {}", dropped.len(), e);
+        }
+        let included: HashMap<&str, (u64, &IncludedTransaction)> = blocks
+            .iter()
+            .flat_map(|block| block.transactions.iter().map(move |tx| (tx.hash.as_str(), (block.number, tx))))
+            .collect();
+        let dropped: HashSet<&str> = dropped.iter().map(String::as_str).collect();
+
+        let now = self.simulator.current_timestamp();
+        let mut to_update = Vec::new();
+        let mut to_fail = Vec::new();
+        let mut to_cancel = Vec::new();
+        let mut to_remove = Vec::new();
+
+        for QueuedMint { minting_status: mint_status, .. } in self.get_queued_mints() {
+            let mint_id = mint_status.mint_id.clone();
+            match mint_status.status {
+                MintStatus::Pending | MintStatus::Confirming => {
+                    let Some(tx) = mint_status.transaction_details else { continue };
+                    let hash = tx.transaction_hash.as_str();
+                    if dropped.contains(hash) {
+                        to_fail.push((mint_id, None, None));
+                        continue;
+                    }
+                    if let Some(&(block_number, inclusion)) = included.get(hash) {
+                        self.record_effective_gas_price(&mint_id, inclusion.effective_gas_price);
+                        if tx.cancellation {
+                            to_cancel.push((mint_id, block_number, tx.transaction_hash.clone()));
+                            continue;
+                        }
+                        if let Some(ref revert_reason) = inclusion.revert_reason {
+                            to_fail.push((mint_id, Some(block_number), Some(revert_reason.clone())));
+                            continue;
+                        }
+                    }
+                    // Several jobs may share a transaction; each one follows its block
+                    let Some(block_number) = tx.block_number.or_else(|| included.get(hash).map(|&(block, _)| block)) else {
+                        continue;
+                    };
+                    let confirmations = self.simulator.confirmations(block_number);
+                    if tx.block_number != Some(block_number) || tx.confirmations != confirmations {
+                        to_update.push((mint_id, block_number, confirmations));
                     }
                 }
-                
-                // Log updates
-                for (mint_id, status) in to_update {
-                    tracing::info!("Mint {} status updated to {:?}", mint_id, status);
+                MintStatus::Confirmed => {
+                    // Keep confirmed mints for a while before removing
+                    if let Some(confirmed_at) = mint_status.confirmed_at {
+                        let elapsed = now.saturating_sub(confirmed_at);
+                        if elapsed >= 300 { // Remove after 5 minutes
+                            to_remove.push(mint_id);
+                        }
+                    }
+                }
+                MintStatus::Failed => {
+                    // Remove failed mints after a delay
+                    let elapsed = now.saturating_sub(mint_status.created_at);
+                    if elapsed >= 60 { // Remove after 1 minute
+                        to_remove.push(mint_id);
+                    }
                 }
             }
-        });
+        }
+
+        for (mint_id, block_number, confirmations) in to_update {
+            match self.update_confirmations(&mint_id, block_number, confirmations).await {
+                Ok(status) => tracing::info!(
+                    "Mint {} is {:?} in block {} with {} confirmations", mint_id, status, block_number, confirmations
+                ),
+                Err(e) => tracing::error!("Failed to persist confirmations for mint {}: {}", mint_id, e),
+            }
+        }
+
+        for (mint_id, block_number, revert_reason) in to_fail {
+            let reason = revert_reason.clone().unwrap_or_else(|| "dropped from the mempool".to_string());
+            match self.fail_mint(&mint_id, block_number, revert_reason).await {
+                Ok(_) => tracing::warn!("Mint {} failed: {}", mint_id, reason),
+                Err(e) => tracing::error!("Failed to persist failure of mint {}: {}", mint_id, e),
+            }
+        }
+
+        for (mint_id, block_number, cancellation_hash) in to_cancel {
+            // The cancellation itself succeeded; it is the mint that failed
+            if let Err(e) = update_transaction_status(self.pool.as_ref(), &cancellation_hash, TransactionStatus::Confirmed).await {
+                tracing::error!("Failed to record cancellation {} of mint {}: {}", cancellation_hash, mint_id, e);
+            }
+            match self.fail_mint(&mint_id, Some(block_number), Some(MINT_CANCELLED_REASON.to_string())).await {
+                Ok(_) => tracing::info!("Mint {} was cancelled in block {}", mint_id, block_number),
+                Err(e) => tracing::error!("Failed to persist cancellation of mint {}: {}", mint_id, e),
+            }
+        }
+
+        self.process_transfers(&included, &dropped).await;
+
+        // Finished jobs stay queryable through the database
+        let mut pending = self.pending_mints.lock().unwrap();
+        for mint_id in to_remove {
+            pending.remove(&mint_id);
+        }
     }
 }
 
-impl Default for MintingQueue {
-    fn default() -> Self {
-        Self::new()
+/// A recorded transaction as the simulator tracks it, for putting it back into the mempool
+fn transaction_details_from_row(row: &ChainTransactionModel) -> TransactionDetails {
+    TransactionDetails {
+        transaction_hash: row.hash.clone(),
+        block_number: row.block_number.map(|block| block as u64),
+        gas_used: row.gas_used as u64,
+        gas_price: row.gas_price,
+        max_fee_per_gas: row.max_fee_per_gas,
+        max_priority_fee_per_gas: row.max_priority_fee_per_gas,
+        status: TransactionStatus::Pending,
+        timestamp: row.created_at.and_utc().timestamp() as u64,
+        confirmations: 0,
+        revert_reason: None,
+        simulated_failure: None,
+        from: Some(row.from_address.clone()),
+        nonce: row.nonce.map(|nonce| nonce as u64),
+        cancellation: false,
     }
 }
 
+fn minting_status_from_job(job: &MintJobModel) -> Result<MintingStatus> {
+    let status: MintStatus = job.status.parse()?;
+    let transaction_status = match status {
+        MintStatus::Confirmed => TransactionStatus::Confirmed,
+        // Only transactions that were mined have a revert reason
+        MintStatus::Failed if job.revert_reason.is_none() => TransactionStatus::Dropped,
+        MintStatus::Failed => TransactionStatus::Failed,
+        MintStatus::Pending | MintStatus::Confirming => TransactionStatus::Pending,
+    };
+    let created_at = job.created_at.and_utc().timestamp() as u64;
+
+    Ok(MintingStatus {
+        mint_id: job.id.clone(),
+        status,
+        transaction_details: Some(TransactionDetails {
+            transaction_hash: job.transaction_hash.clone(),
+            block_number: job.block_number.map(|block| block as u64),
+            gas_used: job.gas_used as u64,
+            gas_price: job.gas_price,
+            max_fee_per_gas: job.max_fee_per_gas,
+            max_priority_fee_per_gas: job.max_priority_fee_per_gas,
+            status: transaction_status,
+            timestamp: created_at,
+            confirmations: job.confirmations as u32,
+            revert_reason: job.revert_reason.clone(),
+            simulated_failure: job.simulated_failure.as_deref().and_then(|failure| failure.parse().ok()),
+            from: job.from_address.clone(),
+            nonce: job.nonce.map(|nonce| nonce as u64),
+            cancellation: job.cancelled,
+        }),
+        created_at,
+        confirmed_at: job.confirmed_at.map(|at| at.and_utc().timestamp() as u64),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blockchain_sim::{
+        BlockchainSimulator, Clock, SimulatedFailure, SimulatorConfig, TransactionRequest, DEFAULT_BLOCK_TIME,
+        DEFAULT_DROP_TIMEOUT, GENESIS_BLOCK,
+    };
+    use sea_orm::{DatabaseBackend, DbErr, MockDatabase, MockExecResult, Value};
+    use std::collections::BTreeMap;
+
+    const START: u64 = 1_700_000_000;
+
+    fn mock_pool(db: MockDatabase) -> DbPool {
+        Arc::new(db.into_connection())
+    }
+
+    fn updated_rows() -> MockExecResult {
+        MockExecResult { last_insert_id: 0, rows_affected: 1 }
+    }
+
+    fn simulator() -> SharedSimulator {
+        let config = SimulatorConfig { seed: Some(7), required_confirmations: 2, ..SimulatorConfig::default() };
+        Arc::new(BlockchainSimulator::new(config, Clock::manual(START)))
+    }
+
+    fn mint_job(mint_id: &str, nft_id: &str, status: MintStatus) -> MintJobModel {
+        let now = chrono::Utc::now().naive_utc();
+        MintJobModel {
+            id: mint_id.to_string(),
+            nft_id: nft_id.to_string(),
+            status: status.as_str().to_string(),
+            transaction_hash: format!("0x{}", "ab".repeat(32)),
+            block_number: Some(19_000_100),
+            gas_used: 200_000,
+            gas_price: Wei::from_gwei(30),
+            confirmations: 0,
+            created_at: now,
+            updated_at: now,
+            confirmed_at: None,
+            simulated_failure: None,
+            revert_reason: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            from_address: None,
+            nonce: None,
+            cancelled: false,
+            batch_id: None,
+        }
+    }
+
+    fn transaction_row(simulator: &SharedSimulator, tx: &TransactionDetails) -> ChainTransactionModel {
+        let now = chrono::Utc::now().naive_utc();
+        ChainTransactionModel {
+            hash: tx.transaction_hash.clone(),
+            from_address: tx.from.clone().unwrap_or_default(),
+            to_address: simulator.contract_address().to_string(),
+            nft_id: Some("nft_1".to_string()),
+            status: TransactionStatus::Pending.as_str().to_string(),
+            block_number: None,
+            block_timestamp: None,
+            gas_used: tx.gas_used as i64,
+            gas_price: tx.gas_price,
+            logs: serde_json::json!([]),
+            revert_reason: None,
+            created_at: now,
+            updated_at: now,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+            base_fee_per_gas: None,
+            block_hash: None,
+            nonce: tx.nonce.map(|nonce| nonce as i64),
+            replaced_by: None,
+        }
+    }
+
+    fn nft(nft_id: &str, owner_id: &str) -> NftModel {
+        NftModel {
+            id: nft_id.to_string(),
+            token_id: "NFT-1".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            image: "https://example.com/1.png".to_string(),
+            minted_at: chrono::Utc::now().naive_utc(),
+            transaction_hash: None,
+            owner_id: owner_id.to_string(),
+            attributes: None,
+            collection_name: None,
+            collection_id: None,
+            mint_status: MintStatus::Pending.as_str().to_string(),
+            burned_at: None,
+        }
+    }
+
+    fn new_nft(nft: &NftModel) -> NewNft {
+        NewNft {
+            token_id: String::new(),
+            name: nft.name.clone(),
+            description: None,
+            image: nft.image.clone(),
+            owner_id: nft.owner_id.clone(),
+            transaction_hash: None,
+            attributes: None,
+            collection: None,
+        }
+    }
+
+    /// Track a mint whose transaction is already in the mempool, as `add_mint` leaves it once stored
+    fn track_mint(queue: &MintingQueue, mint_id: &str, transaction_details: TransactionDetails) {
+        queue.track("0xabc", MintingStatus {
+            mint_id: mint_id.to_string(),
+            status: MintStatus::Pending,
+            transaction_details: Some(transaction_details),
+            created_at: START,
+            confirmed_at: None,
+        });
+    }
 
     #[tokio::test]
     async fn test_minting_queue() {
-        let queue = MintingQueue::new();
         let mint_id = cuid::cuid2();
-        
-        // Add a mint
-        let status = queue.add_mint(mint_id.clone());
+        // The inserted transaction row isn't read back
+        let other = simulator();
+        let row = transaction_row(&other, &other.submit_transaction("0xabc", TransactionRequest::default()));
+        let nft = nft("nft_1", "user_1");
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // The last token ID outside any collection, then the NFT, its transaction and its job
+            .append_query_results([vec![BTreeMap::from([("last_token_id", Value::BigInt(Some(7)))])]])
+            .append_query_results([vec![nft.clone()]])
+            .append_query_results([vec![row]])
+            .append_query_results([vec![mint_job(&mint_id, &nft.id, MintStatus::Pending)]])
+            // The token ID lock
+            .append_exec_results([updated_rows()]);
+        let simulator = simulator();
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+        let mut events = queue.subscribe();
+
+        // Add a mint; its transaction is sent once everything is stored
+        let (minted, status) = queue
+            .add_mint(mint_id.clone(), "0xabc", new_nft(&nft), TransactionRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(minted.id, nft.id);
         assert_eq!(status.status, MintStatus::Pending);
         assert_eq!(status.mint_id, mint_id);
-        
+        assert_eq!(status.transaction_details.unwrap().nonce, Some(0));
+        assert_eq!(simulator.pending_nonce("0xabc"), 1);
+
         // Get status
-        let retrieved = queue.get_mint_status(&mint_id);
+        let retrieved = queue.get_mint_status(&mint_id).await.unwrap();
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().status, MintStatus::Pending);
+
+        // Subscribers see the new mint
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.wallet_address, "0xabc");
+        assert_eq!(event.minting_status.mint_id, mint_id);
+    }
+
+    #[tokio::test]
+    async fn test_mint_that_fails_to_be_stored_is_never_sent() {
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![BTreeMap::from([("last_token_id", Value::BigInt(None))])]])
+            .append_query_errors([DbErr::Custom("insert failed".to_string())])
+            .append_exec_results([updated_rows()]);
+        let simulator = simulator();
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+        let mut events = queue.subscribe();
+
+        let mint_id = cuid::cuid2();
+        let result = queue.add_mint(mint_id.clone(), "0xabc", new_nft(&nft("nft_1", "user_1")), TransactionRequest::default()).await;
+        assert!(result.is_err());
+        assert!(queue.get_cached(&mint_id).is_none());
+        assert!(events.try_recv().is_err());
+
+        // Nothing reached the mempool, so the account's nonces have no gap
+        assert_eq!(simulator.pending_nonce("0xabc"), 0);
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        assert!(simulator.produce_blocks().blocks[0].transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resume_pending_after_restart() {
+        let mint_id = cuid::cuid2();
+        let now = chrono::Utc::now().naive_utc();
+        let owner = crate::entities::UserModel {
+            id: "user_1".to_string(),
+            public_key: "0xabc".to_string(),
+            created_at: now,
+            role: "user".to_string(),
+        };
+        let nft = nft("nft_1", &owner.id);
+        // A job still in the mempool when the server stopped
+        let queued_id = cuid::cuid2();
+        let queued = MintJobModel {
+            transaction_hash: format!("0x{}", "cd".repeat(32)),
+            block_number: None,
+            ..mint_job(&queued_id, &nft.id, MintStatus::Pending)
+        };
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![BTreeMap::from([("block_number", Value::BigInt(Some(19_000_100)))])]])
+            .append_query_results([vec![BTreeMap::from([
+                ("from_address", Value::String(Some(Box::new("0xABC".to_string())))),
+                ("nonce", Value::BigInt(Some(4))),
+            ])]])
+            .append_query_results([vec![mint_job(&mint_id, &nft.id, MintStatus::Confirming), queued.clone()]])
+            .append_query_results([vec![(nft, owner)]])
+            // No transfers were in flight
+            .append_query_results([Vec::<TransferModel>::new()])
+            // Both jobs advance on the next block: one gains a confirmation, the other is included
+            .append_query_results([vec![queued.clone()], vec![queued]])
+            // The transaction row records its block, and each job's NFT follows its status
+            .append_exec_results([updated_rows(), updated_rows(), updated_rows()]);
+        let simulator = simulator();
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+
+        assert_eq!(queue.resume_pending().await.unwrap(), 2);
+        assert_eq!(simulator.head_block_number(), 19_000_100);
+        assert_eq!(simulator.pending_nonce("0xabc"), 5);
+
+        let resumed = queue.get_mint_status(&mint_id).await.unwrap().unwrap();
+        assert_eq!(resumed.status, MintStatus::Confirming);
+        assert_eq!(resumed.transaction_details.unwrap().block_number, Some(19_000_100));
+
+        // The chain continues from the persisted head and picks the queued transaction back up
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        let included = queue.get_mint_status(&queued_id).await.unwrap().unwrap();
+        assert_eq!(included.status, MintStatus::Confirming);
+        assert_eq!(included.transaction_details.unwrap().block_number, Some(19_000_101));
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_fast_forward_confirms_mint() {
+        let mint_id = cuid::cuid2();
+        let job = mint_job(&mint_id, "nft_1", MintStatus::Pending);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![job.clone()], vec![job.clone()], vec![job]])
+            // The transaction row is updated when it is included and again when the mint confirms,
+            // and the NFT row with every status change
+            .append_exec_results([updated_rows(), updated_rows(), updated_rows(), updated_rows(), updated_rows()]);
+        let simulator = simulator();
+        let clock = simulator.clock();
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+
+        let transaction_details = simulator.submit_transaction("0xabc", TransactionRequest::default());
+        track_mint(&queue, &mint_id, transaction_details);
+
+        // Nothing moves until the next block is due
+        clock.advance(DEFAULT_BLOCK_TIME - 1);
+        queue.process_pending().await;
+        assert_eq!(queue.get_mint_status(&mint_id).await.unwrap().unwrap().status, MintStatus::Pending);
+
+        clock.advance(1);
+        queue.process_pending().await;
+        let status = queue.get_mint_status(&mint_id).await.unwrap().unwrap();
+        assert_eq!(status.status, MintStatus::Confirming);
+        let tx = status.transaction_details.unwrap();
+        assert_eq!((tx.block_number, tx.confirmations), (Some(GENESIS_BLOCK + 1), 0));
+
+        clock.advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        let status = queue.get_mint_status(&mint_id).await.unwrap().unwrap();
+        assert_eq!(status.status, MintStatus::Confirming);
+        assert_eq!(status.transaction_details.unwrap().confirmations, 1);
+
+        clock.advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        let status = queue.get_mint_status(&mint_id).await.unwrap().unwrap();
+        assert_eq!(status.status, MintStatus::Confirmed);
+        assert_eq!(status.confirmed_at, Some(START + 3 * DEFAULT_BLOCK_TIME));
+        assert_eq!(status.transaction_details.unwrap().confirmations, 2);
+    }
+
+    #[tokio::test]
+    async fn test_reverted_and_dropped_mints_fail() {
+        let (reverted_id, dropped_id) = (cuid::cuid2(), cuid::cuid2());
+        let reverted_job = mint_job(&reverted_id, "nft_1", MintStatus::Pending);
+        let dropped_job = mint_job(&dropped_id, "nft_2", MintStatus::Pending);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![reverted_job], vec![dropped_job]])
+            // Inclusion and the revert reason, the reverted NFT, then the dropped transaction and its NFT
+            .append_exec_results([updated_rows(), updated_rows(), updated_rows(), updated_rows(), updated_rows()]);
+        let simulator = simulator();
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+
+        for (mint_id, failure) in [(&reverted_id, SimulatedFailure::Revert), (&dropped_id, SimulatedFailure::Dropped)] {
+            let transaction_details = simulator.submit_transaction("0xabc", TransactionRequest { failure: Some(failure), ..TransactionRequest::default() });
+            track_mint(&queue, mint_id, transaction_details);
+        }
+
+        // The reverting transaction is mined and fails; the doomed one is never included
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        let reverted = queue.get_mint_status(&reverted_id).await.unwrap().unwrap();
+        assert_eq!(reverted.status, MintStatus::Failed);
+        let tx = reverted.transaction_details.unwrap();
+        assert_eq!((tx.status, tx.block_number), (TransactionStatus::Failed, Some(GENESIS_BLOCK + 1)));
+        assert_eq!(tx.revert_reason.as_deref(), Some(crate::blockchain_sim::failure::REVERT_REASON));
+        assert_eq!(queue.get_mint_status(&dropped_id).await.unwrap().unwrap().status, MintStatus::Pending);
+
+        simulator.clock().advance(DEFAULT_DROP_TIMEOUT);
+        queue.process_pending().await;
+        let dropped = queue.get_mint_status(&dropped_id).await.unwrap().unwrap();
+        assert_eq!(dropped.status, MintStatus::Failed);
+        let tx = dropped.transaction_details.unwrap();
+        assert_eq!((tx.status, tx.block_number, tx.revert_reason), (TransactionStatus::Dropped, None, None));
+    }
+
+    #[tokio::test]
+    async fn test_reorg_rolls_back_confirmed_mint() {
+        let mint_id = cuid::cuid2();
+        let job = mint_job(&mint_id, "nft_1", MintStatus::Pending);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![job.clone()], vec![job.clone()], vec![job.clone()], vec![job]])
+            // Inclusion, confirmation and their NFT rows; then the orphaned transaction, the rolled back
+            // NFT, the new inclusion and its NFT
+            .append_exec_results((0..8).map(|_| updated_rows()));
+        let simulator = simulator();
+        let clock = simulator.clock();
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+
+        let transaction_details = simulator.submit_transaction("0xabc", TransactionRequest::default());
+        track_mint(&queue, &mint_id, transaction_details);
+        clock.advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        clock.advance(2 * DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        assert_eq!(queue.get_mint_status(&mint_id).await.unwrap().unwrap().status, MintStatus::Confirmed);
+
+        // Orphan every block since genesis; their replacements are produced straight away
+        let mut events = queue.subscribe();
+        assert_eq!(simulator.reorg(3), 3);
+        queue.process_pending().await;
+
+        let rollback = events.try_recv().unwrap();
+        assert_eq!(rollback.orphaned_block, Some(GENESIS_BLOCK + 1));
+        assert_eq!(rollback.minting_status.status, MintStatus::Pending);
+        assert_eq!(rollback.minting_status.confirmed_at, None);
+        assert_eq!(rollback.minting_status.transaction_details.unwrap().block_number, None);
+
+        // The transaction lands in a later block than before, with fewer confirmations
+        let status = queue.get_mint_status(&mint_id).await.unwrap().unwrap();
+        assert_eq!(status.status, MintStatus::Confirming);
+        let tx = status.transaction_details.unwrap();
+        assert_eq!((tx.block_number, tx.confirmations, tx.status), (Some(GENESIS_BLOCK + 2), 1, TransactionStatus::Pending));
+        assert_eq!(events.try_recv().unwrap().orphaned_block, None);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_mint_fails() {
+        let mint_id = cuid::cuid2();
+        let job = mint_job(&mint_id, "nft_1", MintStatus::Pending);
+        let simulator = simulator();
+        let original = simulator.submit_transaction("0xabc", TransactionRequest::default());
+        let original_row = transaction_row(&simulator, &original);
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // The original transaction, then the cancellation's new row
+            .append_query_results([vec![original_row.clone()], vec![original_row]])
+            .append_query_results([vec![job.clone()], vec![job]])
+            // The original marked replaced and the NFT row; then inclusion, the cancellation's status and the NFT row
+            .append_exec_results((0..5).map(|_| updated_rows()));
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+        track_mint(&queue, &mint_id, original.clone());
+
+        let cancel = ReplacementRequest { cancel: true, ..ReplacementRequest::default() };
+        let replaced = queue.replace_mint(&mint_id, cancel).await.unwrap();
+        let cancellation = replaced.transaction_details.unwrap();
+        assert_ne!(cancellation.transaction_hash, original.transaction_hash);
+        assert_eq!((cancellation.nonce, cancellation.cancellation), (Some(0), true));
+
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        let status = queue.get_mint_status(&mint_id).await.unwrap().unwrap();
+        assert_eq!(status.status, MintStatus::Failed);
+        let tx = status.transaction_details.unwrap();
+        assert_eq!((tx.block_number, tx.revert_reason.as_deref()), (Some(GENESIS_BLOCK + 1), Some(MINT_CANCELLED_REASON)));
+
+        // A mint that is no longer pending can't be replaced
+        let error = queue.replace_mint(&mint_id, ReplacementRequest::default()).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<ReplacementError>(), Some(&ReplacementError::NotPending));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_changes_owner_on_confirmation() {
+        // The transfer is the first transaction either simulator sees, so both draw the same values for it
+        let expected = simulator().submit_transaction("0xabc", TransactionRequest { gas_used: Some(TRANSFER_GAS_USED), ..TransactionRequest::default() });
+        let simulator = simulator();
+        let clock = simulator.clock();
+        let nft = NftModel { mint_status: MintStatus::Confirmed.as_str().to_string(), ..nft("nft_1", "user_1") };
+        let now = chrono::Utc::now().naive_utc();
+        let transfer_row = TransferModel {
+            id: "transfer_1".to_string(),
+            nft_id: nft.id.clone(),
+            from_address: "0xabc".to_string(),
+            to_address: "0xdef".to_string(),
+            transaction_hash: expected.transaction_hash.clone(),
+            status: MintStatus::Pending.as_str().to_string(),
+            block_number: None,
+            created_at: now,
+            updated_at: now,
+            confirmed_at: None,
+        };
+        let recipient = crate::entities::UserModel {
+            id: "user_2".to_string(),
+            public_key: "0xdef".to_string(),
+            created_at: now,
+            role: "user".to_string(),
+        };
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // The NFT as it is when the transfer is sent, then its transaction and transfer rows
+            .append_query_results([vec![nft.clone()]])
+            .append_query_results([vec![transaction_row(&simulator, &expected)]])
+            .append_query_results([vec![transfer_row]])
+            .append_query_results([vec![recipient]])
+            // Inclusion and the transfer's block; then the new owner, the transaction's status and the transfer
+            .append_exec_results((0..5).map(|_| updated_rows()));
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+
+        let transfer = queue.transfer_nft(&nft, "0xabc", "0xdef", TransactionRequest::default()).await.unwrap();
+        assert_eq!(transfer.transaction_hash, expected.transaction_hash);
+        assert_eq!(queue.pending_transfer(&nft.id).map(|pending| pending.id), Some(transfer.id.clone()));
+
+        clock.advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        let included = queue.pending_transfer(&nft.id).unwrap();
+        assert_eq!(included.status, MintStatus::Confirming.as_str());
+        assert_eq!(included.block_number, Some(GENESIS_BLOCK as i64 + 1));
+
+        // Once confirmed the NFT belongs to the recipient and the transfer leaves the queue
+        clock.advance(2 * DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        assert!(queue.pending_transfer(&nft.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_confirmed_burn_is_undone_by_a_reorg() {
+        let simulator = simulator();
+        let clock = simulator.clock();
+        let transaction = simulator.submit_transaction("0xabc", TransactionRequest { gas_used: Some(TRANSFER_GAS_USED), ..TransactionRequest::default() });
+        let now = chrono::Utc::now().naive_utc();
+        let burn = TransferModel {
+            id: "transfer_1".to_string(),
+            nft_id: "nft_1".to_string(),
+            from_address: "0xabc".to_string(),
+            to_address: ZERO_ADDRESS.to_string(),
+            transaction_hash: transaction.transaction_hash.clone(),
+            status: MintStatus::Pending.as_str().to_string(),
+            block_number: None,
+            created_at: now,
+            updated_at: now,
+            confirmed_at: None,
+        };
+        let burned_at = chrono::DateTime::from_timestamp((START + 3 * DEFAULT_BLOCK_TIME) as i64, 0).unwrap().naive_utc();
+        let confirmed = TransferModel {
+            status: MintStatus::Confirmed.as_str().to_string(),
+            block_number: Some(GENESIS_BLOCK as i64 + 1),
+            confirmed_at: Some(burned_at),
+            ..burn.clone()
+        };
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // After the reorg no mint sent the orphaned transaction, but the confirmed burn did
+            .append_query_results([Vec::<MintJobModel>::new()])
+            .append_query_results([Vec::<(NftModel, Option<crate::entities::UserModel>)>::new()])
+            .append_query_results([vec![confirmed]])
+            // Inclusion and the burn's block; the burned NFT, the transaction's status and the burn;
+            // the orphaned transaction, the unburned NFT and the burn; then its inclusion and block again
+            .append_exec_results((0..10).map(|_| updated_rows()));
+        let pool = mock_pool(db);
+        let queue = MintingQueue::new(pool.clone(), simulator.clone());
+        queue.pending_transfers.lock().unwrap().insert(burn.id.clone(), burn);
+
+        clock.advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        assert_eq!(queue.pending_transfer("nft_1").unwrap().status, MintStatus::Confirming.as_str());
+        clock.advance(2 * DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        assert!(queue.pending_transfer("nft_1").is_none());
+
+        // Orphan every block since genesis; the burn is pending again, then lands in a replacement block
+        assert_eq!(simulator.reorg(3), 3);
+        queue.process_pending().await;
+        let included = queue.pending_transfer("nft_1").unwrap();
+        assert_eq!(included.status, MintStatus::Confirming.as_str());
+        assert_eq!(included.confirmed_at, None);
+
+        drop(queue);
+        let log = Arc::try_unwrap(pool).unwrap().into_transaction_log();
+        let burned_at_values: Vec<Value> = log
+            .iter()
+            .flat_map(|transaction| transaction.statements())
+            .filter(|statement| statement.sql.starts_with(r#"UPDATE "nfts" SET "burned_at""#))
+            .map(|statement| statement.values.as_ref().unwrap().0[0].clone())
+            .collect();
+        // The burn is stamped with the simulator's clock, then cleared by the rollback
+        assert_eq!(burned_at_values, [Value::ChronoDateTime(Some(Box::new(burned_at))), Value::ChronoDateTime(None)]);
+    }
+
+    #[tokio::test]
+    async fn test_unsaved_transfer_rollback_is_retried() {
+        let simulator = simulator();
+        let clock = simulator.clock();
+        let transaction = simulator.submit_transaction("0xabc", TransactionRequest { gas_used: Some(TRANSFER_GAS_USED), ..TransactionRequest::default() });
+        let now = chrono::Utc::now().naive_utc();
+        let transfer = TransferModel {
+            id: "transfer_1".to_string(),
+            nft_id: "nft_1".to_string(),
+            from_address: "0xabc".to_string(),
+            to_address: "0xdef".to_string(),
+            transaction_hash: transaction.transaction_hash.clone(),
+            status: MintStatus::Pending.as_str().to_string(),
+            block_number: None,
+            created_at: now,
+            updated_at: now,
+            confirmed_at: None,
+        };
+        let user = |id: &str, public_key: &str| crate::entities::UserModel {
+            id: id.to_string(),
+            public_key: public_key.to_string(),
+            created_at: now,
+            role: "user".to_string(),
+        };
+        let confirmed_at = chrono::DateTime::from_timestamp((START + 3 * DEFAULT_BLOCK_TIME) as i64, 0).unwrap().naive_utc();
+        let confirmed = TransferModel {
+            status: MintStatus::Confirmed.as_str().to_string(),
+            block_number: Some(GENESIS_BLOCK as i64 + 1),
+            confirmed_at: Some(confirmed_at),
+            ..transfer.clone()
+        };
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // The recipient once the transfer is confirmed; after the reorg no mint sent the orphaned
+            // transaction, but the confirmed transfer did
+            .append_query_results([vec![user("user_2", "0xdef")]])
+            .append_query_results([Vec::<MintJobModel>::new()])
+            .append_query_results([Vec::<(NftModel, Option<crate::entities::UserModel>)>::new()])
+            .append_query_results([vec![confirmed]])
+            // Looking up the sender fails while rolling back and when the transfer is included again
+            .append_query_errors([DbErr::Custom("connection reset".to_string()), DbErr::Custom("connection reset".to_string())])
+            .append_query_results([vec![user("user_1", "0xabc")]])
+            // Inclusion and the transfer's block; confirmation; the orphaned transaction and its
+            // inclusion again; then the NFT handed back with the transfer's new block
+            .append_exec_results((0..9).map(|_| updated_rows()));
+        let pool = mock_pool(db);
+        let queue = MintingQueue::new(pool.clone(), simulator.clone());
+        queue.pending_transfers.lock().unwrap().insert(transfer.id.clone(), transfer.clone());
+
+        clock.advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        clock.advance(2 * DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        assert!(queue.pending_transfer("nft_1").is_none());
+
+        // The rollback isn't saved, yet the transfer keeps the block its transaction is included in again
+        assert_eq!(simulator.reorg(3), 3);
+        queue.process_pending().await;
+        let included = queue.pending_transfer("nft_1").unwrap();
+        assert_eq!(included.status, MintStatus::Pending.as_str());
+        assert_eq!(included.block_number, Some(GENESIS_BLOCK as i64 + 2));
+        assert_eq!(queue.unsaved_rollbacks.lock().unwrap().get(&transfer.id), Some(&true));
+
+        // The next pass saves the rollback along with the new block
+        queue.process_pending().await;
+        let included = queue.pending_transfer("nft_1").unwrap();
+        assert_eq!(included.status, MintStatus::Confirming.as_str());
+        assert_eq!(included.block_number, Some(GENESIS_BLOCK as i64 + 2));
+        assert!(queue.unsaved_rollbacks.lock().unwrap().is_empty());
+
+        drop(queue);
+        let log = Arc::try_unwrap(pool).unwrap().into_transaction_log();
+        let updated_tables = |statements: &[sea_orm::Statement]| -> Vec<String> {
+            statements
+                .iter()
+                .filter_map(|statement| statement.sql.strip_prefix("UPDATE "))
+                .map(|sql| sql.split_whitespace().next().unwrap().trim_matches('"').to_string())
+                .collect()
+        };
+        // Confirmation is stamped with the simulator's clock
+        assert!(log.iter().flat_map(|transaction| transaction.statements()).any(|statement| {
+            statement.sql.starts_with(r#"UPDATE "transfers""#)
+                && statement.values.as_ref().is_some_and(|values| values.0.contains(&Value::ChronoDateTime(Some(Box::new(confirmed_at)))))
+        }));
+        // The NFT goes back to its sender in the same database transaction that saves the transfer
+        assert_eq!(updated_tables(log.last().unwrap().statements()), ["nfts", "transfers"]);
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_transfers_are_never_sent() {
+        let simulator = simulator();
+        let nft = NftModel { mint_status: MintStatus::Confirmed.as_str().to_string(), ..nft("nft_1", "user_1") };
+        let sold = NftModel { owner_id: "user_2".to_string(), ..nft.clone() };
+        let pending = TransferModel {
+            id: "transfer_1".to_string(),
+            nft_id: nft.id.clone(),
+            from_address: "0xabc".to_string(),
+            to_address: "0xdef".to_string(),
+            transaction_hash: "0x01".to_string(),
+            status: MintStatus::Pending.as_str().to_string(),
+            block_number: None,
+            created_at: chrono::Utc::now().naive_utc(),
+            updated_at: chrono::Utc::now().naive_utc(),
+            confirmed_at: None,
+        };
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // The NFT changed hands; then it's unchanged, but another request stored a transfer first
+            .append_query_results([vec![sold], vec![nft.clone()]])
+            .append_query_results([vec![transaction_row(&simulator, &simulator.prepare_transactions("0xabc", &[TransactionRequest::default()])[0])]])
+            .append_query_errors([DbErr::Custom("duplicate key value violates unique constraint".to_string())]);
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+
+        let moved = queue.transfer_nft(&nft, "0xabc", "0xdef", TransactionRequest::default()).await.unwrap_err();
+        assert_eq!(moved.downcast_ref::<TransferConflict>(), Some(&TransferConflict::Moved));
+        assert!(queue.transfer_nft(&nft, "0xabc", "0xdef", TransactionRequest::default()).await.is_err());
+
+        queue.pending_transfers.lock().unwrap().insert(pending.id.clone(), pending);
+        let queued = queue.transfer_nft(&nft, "0xabc", "0xdef", TransactionRequest::default()).await.unwrap_err();
+        assert_eq!(queued.downcast_ref::<TransferConflict>(), Some(&TransferConflict::Pending(Some("0x01".to_string()))));
+
+        // None of them reached the mempool
+        assert_eq!(simulator.pending_nonce("0xabc"), 0);
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        assert!(simulator.produce_blocks().blocks[0].transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_mint_shares_chunk_transactions() {
+        // The inserted transaction rows aren't read back
+        let other = simulator();
+        let row = transaction_row(&other, &other.submit_transaction("0xabc", TransactionRequest::default()));
+        let simulator = simulator();
+        let nfts: Vec<NftModel> = (1..=3).map(|n| nft(&format!("nft_{}", n), "user_1")).collect();
+        let jobs: Vec<MintJobModel> = nfts.iter().map(|nft| mint_job(&cuid::cuid2(), &nft.id, MintStatus::Pending)).collect();
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // The last token ID outside any collection, then the first chunk's NFTs, transaction and jobs, then the second's
+            .append_query_results([vec![BTreeMap::from([("last_token_id", Value::BigInt(Some(7)))])]])
+            .append_query_results([vec![nfts[0].clone()], vec![nfts[1].clone()]])
+            .append_query_results([vec![row.clone()]])
+            .append_query_results([vec![jobs[0].clone()], vec![jobs[1].clone()]])
+            .append_query_results([vec![nfts[2].clone()]])
+            .append_query_results([vec![row]])
+            .append_query_results([vec![jobs[2].clone()]])
+            // Each job's confirmations once the block including both transactions is produced
+            .append_query_results(jobs.iter().map(|job| vec![job.clone()]))
+            // The token ID lock, inclusion of both transactions, then each job's NFT row
+            .append_exec_results((0..6).map(|_| updated_rows()));
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+        let mut events = queue.subscribe();
+
+        let minted = queue
+            .add_batch_mint("batch_1", "0xabc", nfts.iter().map(new_nft).collect(), 2, TransactionRequest::default())
+            .await
+            .unwrap();
+        let transactions: Vec<TransactionDetails> = minted
+            .iter()
+            .map(|(_, minting_status)| minting_status.transaction_details.clone().unwrap())
+            .collect();
+        assert_eq!(minted.iter().map(|(nft, _)| nft.id.as_str()).collect::<Vec<_>>(), ["nft_1", "nft_2", "nft_3"]);
+        assert_eq!(transactions[0].transaction_hash, transactions[1].transaction_hash);
+        assert_ne!(transactions[1].transaction_hash, transactions[2].transaction_hash);
+        assert_eq!((transactions[0].gas_used, transactions[0].nonce), (batch_mint_gas_used(2), Some(0)));
+        assert_eq!((transactions[2].gas_used, transactions[2].nonce), (batch_mint_gas_used(1), Some(1)));
+        for (_, minting_status) in &minted {
+            assert_eq!(events.try_recv().unwrap().minting_status.mint_id, minting_status.mint_id);
+        }
+
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        queue.process_pending().await;
+        for (_, minting_status) in &minted {
+            let status = queue.get_mint_status(&minting_status.mint_id).await.unwrap().unwrap();
+            assert_eq!(status.status, MintStatus::Confirming);
+            assert_eq!(status.transaction_details.unwrap().block_number, Some(GENESIS_BLOCK + 1));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_that_fails_to_be_stored_is_never_sent() {
+        let nfts: Vec<NftModel> = (1..=3).map(|n| nft(&format!("nft_{}", n), "user_1")).collect();
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            // The first chunk's NFTs are stored, then its transaction row fails
+            .append_query_results([vec![BTreeMap::from([("last_token_id", Value::BigInt(None))])]])
+            .append_query_results([vec![nfts[0].clone()], vec![nfts[1].clone()]])
+            .append_query_errors([DbErr::Custom("insert failed".to_string())])
+            .append_exec_results([updated_rows()]);
+        let simulator = simulator();
+        let queue = MintingQueue::new(mock_pool(db), simulator.clone());
+        let mut events = queue.subscribe();
+
+        let result = queue
+            .add_batch_mint("batch_1", "0xabc", nfts.iter().map(new_nft).collect(), 2, TransactionRequest::default())
+            .await;
+        assert!(result.is_err());
+        assert!(queue.get_pending_mints().is_empty());
+        assert!(events.try_recv().is_err());
+
+        // Neither chunk reached the mempool
+        assert_eq!(simulator.pending_nonce("0xabc"), 0);
+        simulator.clock().advance(DEFAULT_BLOCK_TIME);
+        assert!(simulator.produce_blocks().blocks[0].transactions.is_empty());
+    }
+}
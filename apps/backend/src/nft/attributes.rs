@@ -0,0 +1,166 @@
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use crate::nft::types::NftAttribute;
+
+/// A trait's value: text, a number or a boolean. Dates are numbers too, in unix seconds, as
+/// marketplaces expect them.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum AttributeValue {
+    Boolean(bool),
+    Number(serde_json::Number),
+    Text(String),
+}
+
+impl AttributeValue {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AttributeValue::Number(number) => number.as_f64(),
+            _ => None,
+        }
+    }
+}
+
+/// How marketplaces render a trait, following OpenSea's metadata standard
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayType {
+    Number,
+    BoostNumber,
+    BoostPercentage,
+    Date,
+}
+
+impl DisplayType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisplayType::Number => "number",
+            DisplayType::BoostNumber => "boost_number",
+            DisplayType::BoostPercentage => "boost_percentage",
+            DisplayType::Date => "date",
+        }
+    }
+}
+
+/// Parse a date given as `YYYY-MM-DD` (midnight UTC) or RFC 3339 into unix seconds
+pub fn parse_date(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.timestamp());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|datetime| datetime.and_utc().timestamp())
+}
+
+/// Check the attributes sent with a mint and bring them into the stored form.
+///
+/// Blank rows are dropped, trait types are trimmed and dates given as text become unix seconds.
+/// Numeric display types need a number, and `max_value` needs a number no greater than it.
+pub fn normalize_attributes(attributes: Vec<NftAttribute>) -> Result<Vec<NftAttribute>, String> {
+    let mut normalized = Vec::with_capacity(attributes.len());
+
+    for mut attribute in attributes {
+        attribute.trait_type = attribute.trait_type.trim().to_string();
+        if attribute.trait_type.is_empty() {
+            if matches!(&attribute.value, AttributeValue::Text(text) if text.trim().is_empty()) {
+                continue;
+            }
+            return Err("Every attribute needs a trait_type".to_string());
+        }
+
+        match attribute.display_type {
+            Some(DisplayType::Date) => {
+                if let AttributeValue::Text(text) = &attribute.value {
+                    let timestamp = parse_date(text).ok_or_else(|| {
+                        format!(
+                            "Attribute '{}' is a date, but '{}' isn't one; use YYYY-MM-DD, RFC 3339 or unix seconds",
+                            attribute.trait_type, text
+                        )
+                    })?;
+                    attribute.value = AttributeValue::Number(timestamp.into());
+                }
+                if !matches!(&attribute.value, AttributeValue::Number(number) if number.is_i64() || number.is_u64()) {
+                    return Err(format!("Attribute '{}' is a date and needs whole unix seconds", attribute.trait_type));
+                }
+                if attribute.max_value.is_some() {
+                    return Err(format!("Attribute '{}' is a date and can't have a max_value", attribute.trait_type));
+                }
+            }
+            Some(display_type) if !matches!(attribute.value, AttributeValue::Number(_)) => {
+                return Err(format!(
+                    "Attribute '{}' is displayed as {} and needs a numeric value",
+                    attribute.trait_type,
+                    display_type.as_str()
+                ));
+            }
+            _ => {}
+        }
+
+        if let Some(max_value) = &attribute.max_value {
+            let value = attribute
+                .value
+                .as_f64()
+                .ok_or_else(|| format!("Attribute '{}' has a max_value, so its value must be a number", attribute.trait_type))?;
+            if max_value.as_f64().is_some_and(|max_value| value > max_value) {
+                return Err(format!(
+                    "Attribute '{}' is {}, above its max_value of {}",
+                    attribute.trait_type, value, max_value
+                ));
+            }
+        }
+
+        normalized.push(attribute);
+    }
+
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attributes(value: serde_json::Value) -> Vec<NftAttribute> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_typed_attributes() {
+        let normalized = normalize_attributes(attributes(serde_json::json!([
+            { "trait_type": " Power ", "value": 87, "display_type": "number", "max_value": 100 },
+            { "trait_type": "Shiny", "value": true },
+            { "trait_type": "Birthday", "value": "2024-05-01", "display_type": "date" },
+            { "trait_type": "Element", "value": "Fire" },
+            { "trait_type": "", "value": "" },
+        ])))
+        .unwrap();
+
+        assert_eq!(serde_json::to_value(&normalized).unwrap(), serde_json::json!([
+            { "trait_type": "Power", "value": 87, "display_type": "number", "max_value": 100 },
+            { "trait_type": "Shiny", "value": true },
+            { "trait_type": "Birthday", "value": 1714521600, "display_type": "date" },
+            { "trait_type": "Element", "value": "Fire" },
+        ]));
+    }
+
+    #[test]
+    fn test_reject_mistyped_attributes() {
+        let invalid = [
+            serde_json::json!([{ "trait_type": "", "value": "Fire" }]),
+            serde_json::json!([{ "trait_type": "Power", "value": "high", "display_type": "number" }]),
+            serde_json::json!([{ "trait_type": "Birthday", "value": "someday", "display_type": "date" }]),
+            serde_json::json!([{ "trait_type": "Birthday", "value": 1.5, "display_type": "date" }]),
+            serde_json::json!([{ "trait_type": "Power", "value": "87", "max_value": 100 }]),
+            serde_json::json!([{ "trait_type": "Power", "value": 120, "max_value": 100 }]),
+        ];
+        for attributes_json in invalid {
+            assert!(normalize_attributes(attributes(attributes_json.clone())).is_err(), "accepted {}", attributes_json);
+        }
+
+        assert!(serde_json::from_value::<Vec<NftAttribute>>(serde_json::json!([
+            { "trait_type": "Power", "value": 87, "display_type": "stars" },
+        ]))
+        .is_err());
+    }
+}
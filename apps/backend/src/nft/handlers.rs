@@ -1,184 +1,101 @@
+use std::collections::HashMap;
 use axum::{
     extract::{Path, Query, State},
     Json,
-    response::IntoResponse,
-    http::StatusCode,
+    response::{IntoResponse, Response},
+    http::{HeaderMap, StatusCode},
 };
-use serde::Deserialize;
 use crate::{
     database::DbPool,
-    db_operations::{create_nft, find_nft_by_id, get_nfts_with_owner, get_nfts_by_owner, find_user_by_public_key},
-    auth::types::ApiResponse,
-    nft::types::*,
-    blockchain_sim::MintStatus,
+    db_operations::{
+        claim_idempotency_key, complete_idempotency_key, release_idempotency_key, IdempotencyClaim,
+        SupplyExhausted, TransferConflict, find_nft_by_id, find_nft_transfers, find_transaction_with_nft,
+        find_user_by_public_key, find_collection, find_or_create_collection_by_name, NewNft, NftSearch,
+    },
+    auth::{types::ApiResponse, AuthSession},
+    nft::{attributes::normalize_attributes, search::{load_nft_page, SearchQuery}, types::*},
+    pagination::{PaginatedResponse, PaginationQuery},
+    blockchain_sim::{
+        MintStatus, ReplacementError, ReplacementRequest, SimulatedFailure, TransactionRequest, MAX_FEE_PER_GAS, ZERO_ADDRESS,
+    },
+    crypto::{keccak256, parse_address, to_checksum_address},
+    entities::CollectionModel,
+    wei::Wei,
     minting_queue::MintingQueue,
 };
-use std::sync::Arc;
 
-#[derive(Debug, Deserialize)]
-pub struct PaginationQuery {
-    pub page: Option<u64>,
-    pub limit: Option<u64>,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct SearchQuery {
-    pub query: Option<String>,
-    pub rarity: Option<String>,
-    pub collection: Option<String>,
-    pub owner: Option<String>,
-    pub page: Option<u64>,
-    pub limit: Option<u64>,
-}
-
-// Global minting queue instance
-lazy_static::lazy_static! {
-    static ref MINTING_QUEUE: Arc<MintingQueue> = Arc::new(MintingQueue::new());
-}
+/// Request header forcing a mint's transaction to fail: `revert`, `out-of-gas` or `dropped`
+pub const SIMULATE_FAILURE_HEADER: &str = "x-simulate-failure";
+/// Request header making a mint safe to retry: repeats with the same key replay the first response
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Response header marking a replayed response
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+/// How long a key's response is kept for replays
+const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+/// How long a mint may hold its key before recording a response; far longer than any mint takes
+const IDEMPOTENCY_CLAIM_LEASE_MINUTES: i64 = 5;
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+/// Most items one batch request may mint; a single transaction minting them all fits in a block
+const MAX_BATCH_SIZE: usize = 500;
 
 pub async fn get_nfts_handler(
     State(pool): State<DbPool>,
     Query(query): Query<PaginationQuery>,
 ) -> impl IntoResponse {
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.page.unwrap_or(0) * limit;
-
-    match get_nfts_with_owner(&pool, Some(limit), Some(offset)).await {
-        Ok(nfts) => {
-            let nft_responses: Vec<NftWithOwnerResponse> = nfts
-                .into_iter()
-                .map(|(nft, user)| {
-                    // Convert stored JSON attributes back to NftAttribute structs
-                    let attributes = nft.attributes.as_ref().and_then(|attrs| {
-                        serde_json::from_value::<Vec<crate::nft::types::NftAttribute>>(attrs.clone()).ok()
-                    });
-                    
-                    NftWithOwnerResponse {
-                        id: nft.id,
-                        token_id: nft.token_id,
-                        name: nft.name,
-                        description: nft.description,
-                        image: nft.image,
-                        minted_at: chrono::DateTime::from_naive_utc_and_offset(nft.minted_at, chrono::Utc),
-                        transaction_hash: nft.transaction_hash,
-                        owner: UserResponse {
-                            id: user.id,
-                            public_key: user.public_key,
-                            created_at: chrono::DateTime::from_naive_utc_and_offset(user.created_at, chrono::Utc),
-                        },
-                        attributes,
-                        collection_name: nft.collection_name,
-                        mint_status: Some(MintStatus::Confirmed), // All existing NFTs are confirmed
-                        block_number: None,
-                        gas_used: None,
-                        gas_price: None,
-                    }
-                })
-                .collect();
-
-            let total = nft_responses.len() as u64;
+    match load_nft_page(&pool, &NftSearch::default(), &query).await {
+        Ok(page) => {
             let response = ApiResponse {
                 success: true,
-                data: Some(PaginatedResponse {
-                    data: nft_responses,
-                    total,
-                    page: query.page.unwrap_or(0),
-                    limit,
-                }),
+                data: Some(page.map(NftWithOwnerResponse::from)),
                 message: "NFTs retrieved successfully".to_string(),
             };
-
             (StatusCode::OK, Json(response))
         }
-        Err(e) => {
+        Err((status, message)) => {
             let response = ApiResponse::<PaginatedResponse<NftWithOwnerResponse>> {
                 success: false,
                 data: None,
-                message: format!("Failed to retrieve NFTs: {}", e),
+                message,
             };
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+            (status, Json(response))
         }
     }
 }
 
 pub async fn search_nfts_handler(
     State(pool): State<DbPool>,
-    Query(query): Query<SearchQuery>,
+    Query(pairs): Query<Vec<(String, String)>>,
 ) -> impl IntoResponse {
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.page.unwrap_or(0) * limit;
-
-    // Generate demo search results based on query parameters
-    let nfts: Vec<NftWithOwnerResponse> = (offset..offset + limit)
-        .map(|i| {
-            let rarity = if i % 10 == 0 { "Legendary" } else if i % 5 == 0 { "Epic" } else { "Common" };
-            let collection = format!("Collection {}", (i % 10) + 1);
-            let owner = format!("0x{:040x}", i % 100);
-            
-            // Filter based on search criteria
-            let should_include = query.query.as_ref().map_or(true, |q| {
-                format!("NFT-{:06}", i + 1).contains(q) || 
-                format!("Ethereal Dream #{}", i + 1).contains(q)
-            }) && query.rarity.as_ref().map_or(true, |r| rarity == r) &&
-            query.collection.as_ref().map_or(true, |c| collection.contains(c)) &&
-            query.owner.as_ref().map_or(true, |o| owner.contains(o));
-
-            if should_include {
-                Some(NftWithOwnerResponse {
-                    id: format!("nft_{}", i + 1),
-                    token_id: format!("NFT-{:06}", i + 1),
-                    name: format!("Ethereal Dream #{}", i + 1),
-                    description: Some(format!("A beautiful piece from the {} collection", collection)),
-                    image: format!("https://picsum.photos/400/400?random={}", i + 1),
-                    minted_at: chrono::DateTime::from_naive_utc_and_offset(
-                        chrono::Utc::now().naive_utc() - chrono::Duration::hours((i % 168) as i64),
-                        chrono::Utc
-                    ),
-                    transaction_hash: Some(format!("0x{:064x}", i + 1)),
-                    owner: crate::nft::types::UserResponse {
-                        id: format!("user_{}", i % 100),
-                        public_key: owner,
-                        created_at: chrono::DateTime::from_naive_utc_and_offset(
-                            chrono::Utc::now().naive_utc() - chrono::Duration::days((i % 30) as i64),
-                            chrono::Utc
-                        ),
-                    },
-                    attributes: Some(vec![
-                        crate::nft::types::NftAttribute {
-                            trait_type: "Rarity".to_string(),
-                            value: rarity.to_string(),
-                        },
-                        crate::nft::types::NftAttribute {
-                            trait_type: "Element".to_string(),
-                            value: if i % 4 == 0 { "Fire".to_string() } else if i % 4 == 1 { "Water".to_string() } else if i % 4 == 2 { "Earth".to_string() } else { "Air".to_string() },
-                        },
-                    ]),
-                    collection_name: Some(collection),
-                    mint_status: Some(crate::blockchain_sim::MintStatus::Confirmed),
-                    block_number: Some(12345678 + i as u64),
-                    gas_used: Some(210000 + (i * 1000) as u64),
-                    gas_price: Some(20000000000 + (i * 1000000) as u64),
-                })
-            } else {
-                None
-            }
-        })
-        .filter_map(|nft| nft)
-        .collect();
-
-    let total = nfts.len() as u64;
-    let response = ApiResponse {
-        success: true,
-        data: Some(PaginatedResponse {
-            data: nfts,
-            total,
-            page: query.page.unwrap_or(0),
-            limit,
-        }),
-        message: "NFTs search completed successfully".to_string(),
+    let query = match SearchQuery::from_pairs(pairs) {
+        Ok(query) => query,
+        Err(message) => {
+            let response = ApiResponse::<PaginatedResponse<NftWithOwnerResponse>> {
+                success: false,
+                data: None,
+                message,
+            };
+            return (StatusCode::BAD_REQUEST, Json(response));
+        }
     };
 
-    (StatusCode::OK, Json(response))
+    match load_nft_page(&pool, &query.search, &query.pagination).await {
+        Ok(page) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(page.map(NftWithOwnerResponse::from)),
+                message: "NFTs search completed successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err((status, message)) => {
+            let response = ApiResponse::<PaginatedResponse<NftWithOwnerResponse>> {
+                success: false,
+                data: None,
+                message,
+            };
+            (status, Json(response))
+        }
+    }
 }
 
 pub async fn get_nft_by_id_handler(
@@ -189,22 +106,7 @@ pub async fn get_nft_by_id_handler(
         Ok(Some(nft)) => {
             let response = ApiResponse {
                 success: true,
-                data: Some(NftResponse {
-                    id: nft.id,
-                    token_id: nft.token_id,
-                    name: nft.name,
-                    description: nft.description,
-                    image: nft.image,
-                    minted_at: chrono::DateTime::from_naive_utc_and_offset(nft.minted_at, chrono::Utc),
-                    transaction_hash: nft.transaction_hash,
-                    owner_id: nft.owner_id,
-                    attributes: None,
-                    collection_name: None,
-                    mint_status: Some(MintStatus::Confirmed),
-                    block_number: None,
-                    gas_used: None,
-                    gas_price: None,
-                }),
+                data: Some(NftResponse::from(nft)),
                 message: "NFT retrieved successfully".to_string(),
             };
             (StatusCode::OK, Json(response))
@@ -228,128 +130,761 @@ pub async fn get_nft_by_id_handler(
     }
 }
 
+fn mint_error(status: StatusCode, message: impl Into<String>) -> Response {
+    let response = ApiResponse::<MintResponse> {
+        success: false,
+        data: None,
+        message: message.into(),
+    };
+    (status, Json(response)).into_response()
+}
+
+/// Mint an NFT. With an `Idempotency-Key` header, a repeat of the same request replays the first
+/// response instead of minting again; reusing the key for a different request is rejected.
 pub async fn mint_nft_handler(
     State(pool): State<DbPool>,
+    State(minting_queue): State<MintingQueue>,
+    session: AuthSession,
+    headers: HeaderMap,
     Json(payload): Json<MintNftRequest>,
-) -> impl IntoResponse {
-    // First, find or create the user
-    let user = match find_user_by_public_key(&pool, &payload.owner_wallet).await {
-        Ok(Some(user)) => user,
-        Ok(None) => {
-            // Create new user if doesn't exist
-            match crate::db_operations::create_user(&pool, payload.owner_wallet.clone()).await {
-                Ok(user) => user,
-                Err(e) => {
-                    let response = ApiResponse::<MintResponse> {
-                        success: false,
-                        data: None,
-                        message: format!("Failed to create user: {}", e),
-                    };
-                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+) -> Response {
+    let key = match headers.get(IDEMPOTENCY_KEY_HEADER).map(|value| value.to_str().map(str::trim)) {
+        None => return mint_nft(pool, minting_queue, session, headers, payload).await.into_response(),
+        Some(Ok(key)) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH => key.to_string(),
+        Some(_) => {
+            return mint_error(
+                StatusCode::BAD_REQUEST,
+                format!("Idempotency-Key must be 1 to {} visible characters", MAX_IDEMPOTENCY_KEY_LENGTH),
+            );
+        }
+    };
+
+    let user_id = session.user.id.clone();
+    let fingerprint = mint_request_fingerprint(&payload, &headers);
+    let expires_at = (chrono::Utc::now() + chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS)).naive_utc();
+    let lease = chrono::Duration::minutes(IDEMPOTENCY_CLAIM_LEASE_MINUTES);
+    match claim_idempotency_key(&pool, &user_id, &key, fingerprint.clone(), expires_at, lease).await {
+        Ok(IdempotencyClaim::Claimed) => {}
+        Ok(IdempotencyClaim::Existing(existing)) if existing.request_hash != fingerprint => {
+            return mint_error(StatusCode::UNPROCESSABLE_ENTITY, "Idempotency-Key was already used for a different mint request");
+        }
+        Ok(IdempotencyClaim::Existing(existing)) => {
+            return match (existing.status_code, existing.response) {
+                (Some(status), Some(response)) => {
+                    let status = StatusCode::from_u16(status as u16).unwrap_or(StatusCode::OK);
+                    (status, [(IDEMPOTENT_REPLAYED_HEADER, "true")], Json(response)).into_response()
                 }
+                _ => mint_error(StatusCode::CONFLICT, "A mint request with this Idempotency-Key is still being processed"),
+            };
+        }
+        Err(e) => return mint_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to claim Idempotency-Key: {}", e)),
+    }
+
+    let (status, Json(response)) = mint_nft(pool.clone(), minting_queue, session, headers, payload).await;
+    // A server error released the key so the same request can be retried
+    let recorded = if status.is_server_error() {
+        release_idempotency_key(&pool, &user_id, &key).await
+    } else {
+        match serde_json::to_value(&response) {
+            Ok(body) => complete_idempotency_key(&pool, &user_id, &key, status.as_u16(), body).await,
+            Err(e) => Err(e.into()),
+        }
+    };
+    if let Err(e) = recorded {
+        tracing::error!("Failed to record the response to Idempotency-Key {}: {}", key, e);
+    }
+    (status, Json(response)).into_response()
+}
+
+/// Why fee caps sent by a client are refused: one above [`MAX_FEE_PER_GAS`] would only overflow fees
+fn excessive_fee_cap(max_fee_per_gas: Option<Wei>, max_priority_fee_per_gas: Option<Wei>) -> Option<String> {
+    [max_fee_per_gas, max_priority_fee_per_gas]
+        .into_iter()
+        .flatten()
+        .any(|fee| fee > MAX_FEE_PER_GAS)
+        .then(|| format!("Fees per gas must not exceed {} gwei", MAX_FEE_PER_GAS.to_gwei_string()))
+}
+
+/// Fingerprint of everything that decides what a mint request does, whatever the JSON's key order or spacing
+fn mint_request_fingerprint(payload: &MintNftRequest, headers: &HeaderMap) -> String {
+    let mut request = serde_json::to_vec(payload).unwrap_or_default();
+    if let Some(failure) = headers.get(SIMULATE_FAILURE_HEADER) {
+        request.extend_from_slice(b"\n");
+        request.extend_from_slice(failure.as_bytes());
+    }
+    hex::encode(keccak256(&request))
+}
+
+async fn mint_nft(
+    pool: DbPool,
+    minting_queue: MintingQueue,
+    session: AuthSession,
+    headers: HeaderMap,
+    mut payload: MintNftRequest,
+) -> (StatusCode, Json<ApiResponse<MintResponse>>) {
+    // Mints may only go to the wallet that signed in
+    if !session.owns_wallet(&payload.owner_wallet) {
+        let response = ApiResponse::<MintResponse> {
+            success: false,
+            data: None,
+            message: "Owner wallet does not match the signed-in wallet".to_string(),
+        };
+        return (StatusCode::FORBIDDEN, Json(response));
+    }
+    let user = session.user;
+
+    // A failure can be forced with a header or a tag in the name, e.g. "Broken [revert]"
+    let forced_failure = match headers.get(SIMULATE_FAILURE_HEADER).map(|value| value.to_str().unwrap_or_default().parse()) {
+        Some(Ok(failure)) => Some(failure),
+        Some(Err(e)) => {
+            let response = ApiResponse::<MintResponse> {
+                success: false,
+                data: None,
+                message: format!("Invalid {} header: {}", SIMULATE_FAILURE_HEADER, e),
+            };
+            return (StatusCode::BAD_REQUEST, Json(response));
+        }
+        None => SimulatedFailure::from_name(&payload.name),
+    };
+
+    if let (Some(max_fee), Some(priority_fee)) = (payload.max_fee_per_gas, payload.max_priority_fee_per_gas) {
+        if priority_fee > max_fee {
+            let response = ApiResponse::<MintResponse> {
+                success: false,
+                data: None,
+                message: "Max priority fee per gas must not exceed max fee per gas".to_string(),
+            };
+            return (StatusCode::BAD_REQUEST, Json(response));
+        }
+    }
+    if payload.max_fee_per_gas.is_some_and(|fee| fee.is_zero()) {
+        let response = ApiResponse::<MintResponse> {
+            success: false,
+            data: None,
+            message: "Max fee per gas must be greater than zero".to_string(),
+        };
+        return (StatusCode::BAD_REQUEST, Json(response));
+    }
+    if let Some(message) = excessive_fee_cap(payload.max_fee_per_gas, payload.max_priority_fee_per_gas) {
+        let response = ApiResponse::<MintResponse> {
+            success: false,
+            data: None,
+            message,
+        };
+        return (StatusCode::BAD_REQUEST, Json(response));
+    }
+    if let Some(attributes) = payload.attributes.take() {
+        match normalize_attributes(attributes) {
+            Ok(attributes) => payload.attributes = Some(attributes),
+            Err(message) => {
+                let response = ApiResponse::<MintResponse> {
+                    success: false,
+                    data: None,
+                    message,
+                };
+                return (StatusCode::BAD_REQUEST, Json(response));
             }
         }
-        Err(e) => {
+    }
+
+    let collection = match resolve_collection(&pool, &payload, &user.id).await {
+        Ok(collection) => collection,
+        Err((status, message)) => {
             let response = ApiResponse::<MintResponse> {
                 success: false,
                 data: None,
-                message: format!("Failed to find user: {}", e),
+                message,
             };
-            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+            return (status, Json(response));
         }
     };
 
-    // Generate a unique token ID and mint ID
-    let token_id = format!("NFT-{}", cuid::cuid2());
     let mint_id = cuid::cuid2();
+    let new_nft = NewNft {
+        // Taken from the collection when the mint is stored
+        token_id: String::new(),
+        name: payload.name.clone(),
+        description: payload.description.clone(),
+        image: payload.image_url.clone(),
+        owner_id: user.id.clone(),
+        transaction_hash: None,
+        attributes: payload.attributes.as_ref().map(|attrs| serde_json::to_value(attrs).unwrap_or_default()),
+        collection,
+    };
+    let request = TransactionRequest {
+        max_fee_per_gas: payload.max_fee_per_gas,
+        max_priority_fee_per_gas: payload.max_priority_fee_per_gas,
+        failure: forced_failure,
+        gas_used: None,
+    };
 
-    // Add to minting queue for blockchain simulation
-    let minting_status = MINTING_QUEUE.add_mint(mint_id.clone());
-    
-    // Get transaction details from the minting status
+    // Stored and sent to the simulated chain's mempool, then tracked until it is confirmed
+    let (nft, minting_status) = match minting_queue.add_mint(mint_id.clone(), &user.public_key, new_nft, request).await {
+        Ok(minted) => minted,
+        Err(e) if e.is::<SupplyExhausted>() => {
+            let response = ApiResponse::<MintResponse> {
+                success: false,
+                data: None,
+                message: e.to_string(),
+            };
+            return (StatusCode::CONFLICT, Json(response));
+        }
+        Err(e) => {
+            let response = ApiResponse::<MintResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to mint NFT: {}", e),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+        }
+    };
     let transaction_details = minting_status.transaction_details.as_ref().unwrap();
-    
-    // Create the NFT in database
-    match create_nft(
-        &pool,
-        token_id.clone(),
-        payload.name.clone(),
-        payload.description.clone(),
-        payload.image_url.clone(),
-        user.id.clone(),
-        Some(transaction_details.transaction_hash.clone()),
-        payload.attributes.as_ref().map(|attrs| serde_json::to_value(attrs).unwrap_or_default()),
-        payload.collection_name.clone(),
-    ).await {
-        Ok(nft) => {
-            let response = ApiResponse {
-                success: true,
-                data: Some(MintResponse {
-                    success: true,
-                    nft: Some(NftResponse {
-                        id: nft.id,
-                        token_id: nft.token_id,
-                        name: nft.name,
-                        description: nft.description,
-                        image: nft.image,
-                        minted_at: chrono::DateTime::from_naive_utc_and_offset(nft.minted_at, chrono::Utc),
-                        transaction_hash: nft.transaction_hash,
-                        owner_id: nft.owner_id,
-                        attributes: payload.attributes,
-                        collection_name: payload.collection_name,
-                                            mint_status: Some(minting_status.status),
-                    block_number: Some(transaction_details.block_number),
-                    gas_used: Some(transaction_details.gas_used),
-                    gas_price: Some(transaction_details.gas_price),
-                }),
-                mint_id: Some(mint_id),
-                transaction_hash: Some(transaction_details.transaction_hash.clone()),
-                block_number: Some(transaction_details.block_number),
+
+    let response = ApiResponse {
+        success: true,
+        data: Some(MintResponse {
+            success: true,
+            nft: Some(NftResponse {
+                id: nft.id,
+                token_id: nft.token_id,
+                name: nft.name,
+                description: nft.description,
+                image: nft.image,
+                minted_at: chrono::DateTime::from_naive_utc_and_offset(nft.minted_at, chrono::Utc),
+                transaction_hash: nft.transaction_hash,
+                owner_id: nft.owner_id,
+                attributes: payload.attributes,
+                collection_name: nft.collection_name,
+                collection_id: nft.collection_id,
+                mint_status: Some(minting_status.status),
+                block_number: transaction_details.block_number,
                 gas_used: Some(transaction_details.gas_used),
                 gas_price: Some(transaction_details.gas_price),
-                mint_status: minting_status.status.clone(),
-                    message: "NFT minting initiated successfully".to_string(),
-                }),
-                message: "NFT minting initiated successfully".to_string(),
+                burned_at: None,
+            }),
+            mint_id: Some(mint_id),
+            transaction_hash: Some(transaction_details.transaction_hash.clone()),
+            block_number: transaction_details.block_number,
+            gas_used: Some(transaction_details.gas_used),
+            gas_price: Some(transaction_details.gas_price),
+            max_fee_per_gas: transaction_details.max_fee_per_gas,
+            max_priority_fee_per_gas: transaction_details.max_priority_fee_per_gas,
+            nonce: transaction_details.nonce,
+            mint_status: minting_status.status,
+            message: "NFT minting initiated successfully".to_string(),
+        }),
+        message: "NFT minting initiated successfully".to_string(),
+    };
+    (StatusCode::CREATED, Json(response))
+}
+
+/// Resolve the collection a mint request targets, creating it on first use of a new collection name
+async fn resolve_collection(
+    pool: &DbPool,
+    payload: &MintNftRequest,
+    creator_id: &str,
+) -> Result<Option<CollectionModel>, (StatusCode, String)> {
+    if let Some(collection_id) = payload.collection_id.as_deref() {
+        match find_collection(pool, collection_id).await {
+            Ok(Some(collection)) => Ok(Some(collection)),
+            Ok(None) => Err((StatusCode::NOT_FOUND, "Collection not found".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to find collection: {}", e))),
+        }
+    } else if let Some(collection_name) = payload.collection_name.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+        match find_or_create_collection_by_name(pool, collection_name, creator_id).await {
+            Ok(collection) => Ok(Some(collection)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to resolve collection: {}", e))),
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+fn batch_error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<ApiResponse<BatchMintResponse>>) {
+    let response = ApiResponse::<BatchMintResponse> {
+        success: false,
+        data: None,
+        message: message.into(),
+    };
+    (status, Json(response))
+}
+
+/// Mint many NFTs to the signed-in wallet at once. Items are checked one by one and a rejected
+/// item is reported without holding up the rest; the accepted ones are stored together and sent
+/// in one transaction per `chunk_size` items.
+pub async fn batch_mint_handler(
+    State(pool): State<DbPool>,
+    State(minting_queue): State<MintingQueue>,
+    session: AuthSession,
+    headers: HeaderMap,
+    Json(payload): Json<BatchMintRequest>,
+) -> impl IntoResponse {
+    if payload.items.is_empty() {
+        return batch_error(StatusCode::BAD_REQUEST, "A batch needs at least one item");
+    }
+    if payload.items.len() > MAX_BATCH_SIZE {
+        return batch_error(StatusCode::BAD_REQUEST, format!("A batch can mint at most {} NFTs", MAX_BATCH_SIZE));
+    }
+    let chunk_size = payload.chunk_size.unwrap_or(payload.items.len());
+    if chunk_size == 0 {
+        return batch_error(StatusCode::BAD_REQUEST, "Chunk size must be at least 1");
+    }
+    if let (Some(max_fee), Some(priority_fee)) = (payload.max_fee_per_gas, payload.max_priority_fee_per_gas) {
+        if priority_fee > max_fee {
+            return batch_error(StatusCode::BAD_REQUEST, "Max priority fee per gas must not exceed max fee per gas");
+        }
+    }
+    if payload.max_fee_per_gas.is_some_and(|fee| fee.is_zero()) {
+        return batch_error(StatusCode::BAD_REQUEST, "Max fee per gas must be greater than zero");
+    }
+    if let Some(message) = excessive_fee_cap(payload.max_fee_per_gas, payload.max_priority_fee_per_gas) {
+        return batch_error(StatusCode::BAD_REQUEST, message);
+    }
+    // The header is the only way to force a failure, as it would fail every item sharing the transaction
+    let forced_failure = match headers.get(SIMULATE_FAILURE_HEADER).map(|value| value.to_str().unwrap_or_default().parse()) {
+        Some(Ok(failure)) => Some(failure),
+        Some(Err(e)) => return batch_error(StatusCode::BAD_REQUEST, format!("Invalid {} header: {}", SIMULATE_FAILURE_HEADER, e)),
+        None => None,
+    };
+
+    let mut items: Vec<BatchMintItemResult> = Vec::with_capacity(payload.items.len());
+    let mut accepted = Vec::new();
+    // Items of a drop usually share a collection, so each is looked up once
+    let mut collections: HashMap<(Option<String>, Option<String>), Option<CollectionModel>> = HashMap::new();
+    for (index, mut item) in payload.items.into_iter().enumerate() {
+        let attributes = item.attributes.take().map(normalize_attributes).transpose();
+        let rejection = if !session.owns_wallet(&item.owner_wallet) {
+            Some("Owner wallet does not match the signed-in wallet".to_string())
+        } else if item.max_fee_per_gas.is_some() || item.max_priority_fee_per_gas.is_some() {
+            Some("Fee caps are set for the whole batch, not per item".to_string())
+        } else if let Err(message) = &attributes {
+            Some(message.clone())
+        } else {
+            let key = (item.collection_id.clone(), item.collection_name.as_deref().map(|name| name.trim().to_lowercase()));
+            let collection = match collections.get(&key) {
+                Some(collection) => Ok(collection.clone()),
+                None => resolve_collection(&pool, &item, &session.user.id).await,
             };
-            (StatusCode::CREATED, Json(response))
+            match collection {
+                Ok(collection) => {
+                    collections.insert(key, collection.clone());
+                    accepted.push((index, NewNft {
+                        // Taken from the collection when the batch is stored
+                        token_id: String::new(),
+                        name: item.name,
+                        description: item.description,
+                        image: item.image_url,
+                        owner_id: session.user.id.clone(),
+                        transaction_hash: None,
+                        attributes: attributes.ok().flatten().map(|attrs| serde_json::to_value(attrs).unwrap_or_default()),
+                        collection,
+                    }));
+                    None
+                }
+                Err((status, message)) if status.is_server_error() => return batch_error(status, message),
+                Err((_, message)) => Some(message),
+            }
+        };
+        items.push(BatchMintItemResult {
+            index,
+            success: rejection.is_none(),
+            mint_id: None,
+            nft: None,
+            transaction_hash: None,
+            error: rejection,
+        });
+    }
+
+    let rejected = items.len() - accepted.len();
+    let batch_id = cuid::cuid2();
+    if accepted.is_empty() {
+        let response = ApiResponse {
+            success: false,
+            data: Some(BatchMintResponse { batch_id, transaction_hashes: Vec::new(), queued: 0, rejected, items }),
+            message: "No item of the batch could be minted".to_string(),
+        };
+        return (StatusCode::BAD_REQUEST, Json(response));
+    }
+
+    let (indexes, nfts): (Vec<usize>, Vec<NewNft>) = accepted.into_iter().unzip();
+    let request = TransactionRequest {
+        max_fee_per_gas: payload.max_fee_per_gas,
+        max_priority_fee_per_gas: payload.max_priority_fee_per_gas,
+        failure: forced_failure,
+        gas_used: None,
+    };
+    let minted = match minting_queue.add_batch_mint(&batch_id, &session.user.public_key, nfts, chunk_size, request).await {
+        Ok(minted) => minted,
+        Err(e) if e.is::<SupplyExhausted>() => return batch_error(StatusCode::CONFLICT, e.to_string()),
+        Err(e) => return batch_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to queue batch mint: {}", e)),
+    };
+
+    let mut transaction_hashes: Vec<String> = Vec::new();
+    for (index, (nft, minting_status)) in indexes.into_iter().zip(minted) {
+        let transaction_hash = minting_status.transaction_details.map(|tx| tx.transaction_hash);
+        if let Some(hash) = transaction_hash.as_ref().filter(|hash| !transaction_hashes.contains(hash)) {
+            transaction_hashes.push(hash.clone());
+        }
+        let item = &mut items[index];
+        item.mint_id = Some(minting_status.mint_id);
+        item.nft = Some(NftResponse::from(nft));
+        item.transaction_hash = transaction_hash;
+    }
+
+    let queued = items.len() - rejected;
+    let response = ApiResponse {
+        success: true,
+        data: Some(BatchMintResponse { batch_id, transaction_hashes, queued, rejected, items }),
+        message: format!("Batch minting of {} NFTs initiated successfully", queued),
+    };
+    (StatusCode::CREATED, Json(response))
+}
+
+/// Progress of every mint in a batch
+pub async fn get_batch_status_handler(
+    State(minting_queue): State<MintingQueue>,
+    Path(batch_id): Path<String>,
+) -> impl IntoResponse {
+    match minting_queue.get_batch_mints(&batch_id).await {
+        Ok(mints) if mints.is_empty() => {
+            let response = ApiResponse::<BatchStatusResponse> {
+                success: false,
+                data: None,
+                message: "Batch not found".to_string(),
+            };
+            (StatusCode::NOT_FOUND, Json(response))
+        }
+        Ok(mints) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(BatchStatusResponse::new(batch_id, mints)),
+                message: "Batch status retrieved successfully".to_string(),
+            };
+            (StatusCode::OK, Json(response))
         }
         Err(e) => {
-            let response = ApiResponse::<MintResponse> {
+            let response = ApiResponse::<BatchStatusResponse> {
                 success: false,
                 data: None,
-                message: format!("Failed to mint NFT: {}", e),
+                message: format!("Failed to retrieve batch status: {}", e),
             };
             (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
         }
     }
 }
 
+/// Resend a pending mint with higher fees so it is mined sooner
+pub async fn speed_up_mint_handler(
+    State(minting_queue): State<MintingQueue>,
+    session: AuthSession,
+    Path(mint_id): Path<String>,
+    payload: Option<Json<ReplaceMintRequest>>,
+) -> impl IntoResponse {
+    replace_mint(minting_queue, session, mint_id, payload, false).await
+}
+
+/// Replace a pending mint with an empty self-transfer, so the mint is never mined
+pub async fn cancel_mint_handler(
+    State(minting_queue): State<MintingQueue>,
+    session: AuthSession,
+    Path(mint_id): Path<String>,
+    payload: Option<Json<ReplaceMintRequest>>,
+) -> impl IntoResponse {
+    replace_mint(minting_queue, session, mint_id, payload, true).await
+}
+
+async fn replace_mint(
+    minting_queue: MintingQueue,
+    session: AuthSession,
+    mint_id: String,
+    payload: Option<Json<ReplaceMintRequest>>,
+    cancel: bool,
+) -> (StatusCode, Json<ApiResponse<MintStatusResponse>>) {
+    let payload = payload.map(|Json(payload)| payload).unwrap_or_default();
+    if let (Some(max_fee), Some(priority_fee)) = (payload.max_fee_per_gas, payload.max_priority_fee_per_gas) {
+        if priority_fee > max_fee {
+            let response = ApiResponse::<MintStatusResponse> {
+                success: false,
+                data: None,
+                message: "Max priority fee per gas must not exceed max fee per gas".to_string(),
+            };
+            return (StatusCode::BAD_REQUEST, Json(response));
+        }
+    }
+    if let Some(message) = excessive_fee_cap(payload.max_fee_per_gas, payload.max_priority_fee_per_gas) {
+        let response = ApiResponse::<MintStatusResponse> {
+            success: false,
+            data: None,
+            message,
+        };
+        return (StatusCode::BAD_REQUEST, Json(response));
+    }
+
+    // Only the wallet that sent the mint can replace its transaction
+    let sender = match minting_queue.get_mint_status(&mint_id).await {
+        Ok(Some(minting_status)) => minting_status.transaction_details.and_then(|tx| tx.from),
+        Ok(None) => {
+            let response = ApiResponse::<MintStatusResponse> {
+                success: false,
+                data: None,
+                message: "Mint not found".to_string(),
+            };
+            return (StatusCode::NOT_FOUND, Json(response));
+        }
+        Err(e) => {
+            let response = ApiResponse::<MintStatusResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve mint status: {}", e),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+        }
+    };
+    if !sender.is_some_and(|sender| session.owns_wallet(&sender)) {
+        let response = ApiResponse::<MintStatusResponse> {
+            success: false,
+            data: None,
+            message: "Only the wallet that sent the mint can replace it".to_string(),
+        };
+        return (StatusCode::FORBIDDEN, Json(response));
+    }
+
+    let request = ReplacementRequest {
+        max_fee_per_gas: payload.max_fee_per_gas,
+        max_priority_fee_per_gas: payload.max_priority_fee_per_gas,
+        cancel,
+    };
+    match minting_queue.replace_mint(&mint_id, request).await {
+        Ok(minting_status) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(MintStatusResponse::from(minting_status)),
+                message: if cancel { "Mint cancellation sent" } else { "Mint sped up" }.to_string(),
+            };
+            (StatusCode::OK, Json(response))
+        }
+        Err(e) => {
+            let status = match e.downcast_ref::<ReplacementError>() {
+                Some(ReplacementError::NotPending) => StatusCode::CONFLICT,
+                Some(ReplacementError::Underpriced { .. }) => StatusCode::BAD_REQUEST,
+                None => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            let response = ApiResponse::<MintStatusResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to replace mint transaction: {}", e),
+            };
+            (status, Json(response))
+        }
+    }
+}
+
+fn transfer_error(status: StatusCode, message: impl Into<String>) -> (StatusCode, Json<ApiResponse<TransferResponse>>) {
+    let response = ApiResponse::<TransferResponse> {
+        success: false,
+        data: None,
+        message: message.into(),
+    };
+    (status, Json(response))
+}
+
+/// Send a minted NFT to another wallet. It changes hands once the transfer transaction is confirmed.
+pub async fn transfer_nft_handler(
+    State(pool): State<DbPool>,
+    State(minting_queue): State<MintingQueue>,
+    session: AuthSession,
+    Path(id): Path<String>,
+    Json(payload): Json<TransferNftRequest>,
+) -> impl IntoResponse {
+    let to_wallet = match parse_address(&payload.to_wallet) {
+        Ok(address) => to_checksum_address(&address),
+        Err(e) => return transfer_error(StatusCode::BAD_REQUEST, format!("Invalid recipient wallet: {}", e)),
+    };
+    if to_wallet == ZERO_ADDRESS {
+        return transfer_error(StatusCode::BAD_REQUEST, "Use the burn endpoint to send an NFT to the zero address");
+    }
+    if session.owns_wallet(&to_wallet) {
+        return transfer_error(StatusCode::BAD_REQUEST, "NFT already belongs to the recipient wallet");
+    }
+
+    let fees = TransferFees {
+        max_fee_per_gas: payload.max_fee_per_gas,
+        max_priority_fee_per_gas: payload.max_priority_fee_per_gas,
+    };
+    send_nft(pool, minting_queue, session, id, to_wallet, fees, "NFT transfer initiated successfully").await
+}
+
+/// Burn a minted NFT by sending it to the zero address. It is marked burned once the transaction is confirmed.
+pub async fn burn_nft_handler(
+    State(pool): State<DbPool>,
+    State(minting_queue): State<MintingQueue>,
+    session: AuthSession,
+    Path(id): Path<String>,
+    payload: Option<Json<TransferFees>>,
+) -> impl IntoResponse {
+    let fees = payload.map(|Json(fees)| fees).unwrap_or_default();
+    send_nft(pool, minting_queue, session, id, ZERO_ADDRESS.to_string(), fees, "NFT burn initiated successfully").await
+}
+
+/// Check that the signed-in wallet may move the NFT, then send it to `to_wallet`
+async fn send_nft(
+    pool: DbPool,
+    minting_queue: MintingQueue,
+    session: AuthSession,
+    id: String,
+    to_wallet: String,
+    fees: TransferFees,
+    message: &str,
+) -> (StatusCode, Json<ApiResponse<TransferResponse>>) {
+    let nft = match find_nft_by_id(&pool, &id).await {
+        Ok(Some(nft)) => nft,
+        Ok(None) => return transfer_error(StatusCode::NOT_FOUND, "NFT not found"),
+        Err(e) => return transfer_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to retrieve NFT: {}", e)),
+    };
+    if nft.owner_id != session.user.id {
+        return transfer_error(StatusCode::FORBIDDEN, "Only the NFT's owner can transfer it");
+    }
+    if nft.burned_at.is_some() {
+        return transfer_error(StatusCode::CONFLICT, "NFT has been burned");
+    }
+    if nft.mint_status != MintStatus::Confirmed.as_str() {
+        return transfer_error(StatusCode::CONFLICT, "NFT can't be transferred until its mint is confirmed");
+    }
+
+    if let (Some(max_fee), Some(priority_fee)) = (fees.max_fee_per_gas, fees.max_priority_fee_per_gas) {
+        if priority_fee > max_fee {
+            return transfer_error(StatusCode::BAD_REQUEST, "Max priority fee per gas must not exceed max fee per gas");
+        }
+    }
+    if fees.max_fee_per_gas.is_some_and(|fee| fee.is_zero()) {
+        return transfer_error(StatusCode::BAD_REQUEST, "Max fee per gas must be greater than zero");
+    }
+    if let Some(message) = excessive_fee_cap(fees.max_fee_per_gas, fees.max_priority_fee_per_gas) {
+        return transfer_error(StatusCode::BAD_REQUEST, message);
+    }
+
+    // One transfer at a time, so each is sent by the wallet that owns the NFT when it is mined.
+    // The queue checks again before sending.
+    if let Some(pending) = minting_queue.pending_transfer(&nft.id) {
+        return transfer_error(StatusCode::CONFLICT, TransferConflict::Pending(Some(pending.transaction_hash)).to_string());
+    }
+
+    let request = TransactionRequest {
+        max_fee_per_gas: fees.max_fee_per_gas,
+        max_priority_fee_per_gas: fees.max_priority_fee_per_gas,
+        ..TransactionRequest::default()
+    };
+    match minting_queue.transfer_nft(&nft, &session.user.public_key, &to_wallet, request).await {
+        Ok(transfer) => {
+            let response = ApiResponse {
+                success: true,
+                data: Some(TransferResponse::from(transfer)),
+                message: message.to_string(),
+            };
+            (StatusCode::CREATED, Json(response))
+        }
+        Err(e) if e.is::<TransferConflict>() => transfer_error(StatusCode::CONFLICT, e.to_string()),
+        Err(e) => transfer_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to transfer NFT: {}", e)),
+    }
+}
+
+/// Every owner an NFT has had: its mint, then each transfer that hasn't failed
+pub async fn get_nft_provenance_handler(
+    State(pool): State<DbPool>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let nft = match find_nft_by_id(&pool, &id).await {
+        Ok(Some(nft)) => nft,
+        Ok(None) => {
+            let response = ApiResponse::<ProvenanceResponse> {
+                success: false,
+                data: None,
+                message: "NFT not found".to_string(),
+            };
+            return (StatusCode::NOT_FOUND, Json(response));
+        }
+        Err(e) => {
+            let response = ApiResponse::<ProvenanceResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve NFT: {}", e),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+        }
+    };
+
+    let mint_transaction = match nft.transaction_hash.as_deref() {
+        Some(hash) => find_transaction_with_nft(&pool, hash).await.map(|found| found.map(|(tx, _)| tx)),
+        None => Ok(None),
+    };
+    let (mint_transaction, transfers) = match (mint_transaction, find_nft_transfers(&pool, &nft.id).await) {
+        (Ok(mint_transaction), Ok(transfers)) => (mint_transaction, transfers),
+        (Err(e), _) | (_, Err(e)) => {
+            let response = ApiResponse::<ProvenanceResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve provenance: {}", e),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+        }
+    };
+
+    let to_timestamp = |at: chrono::NaiveDateTime| chrono::DateTime::from_naive_utc_and_offset(at, chrono::Utc);
+    // A failed mint never put the NFT on chain
+    let mint = mint_transaction.filter(|_| nft.mint_status != MintStatus::Failed.as_str()).map(|tx| OwnershipChange {
+        transfer_id: None,
+        from_address: ZERO_ADDRESS.to_string(),
+        to_address: tx.from_address,
+        transaction_hash: tx.hash,
+        status: nft.mint_status.parse().ok(),
+        block_number: tx.block_number.map(|block| block as u64),
+        block_timestamp: tx.block_timestamp.map(to_timestamp),
+    });
+    let history: Vec<OwnershipChange> = mint
+        .into_iter()
+        .chain(transfers.into_iter().map(|(transfer, tx)| OwnershipChange {
+            transfer_id: Some(transfer.id),
+            from_address: transfer.from_address,
+            to_address: transfer.to_address,
+            transaction_hash: transfer.transaction_hash,
+            status: transfer.status.parse().ok(),
+            block_number: transfer.block_number.map(|block| block as u64),
+            block_timestamp: tx.and_then(|tx| tx.block_timestamp).map(to_timestamp),
+        }))
+        .collect();
+    let owner = history
+        .iter()
+        .rev()
+        .find(|change| change.status == Some(MintStatus::Confirmed))
+        .map(|change| change.to_address.clone());
+
+    let response = ApiResponse {
+        success: true,
+        data: Some(ProvenanceResponse {
+            nft_id: nft.id,
+            token_id: nft.token_id,
+            owner,
+            history,
+        }),
+        message: "Provenance retrieved successfully".to_string(),
+    };
+    (StatusCode::OK, Json(response))
+}
+
 pub async fn get_mint_status_handler(
+    State(minting_queue): State<MintingQueue>,
     Path(mint_id): Path<String>,
 ) -> impl IntoResponse {
-    match MINTING_QUEUE.get_mint_status(&mint_id) {
-        Some(minting_status) => {
-            let transaction_details = minting_status.transaction_details.as_ref();
-            
+    match minting_queue.get_mint_status(&mint_id).await {
+        Ok(Some(minting_status)) => {
             let response = ApiResponse {
                 success: true,
-                data: Some(MintStatusResponse {
-                    mint_id: minting_status.mint_id,
-                    status: minting_status.status,
-                    transaction_hash: transaction_details.map(|tx| tx.transaction_hash.clone()),
-                    block_number: transaction_details.map(|tx| tx.block_number),
-                    gas_used: transaction_details.map(|tx| tx.gas_used),
-                    gas_price: transaction_details.map(|tx| tx.gas_price),
-                    confirmations: transaction_details.map(|tx| tx.confirmations),
-                    created_at: minting_status.created_at,
-                    confirmed_at: minting_status.confirmed_at,
-                }),
+                data: Some(MintStatusResponse::from(minting_status)),
                 message: "Mint status retrieved successfully".to_string(),
             };
             (StatusCode::OK, Json(response))
         }
-        None => {
+        Ok(None) => {
             let response = ApiResponse::<MintStatusResponse> {
                 success: false,
                 data: None,
@@ -357,6 +892,14 @@ pub async fn get_mint_status_handler(
             };
             (StatusCode::NOT_FOUND, Json(response))
         }
+        Err(e) => {
+            let response = ApiResponse::<MintStatusResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve mint status: {}", e),
+            };
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+        }
     }
 }
 
@@ -366,7 +909,7 @@ pub async fn get_user_nfts_handler(
     Query(query): Query<PaginationQuery>,
 ) -> impl IntoResponse {
     // First find the user
-    let user = match find_user_by_public_key(&pool, &wallet_address).await {
+    let user = match find_user_by_public_key(pool.as_ref(), &wallet_address).await {
         Ok(Some(user)) => user,
         Ok(None) => {
             let response = ApiResponse::<PaginatedResponse<NftResponse>> {
@@ -386,59 +929,211 @@ pub async fn get_user_nfts_handler(
         }
     };
 
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.page.unwrap_or(0) * limit;
-
-    match get_nfts_by_owner(&pool, &user.id, Some(limit), Some(offset)).await {
-        Ok(nfts) => {
-            let nft_responses: Vec<NftResponse> = nfts
-                .into_iter()
-                .map(|nft| {
-                    // Convert stored JSON attributes back to NftAttribute structs
-                    let attributes = nft.attributes.as_ref().and_then(|attrs| {
-                        serde_json::from_value::<Vec<crate::nft::types::NftAttribute>>(attrs.clone()).ok()
-                    });
-                    
-                    NftResponse {
-                        id: nft.id,
-                        token_id: nft.token_id,
-                        name: nft.name,
-                        description: nft.description,
-                        image: nft.image,
-                        minted_at: chrono::DateTime::from_naive_utc_and_offset(nft.minted_at, chrono::Utc),
-                        transaction_hash: nft.transaction_hash,
-                        owner_id: nft.owner_id,
-                        attributes,
-                        collection_name: nft.collection_name,
-                        mint_status: Some(MintStatus::Confirmed),
-                        block_number: None,
-                        gas_used: None,
-                        gas_price: None,
-                    }
-                })
-                .collect();
-
-            let total = nft_responses.len() as u64;
+    let search = NftSearch {
+        owner: Some(user.public_key),
+        ..NftSearch::default()
+    };
+    match load_nft_page(&pool, &search, &query).await {
+        Ok(page) => {
             let response = ApiResponse {
                 success: true,
-                data: Some(PaginatedResponse {
-                    data: nft_responses,
-                    total,
-                    page: query.page.unwrap_or(0),
-                    limit,
-                }),
+                data: Some(page.map(|(nft, _)| NftResponse::from(nft))),
                 message: "User NFTs retrieved successfully".to_string(),
             };
-
             (StatusCode::OK, Json(response))
         }
-        Err(e) => {
+        Err((status, message)) => {
             let response = ApiResponse::<PaginatedResponse<NftResponse>> {
                 success: false,
                 data: None,
-                message: format!("Failed to retrieve user NFTs: {}", e),
+                message,
             };
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(response))
+            (status, Json(response))
         }
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use sea_orm::{DatabaseBackend, DbErr, MockDatabase, MockExecResult};
+    use crate::blockchain_sim::{BlockchainSimulator, Clock, SimulatorConfig};
+    use crate::entities::{IdempotencyKeyModel, NftModel, UserModel};
+
+    const MINT_REQUEST: &str = r#"{"name":"Dream","image_url":"https://example.com/1.png","owner_wallet":"0xabc"}"#;
+
+    fn mint_request(json: &str) -> MintNftRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn rows_affected(rows_affected: u64) -> MockExecResult {
+        MockExecResult { last_insert_id: 0, rows_affected }
+    }
+
+    /// The key as an earlier request left it, holding `response` once that request was handled
+    fn claimed_key(request_hash: String, response: Option<(i16, serde_json::Value)>) -> IdempotencyKeyModel {
+        let now = chrono::Utc::now().naive_utc();
+        IdempotencyKeyModel {
+            user_id: "user_1".to_string(),
+            key: "key_1".to_string(),
+            request_hash,
+            status_code: response.as_ref().map(|(status_code, _)| *status_code),
+            response: response.map(|(_, response)| response),
+            created_at: now,
+            expires_at: now + chrono::Duration::hours(IDEMPOTENCY_KEY_TTL_HOURS),
+        }
+    }
+
+    fn minting_queue(pool: &DbPool) -> MintingQueue {
+        let simulator = Arc::new(BlockchainSimulator::new(SimulatorConfig { seed: Some(7), ..SimulatorConfig::default() }, Clock::manual(1_700_000_000)));
+        MintingQueue::new(pool.clone(), simulator)
+    }
+
+    /// Signed in as `user_1`, the owner of `0xabc`
+    fn session() -> AuthSession {
+        AuthSession::for_user(UserModel {
+            id: "user_1".to_string(),
+            public_key: "0xabc".to_string(),
+            created_at: chrono::Utc::now().naive_utc(),
+            role: "user".to_string(),
+        })
+    }
+
+    /// Send `json` to the mint handler with the idempotency key, signed in as the owner of `0xabc`
+    async fn mint_with_key(pool: DbPool, json: &str) -> Response {
+        let minting_queue = minting_queue(&pool);
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "key_1".parse().unwrap());
+        mint_nft_handler(State(pool), State(minting_queue), session(), headers, Json(mint_request(json))).await
+    }
+
+    async fn body(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn test_mint_request_fingerprint() {
+        let headers = HeaderMap::new();
+        let original = mint_request(r#"{"name":"Dream","image_url":"https://example.com/1.png","owner_wallet":"0xabc"}"#);
+        let reordered = mint_request(r#"{ "owner_wallet": "0xabc", "image_url": "https://example.com/1.png", "name": "Dream" }"#);
+        let changed = mint_request(r#"{"name":"Dream 2","image_url":"https://example.com/1.png","owner_wallet":"0xabc"}"#);
+
+        // Retries match however the client serialized them; any change to the mint does not
+        let fingerprint = mint_request_fingerprint(&original, &headers);
+        assert_eq!(fingerprint, mint_request_fingerprint(&reordered, &headers));
+        assert_ne!(fingerprint, mint_request_fingerprint(&changed, &headers));
+
+        let mut failing = HeaderMap::new();
+        failing.insert(SIMULATE_FAILURE_HEADER, "revert".parse().unwrap());
+        assert_ne!(fingerprint, mint_request_fingerprint(&original, &failing));
+    }
+
+    #[test]
+    fn test_excessive_fee_caps_are_refused() {
+        assert_eq!(excessive_fee_cap(None, None), None);
+        assert_eq!(excessive_fee_cap(Some(MAX_FEE_PER_GAS), Some(MAX_FEE_PER_GAS)), None);
+        assert!(excessive_fee_cap(Some(Wei::MAX), None).is_some());
+        assert!(excessive_fee_cap(None, Some(MAX_FEE_PER_GAS.saturating_add(Wei::new(1)))).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_mint_replays_response() {
+        let fingerprint = mint_request_fingerprint(&mint_request(MINT_REQUEST), &HeaderMap::new());
+        let recorded = serde_json::json!({ "success": true, "data": { "mint_id": "mint_1" }, "message": "NFT minting initiated successfully" });
+        // No expired keys are deleted and the claim loses to the first request's row
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([rows_affected(0), rows_affected(0)])
+            .append_query_results([vec![claimed_key(fingerprint, Some((201, recorded.clone())))]]);
+
+        let response = mint_with_key(Arc::new(db.into_connection()), MINT_REQUEST).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers().get(IDEMPOTENT_REPLAYED_HEADER).unwrap(), "true");
+        assert_eq!(body(response).await, recorded);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_reused_for_another_mint() {
+        let other = mint_request_fingerprint(&mint_request(r#"{"name":"Other","image_url":"https://example.com/2.png","owner_wallet":"0xabc"}"#), &HeaderMap::new());
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([rows_affected(0), rows_affected(0)])
+            .append_query_results([vec![claimed_key(other, Some((201, serde_json::json!({}))))]]);
+
+        let response = mint_with_key(Arc::new(db.into_connection()), MINT_REQUEST).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(response.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_in_flight() {
+        // The first request has claimed the key but not recorded its response yet
+        let fingerprint = mint_request_fingerprint(&mint_request(MINT_REQUEST), &HeaderMap::new());
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([rows_affected(0), rows_affected(0)])
+            .append_query_results([vec![claimed_key(fingerprint, None)]]);
+
+        let response = mint_with_key(Arc::new(db.into_connection()), MINT_REQUEST).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert!(response.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_released_after_server_error() {
+        // The key is claimed, then the mint fails to take a token ID
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_exec_results([rows_affected(0), rows_affected(1), rows_affected(1), rows_affected(1)])
+            .append_query_errors([DbErr::Custom("connection reset".to_string())]);
+        let pool = Arc::new(db.into_connection());
+
+        let response = mint_with_key(pool.clone(), MINT_REQUEST).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(body(response).await["message"].as_str().unwrap().starts_with("Failed to mint NFT"));
+
+        // The last statement frees the key, so a retry mints instead of replaying the error
+        let log = Arc::try_unwrap(pool).unwrap().into_transaction_log();
+        let released = log.last().unwrap().statements()[0].to_string();
+        assert!(released.starts_with(r#"DELETE FROM "idempotency_keys""#), "{}", released);
+        assert!(released.contains("'key_1'"), "{}", released);
+    }
+
+    #[tokio::test]
+    async fn test_burned_nft_cannot_be_transferred_or_burned_again() {
+        let burned = NftModel {
+            id: "nft_1".to_string(),
+            token_id: "1".to_string(),
+            name: "Dream".to_string(),
+            description: None,
+            image: "https://example.com/1.png".to_string(),
+            minted_at: chrono::Utc::now().naive_utc(),
+            transaction_hash: None,
+            owner_id: "user_1".to_string(),
+            attributes: None,
+            collection_name: None,
+            collection_id: None,
+            mint_status: MintStatus::Confirmed.as_str().to_string(),
+            burned_at: Some(chrono::Utc::now().naive_utc()),
+        };
+        let db = MockDatabase::new(DatabaseBackend::Postgres).append_query_results([vec![burned.clone()], vec![burned]]);
+        let pool: DbPool = Arc::new(db.into_connection());
+        let minting_queue = minting_queue(&pool);
+
+        let request = TransferNftRequest {
+            to_wallet: "0x0CB030d11A8Be48b60418857874deEe61D1071e0".to_string(),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+        };
+        let transferred = transfer_nft_handler(State(pool.clone()), State(minting_queue.clone()), session(), Path("nft_1".to_string()), Json(request))
+            .await
+            .into_response();
+        assert_eq!(transferred.status(), StatusCode::CONFLICT);
+        assert_eq!(body(transferred).await["message"], "NFT has been burned");
+
+        let burned_again = burn_nft_handler(State(pool), State(minting_queue.clone()), session(), Path("nft_1".to_string()), None)
+            .await
+            .into_response();
+        assert_eq!(burned_again.status(), StatusCode::CONFLICT);
+        assert_eq!(body(burned_again).await["message"], "NFT has been burned");
+        assert!(minting_queue.pending_transfer("nft_1").is_none());
+    }
+}
@@ -1,2 +1,4 @@
+pub mod attributes;
 pub mod handlers;
-pub mod types; 
\ No newline at end of file
+pub mod search;
+pub mod types;
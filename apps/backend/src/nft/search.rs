@@ -0,0 +1,158 @@
+use axum::http::StatusCode;
+use crate::database::DbPool;
+use crate::db_operations::{search_nfts, NftSearch};
+use crate::nft::attributes::parse_date;
+use crate::entities::{NftModel, UserModel};
+use crate::pagination::{Cursor, PaginatedResponse, PaginationQuery};
+
+/// Search parameters for `GET /api/nfts/search`.
+///
+/// Besides the fixed keys (`query`, `collection`, `owner`, `sort`, `include_burned`, `page`, `limit`, `cursor`), any number
+/// of `trait[<trait_type>]=<value>` pairs may be given. Values for the same trait are ORed,
+/// different traits are ANDed. `rarity=<value>` is shorthand for `trait[Rarity]=<value>`.
+/// Numeric traits can be bounded with `trait_min[<trait_type>]` and `trait_max[<trait_type>]`,
+/// given as numbers or, for date traits, as `YYYY-MM-DD` or RFC 3339 dates.
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    pub search: NftSearch,
+    pub pagination: PaginationQuery,
+}
+
+impl SearchQuery {
+    pub fn from_pairs(pairs: Vec<(String, String)>) -> Result<Self, String> {
+        let mut query = SearchQuery::default();
+
+        for (key, value) in pairs {
+            if let Some(trait_type) = key.strip_prefix("trait[").and_then(|rest| rest.strip_suffix(']')) {
+                if trait_type.is_empty() {
+                    return Err("Trait filters need a trait type, e.g. trait[Element]=Fire".to_string());
+                }
+                query.search.add_trait(trait_type, value);
+                continue;
+            }
+
+            let bound = key
+                .strip_prefix("trait_min[")
+                .map(|rest| (rest, true))
+                .or_else(|| key.strip_prefix("trait_max[").map(|rest| (rest, false)));
+            if let Some((trait_type, is_min)) = bound.and_then(|(rest, is_min)| Some((rest.strip_suffix(']')?, is_min))) {
+                if trait_type.is_empty() {
+                    return Err("Trait ranges need a trait type, e.g. trait_min[Power]=10".to_string());
+                }
+                let bound = parse_bound(&value).ok_or_else(|| format!("Invalid {}: {}", key, value))?;
+                if is_min {
+                    query.search.add_trait_range(trait_type, Some(bound), None);
+                } else {
+                    query.search.add_trait_range(trait_type, None, Some(bound));
+                }
+                continue;
+            }
+
+            match key.as_str() {
+                "query" | "q" => query.search.text = Some(value).filter(|text| !text.trim().is_empty()),
+                "rarity" => query.search.add_trait("Rarity", value),
+                "collection" => query.search.collection = Some(value),
+                "owner" => query.search.owner = Some(value),
+                "sort" => query.search.sort = value.parse()?,
+                "include_burned" => {
+                    query.search.include_burned = value.parse().map_err(|_| format!("Invalid include_burned: {}", value))?
+                }
+                "page" => query.pagination.page = Some(value.parse().map_err(|_| format!("Invalid page: {}", value))?),
+                "limit" => query.pagination.limit = Some(value.parse().map_err(|_| format!("Invalid limit: {}", value))?),
+                "cursor" => query.pagination.cursor = Some(value),
+                other => return Err(format!("Unknown search parameter: {}", other)),
+            }
+        }
+
+        Ok(query)
+    }
+}
+
+/// A range bound: a finite number, or a date as unix seconds
+fn parse_bound(value: &str) -> Option<f64> {
+    match value.trim().parse::<f64>() {
+        Ok(number) => number.is_finite().then_some(number),
+        Err(_) => parse_date(value).map(|timestamp| timestamp as f64),
+    }
+}
+
+/// Load one page of NFTs matching `search`, by page number or by keyset cursor.
+///
+/// One row beyond the page is fetched so `has_more` is exact even without counting.
+pub async fn load_nft_page(
+    pool: &DbPool,
+    search: &NftSearch,
+    pagination: &PaginationQuery,
+) -> Result<PaginatedResponse<(NftModel, UserModel)>, (StatusCode, String)> {
+    let cursor = pagination.cursor().map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    if cursor.is_some() && !search.sort.supports_cursor() {
+        return Err((StatusCode::BAD_REQUEST, "Cursors can only be used when sorting by newest or oldest".to_string()));
+    }
+
+    let (rows, total) = search_nfts(pool, search, pagination.limit() + 1, pagination.offset(), cursor.as_ref())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to retrieve NFTs: {}", e)))?;
+
+    let resumable = search.sort.supports_cursor();
+    Ok(PaginatedResponse::from_rows(rows, total, pagination, |(nft, _)| {
+        resumable.then(|| Cursor::new(nft.minted_at, nft.id.clone()))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_operations::{NftSort, TraitRange};
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_trait_filters() {
+        let query = SearchQuery::from_pairs(pairs(&[
+            ("query", "dream"),
+            ("trait[Element]", "Fire"),
+            ("trait[Element]", "Water"),
+            ("rarity", "Epic"),
+            ("trait_min[Power]", "10"),
+            ("trait_max[Power]", "90.5"),
+            ("trait_min[Birthday]", "2024-05-01"),
+            ("sort", "name"),
+            ("include_burned", "true"),
+            ("page", "2"),
+        ]))
+        .unwrap();
+
+        assert_eq!(query.search.text.as_deref(), Some("dream"));
+        assert_eq!(
+            query.search.traits,
+            vec![
+                ("Element".to_string(), vec!["Fire".to_string(), "Water".to_string()]),
+                ("Rarity".to_string(), vec!["Epic".to_string()]),
+            ]
+        );
+        assert_eq!(
+            query.search.trait_ranges,
+            vec![
+                TraitRange { trait_type: "Power".to_string(), min: Some(10.0), max: Some(90.5) },
+                TraitRange { trait_type: "Birthday".to_string(), min: Some(1714521600.0), max: None },
+            ]
+        );
+        assert_eq!(query.search.sort, NftSort::Name);
+        assert!(query.search.include_burned);
+        assert_eq!(query.pagination.page, Some(2));
+    }
+
+    #[test]
+    fn test_reject_invalid_parameters() {
+        assert!(SearchQuery::from_pairs(pairs(&[("trait[]", "Fire")])).is_err());
+        assert!(SearchQuery::from_pairs(pairs(&[("trait_min[]", "10")])).is_err());
+        assert!(SearchQuery::from_pairs(pairs(&[("trait_max[Power]", "high")])).is_err());
+        assert!(SearchQuery::from_pairs(pairs(&[("trait_max[Power]", "inf")])).is_err());
+        assert!(SearchQuery::from_pairs(pairs(&[("sort", "price")])).is_err());
+        assert!(SearchQuery::from_pairs(pairs(&[("colour", "red")])).is_err());
+        assert!(SearchQuery::from_pairs(pairs(&[("limit", "ten")])).is_err());
+        assert!(SearchQuery::from_pairs(pairs(&[("include_burned", "yes")])).is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
-use crate::blockchain_sim::MintStatus;
+use crate::blockchain_sim::{MintStatus, MintingStatus, TransactionStatus};
+use crate::entities::{NftModel, TransferModel, UserModel};
+use crate::nft::attributes::{AttributeValue, DisplayType};
+use crate::wei::Wei;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct MintNftRequest {
     pub name: String,
     pub description: Option<String>,
@@ -9,12 +12,55 @@ pub struct MintNftRequest {
     pub owner_wallet: String,
     pub attributes: Option<Vec<NftAttribute>>,
     pub collection_name: Option<String>,
+    /// Id or slug of an existing collection; takes precedence over `collection_name`
+    pub collection_id: Option<String>,
+    /// EIP-1559 fee caps in wei; unset fees follow the standard gas estimate
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Many mints to the signed-in wallet sent together, e.g. for a drop. The fee caps in wei apply
+/// to every transaction of the batch, so items can't set their own.
+#[derive(Debug, Deserialize)]
+pub struct BatchMintRequest {
+    pub items: Vec<MintNftRequest>,
+    /// Most NFTs one transaction mints; the whole batch goes in a single transaction when unset
+    pub chunk_size: Option<usize>,
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+}
+
+/// Fees for a speed-up or cancellation, in wei; unset fees are bumped just enough to replace the pending transaction
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplaceMintRequest {
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+}
+
+/// Wallet to send an NFT to; fee caps in wei follow the standard gas estimate when unset
+#[derive(Debug, Deserialize)]
+pub struct TransferNftRequest {
+    pub to_wallet: String,
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+}
+
+/// Fee caps in wei for a transfer or burn; unset fees follow the standard gas estimate
+#[derive(Debug, Default, Deserialize)]
+pub struct TransferFees {
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NftAttribute {
     pub trait_type: String,
-    pub value: String,
+    pub value: AttributeValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_type: Option<DisplayType>,
+    /// Upper bound marketplaces show next to a numeric value, e.g. "87 of 100"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<serde_json::Number>,
 }
 
 #[derive(Debug, Serialize)]
@@ -29,10 +75,38 @@ pub struct NftResponse {
     pub owner_id: String,
     pub attributes: Option<Vec<NftAttribute>>,
     pub collection_name: Option<String>,
+    pub collection_id: Option<String>,
     pub mint_status: Option<MintStatus>,
     pub block_number: Option<u64>,
     pub gas_used: Option<u64>,
-    pub gas_price: Option<u64>,
+    pub gas_price: Option<Wei>,
+    pub burned_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<NftModel> for NftResponse {
+    fn from(nft: NftModel) -> Self {
+        // Convert stored JSON attributes back to NftAttribute structs
+        let attributes = nft.attributes.and_then(|attrs| serde_json::from_value::<Vec<NftAttribute>>(attrs).ok());
+
+        NftResponse {
+            id: nft.id,
+            token_id: nft.token_id,
+            name: nft.name,
+            description: nft.description,
+            image: nft.image,
+            minted_at: chrono::DateTime::from_naive_utc_and_offset(nft.minted_at, chrono::Utc),
+            transaction_hash: nft.transaction_hash,
+            owner_id: nft.owner_id,
+            attributes,
+            collection_name: nft.collection_name,
+            collection_id: nft.collection_id,
+            mint_status: nft.mint_status.parse().ok(),
+            block_number: None,
+            gas_used: None,
+            gas_price: None,
+            burned_at: nft.burned_at.map(|at| chrono::DateTime::from_naive_utc_and_offset(at, chrono::Utc)),
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -47,10 +121,41 @@ pub struct NftWithOwnerResponse {
     pub owner: UserResponse,
     pub attributes: Option<Vec<NftAttribute>>,
     pub collection_name: Option<String>,
+    pub collection_id: Option<String>,
     pub mint_status: Option<MintStatus>,
     pub block_number: Option<u64>,
     pub gas_used: Option<u64>,
-    pub gas_price: Option<u64>,
+    pub gas_price: Option<Wei>,
+    pub burned_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<(NftModel, UserModel)> for NftWithOwnerResponse {
+    fn from((nft, user): (NftModel, UserModel)) -> Self {
+        let nft = NftResponse::from(nft);
+
+        NftWithOwnerResponse {
+            id: nft.id,
+            token_id: nft.token_id,
+            name: nft.name,
+            description: nft.description,
+            image: nft.image,
+            minted_at: nft.minted_at,
+            transaction_hash: nft.transaction_hash,
+            owner: UserResponse {
+                id: user.id,
+                public_key: user.public_key,
+                created_at: chrono::DateTime::from_naive_utc_and_offset(user.created_at, chrono::Utc),
+            },
+            attributes: nft.attributes,
+            collection_name: nft.collection_name,
+            collection_id: nft.collection_id,
+            mint_status: nft.mint_status,
+            block_number: nft.block_number,
+            gas_used: nft.gas_used,
+            gas_price: nft.gas_price,
+            burned_at: nft.burned_at,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -60,14 +165,6 @@ pub struct UserResponse {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct PaginatedResponse<T> {
-    pub data: Vec<T>,
-    pub total: u64,
-    pub page: u64,
-    pub limit: u64,
-}
-
 #[derive(Debug, Serialize)]
 pub struct MintResponse {
     pub success: bool,
@@ -76,20 +173,179 @@ pub struct MintResponse {
     pub transaction_hash: Option<String>,
     pub block_number: Option<u64>,
     pub gas_used: Option<u64>,
-    pub gas_price: Option<u64>,
+    pub gas_price: Option<Wei>,
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+    /// Position of the mint transaction among the owner wallet's transactions
+    pub nonce: Option<u64>,
     pub mint_status: MintStatus,
     pub message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MintStatusResponse {
     pub mint_id: String,
     pub status: MintStatus,
+    /// The transaction the mint currently follows; a speed-up or cancellation changes it
     pub transaction_hash: Option<String>,
+    pub nonce: Option<u64>,
     pub block_number: Option<u64>,
     pub gas_used: Option<u64>,
-    pub gas_price: Option<u64>,
+    pub gas_price: Option<Wei>,
     pub confirmations: Option<u32>,
     pub created_at: u64,
     pub confirmed_at: Option<u64>,
-} 
\ No newline at end of file
+    /// Why the mint failed: the transaction's revert reason, or a note that it was dropped
+    pub failure_reason: Option<String>,
+}
+
+impl From<MintingStatus> for MintStatusResponse {
+    fn from(minting_status: MintingStatus) -> Self {
+        let transaction_details = minting_status.transaction_details.as_ref();
+
+        MintStatusResponse {
+            mint_id: minting_status.mint_id,
+            status: minting_status.status,
+            transaction_hash: transaction_details.map(|tx| tx.transaction_hash.clone()),
+            nonce: transaction_details.and_then(|tx| tx.nonce),
+            block_number: transaction_details.and_then(|tx| tx.block_number),
+            gas_used: transaction_details.map(|tx| tx.gas_used),
+            gas_price: transaction_details.map(|tx| tx.gas_price),
+            confirmations: transaction_details.map(|tx| tx.confirmations),
+            created_at: minting_status.created_at,
+            confirmed_at: minting_status.confirmed_at,
+            failure_reason: transaction_details.and_then(|tx| match tx.status {
+                TransactionStatus::Dropped => Some("Transaction was dropped from the mempool".to_string()),
+                _ => tx.revert_reason.clone(),
+            }),
+        }
+    }
+}
+
+/// What became of one item of a batch mint, at its position in the request
+#[derive(Debug, Serialize)]
+pub struct BatchMintItemResult {
+    pub index: usize,
+    pub success: bool,
+    pub mint_id: Option<String>,
+    pub nft: Option<NftResponse>,
+    pub transaction_hash: Option<String>,
+    /// Why the item was rejected; the rest of the batch is minted without it
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchMintResponse {
+    pub batch_id: String,
+    /// One transaction per chunk, in the order they were sent
+    pub transaction_hashes: Vec<String>,
+    pub queued: usize,
+    pub rejected: usize,
+    pub items: Vec<BatchMintItemResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchMintStatus {
+    pub nft_id: String,
+    #[serde(flatten)]
+    pub mint: MintStatusResponse,
+}
+
+/// Progress of every mint a batch request queued
+#[derive(Debug, Serialize)]
+pub struct BatchStatusResponse {
+    pub batch_id: String,
+    pub total: usize,
+    pub pending: usize,
+    pub confirming: usize,
+    pub confirmed: usize,
+    pub failed: usize,
+    /// Set once every mint is confirmed or failed
+    pub finished: bool,
+    pub transaction_hashes: Vec<String>,
+    pub mints: Vec<BatchMintStatus>,
+}
+
+impl BatchStatusResponse {
+    pub fn new(batch_id: String, mints: Vec<(String, MintingStatus)>) -> Self {
+        let count = |status: MintStatus| mints.iter().filter(|(_, mint)| mint.status == status).count();
+        let (pending, confirming) = (count(MintStatus::Pending), count(MintStatus::Confirming));
+        let (confirmed, failed) = (count(MintStatus::Confirmed), count(MintStatus::Failed));
+
+        let mut transaction_hashes: Vec<String> = Vec::new();
+        for hash in mints.iter().filter_map(|(_, mint)| mint.transaction_details.as_ref()).map(|tx| &tx.transaction_hash) {
+            if !transaction_hashes.contains(hash) {
+                transaction_hashes.push(hash.clone());
+            }
+        }
+
+        BatchStatusResponse {
+            batch_id,
+            total: mints.len(),
+            pending,
+            confirming,
+            confirmed,
+            failed,
+            finished: pending + confirming == 0,
+            transaction_hashes,
+            mints: mints
+                .into_iter()
+                .map(|(nft_id, mint)| BatchMintStatus { nft_id, mint: MintStatusResponse::from(mint) })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferResponse {
+    pub id: String,
+    pub nft_id: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub transaction_hash: String,
+    /// The NFT changes hands once this reaches `Confirmed`
+    pub status: Option<MintStatus>,
+    pub block_number: Option<u64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub confirmed_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<TransferModel> for TransferResponse {
+    fn from(transfer: TransferModel) -> Self {
+        TransferResponse {
+            id: transfer.id,
+            nft_id: transfer.nft_id,
+            from_address: transfer.from_address,
+            to_address: transfer.to_address,
+            transaction_hash: transfer.transaction_hash,
+            status: transfer.status.parse().ok(),
+            block_number: transfer.block_number.map(|block| block as u64),
+            created_at: chrono::DateTime::from_naive_utc_and_offset(transfer.created_at, chrono::Utc),
+            confirmed_at: transfer.confirmed_at.map(|at| chrono::DateTime::from_naive_utc_and_offset(at, chrono::Utc)),
+        }
+    }
+}
+
+/// One change of an NFT's owner: its mint, sent from the zero address, or a transfer
+#[derive(Debug, Serialize)]
+pub struct OwnershipChange {
+    /// Unset for the mint
+    pub transfer_id: Option<String>,
+    pub from_address: String,
+    pub to_address: String,
+    pub transaction_hash: String,
+    pub status: Option<MintStatus>,
+    pub block_number: Option<u64>,
+    /// Time of the block that included the change
+    pub block_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProvenanceResponse {
+    pub nft_id: String,
+    pub token_id: String,
+    /// Wallet holding the NFT after the last confirmed change
+    pub owner: Option<String>,
+    /// Every change of owner that hasn't failed, oldest first
+    pub history: Vec<OwnershipChange>,
+}
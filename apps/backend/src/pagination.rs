@@ -0,0 +1,180 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// `?page=&limit=` offset pagination, or `?cursor=&limit=` keyset pagination where supported
+#[derive(Debug, Default, Deserialize)]
+pub struct PaginationQuery {
+    pub page: Option<u64>,
+    pub limit: Option<u64>,
+    pub cursor: Option<String>,
+}
+
+impl PaginationQuery {
+    pub fn page(&self) -> u64 {
+        self.page.unwrap_or(0)
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Rows to skip; keyset pages always start right after their cursor. Pages too far out for
+    /// Postgres' signed offsets stop at the largest one, which is past every row.
+    pub fn offset(&self) -> u64 {
+        if self.cursor.is_some() {
+            0
+        } else {
+            self.page().saturating_mul(self.limit()).min(i64::MAX as u64)
+        }
+    }
+
+    pub fn cursor(&self) -> Result<Option<Cursor>> {
+        self.cursor.as_deref().map(Cursor::decode).transpose()
+    }
+}
+
+/// Position of the last row of a page in `(minted_at, id)` order.
+///
+/// Clients treat the encoded form as opaque; it is hex so it can be passed in a URL unescaped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub minted_at: NaiveDateTime,
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn new(minted_at: NaiveDateTime, id: impl Into<String>) -> Self {
+        Cursor { minted_at, id: id.into() }
+    }
+
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}:{}", self.minted_at.and_utc().timestamp_micros(), self.id))
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self> {
+        let invalid = || anyhow!("Invalid cursor");
+        let decoded = String::from_utf8(hex::decode(encoded).map_err(|_| invalid())?).map_err(|_| invalid())?;
+        let (micros, id) = decoded.split_once(':').ok_or_else(invalid)?;
+        let micros = micros.parse::<i64>().map_err(|_| invalid())?;
+        let minted_at = DateTime::from_timestamp_micros(micros).ok_or_else(invalid)?.naive_utc();
+        if id.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Cursor { minted_at, id: id.to_string() })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
+    /// Number of rows matching the query across all pages
+    pub total: u64,
+    pub page: u64,
+    pub limit: u64,
+    pub has_more: bool,
+    /// Pass as `cursor` to fetch the next page; only set for keyset-paginated listings
+    pub next_cursor: Option<String>,
+}
+
+impl<T> PaginatedResponse<T> {
+    /// Build an offset-paginated page from exactly one page of rows
+    pub fn new(data: Vec<T>, total: u64, query: &PaginationQuery) -> Self {
+        let has_more = query.offset().saturating_add(data.len() as u64) < total;
+
+        PaginatedResponse {
+            data,
+            total,
+            page: query.page(),
+            limit: query.limit(),
+            has_more,
+            next_cursor: None,
+        }
+    }
+
+    /// Build a page from up to `limit + 1` rows; the extra row only signals that more exist.
+    ///
+    /// `cursor` gives the keyset position of a row, or `None` when the ordering cannot be resumed from a cursor.
+    pub fn from_rows(mut rows: Vec<T>, total: u64, query: &PaginationQuery, cursor: impl Fn(&T) -> Option<Cursor>) -> Self {
+        let limit = query.limit();
+        let has_more = rows.len() as u64 > limit;
+        rows.truncate(limit as usize);
+        let next_cursor = if has_more { rows.last().and_then(cursor).map(|cursor| cursor.encode()) } else { None };
+
+        PaginatedResponse {
+            data: rows,
+            total,
+            page: query.page(),
+            limit,
+            has_more,
+            next_cursor,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> PaginatedResponse<U> {
+        PaginatedResponse {
+            data: self.data.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            limit: self.limit,
+            has_more: self.has_more,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(micros: i64) -> NaiveDateTime {
+        DateTime::from_timestamp_micros(micros).unwrap().naive_utc()
+    }
+
+    #[test]
+    fn test_cursor_roundtrip() {
+        let cursor = Cursor::new(at(1_760_000_000_123_456), "abc123");
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+
+        assert!(Cursor::decode("not-hex").is_err());
+        assert!(Cursor::decode(&hex::encode("12345")).is_err());
+        assert!(Cursor::decode(&hex::encode("abc:id")).is_err());
+    }
+
+    #[test]
+    fn test_offset_page_has_more() {
+        let query = PaginationQuery { page: Some(1), limit: Some(2), cursor: None };
+        assert!(PaginatedResponse::new(vec![3, 4], 5, &query).has_more);
+        assert!(!PaginatedResponse::new(vec![3, 4], 4, &query).has_more);
+    }
+
+    #[test]
+    fn test_offset_of_far_pages_does_not_overflow() {
+        let query = PaginationQuery { page: Some(u64::MAX), limit: Some(MAX_PAGE_SIZE), cursor: None };
+        assert_eq!(query.offset(), i64::MAX as u64);
+        assert!(!PaginatedResponse::new(Vec::<u64>::new(), 5, &query).has_more);
+    }
+
+    #[test]
+    fn test_keyset_page_trims_lookahead_row() {
+        let query = PaginationQuery { page: None, limit: Some(2), cursor: None };
+        let cursor_of = |row: &(i64, &str)| Some(Cursor::new(at(row.0), row.1));
+
+        let page = PaginatedResponse::from_rows(vec![(3, "c"), (2, "b"), (1, "a")], 3, &query, cursor_of);
+        assert_eq!(page.data.len(), 2);
+        assert!(page.has_more);
+        assert_eq!(Cursor::decode(page.next_cursor.as_deref().unwrap()).unwrap(), Cursor::new(at(2), "b"));
+
+        let last = PaginatedResponse::from_rows(vec![(1, "a")], 3, &query, cursor_of);
+        assert!(!last.has_more);
+        assert_eq!(last.next_cursor, None);
+
+        let unresumable = PaginatedResponse::from_rows(vec![(3, "c"), (2, "b"), (1, "a")], 3, &query, |_| None);
+        assert!(unresumable.has_more);
+        assert_eq!(unresumable.next_cursor, None);
+    }
+}
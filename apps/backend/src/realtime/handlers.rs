@@ -0,0 +1,297 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    Json,
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use std::collections::HashSet;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tokio::time::{timeout, Duration};
+use crate::{
+    auth::types::ApiResponse,
+    blockchain_sim::MintingStatus,
+    minting_queue::{MintEvent, MintingQueue},
+    nft::types::MintStatusResponse,
+    realtime::types::*,
+};
+
+/// How often the stream of a final mint checks whether a reorg can still reach its block
+const SETTLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Server-Sent Events stream of one mint's status. It ends once the mint is final and its block is
+/// beyond the simulator's max reorg depth, so a confirmed mint that is rolled back still reports it.
+pub async fn mint_status_stream_handler(
+    State(minting_queue): State<MintingQueue>,
+    Path(mint_id): Path<String>,
+) -> Response {
+    // Subscribe before reading the current status so no transition slips in between
+    let events = minting_queue.subscribe();
+
+    let current = match minting_queue.get_mint_status(&mint_id).await {
+        Ok(Some(minting_status)) => minting_status,
+        Ok(None) => {
+            let response = ApiResponse::<MintStatusResponse> {
+                success: false,
+                data: None,
+                message: "Mint not found".to_string(),
+            };
+            return (StatusCode::NOT_FOUND, Json(response)).into_response();
+        }
+        Err(e) => {
+            let response = ApiResponse::<MintStatusResponse> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve mint status: {}", e),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response)).into_response();
+        }
+    };
+
+    Sse::new(mint_status_stream(minting_queue, current, events))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn mint_status_event(minting_status: MintingStatus) -> Result<Event, axum::Error> {
+    Event::default()
+        .event("mint_status")
+        .json_data(MintStatusResponse::from(minting_status))
+}
+
+/// A `mint_status` event, or a `mint_reorg` event when the transition is a rollback
+fn mint_event(minting_status: MintingStatus, orphaned_block: Option<u64>) -> Result<Event, axum::Error> {
+    match orphaned_block {
+        Some(orphaned_block) => Event::default().event("mint_reorg").json_data(MintReorgEvent {
+            orphaned_block,
+            data: MintStatusResponse::from(minting_status),
+        }),
+        None => mint_status_event(minting_status),
+    }
+}
+
+fn mint_status_stream(
+    minting_queue: MintingQueue,
+    current: MintingStatus,
+    events: Receiver<MintEvent>,
+) -> impl Stream<Item = Result<Event, axum::Error>> {
+    let mint_id = current.mint_id.clone();
+    // The latest status while the mint can still change
+    let watched = (!minting_queue.is_settled(&current)).then(|| current.clone());
+    let initial = stream::once(async move { mint_status_event(current) });
+
+    let updates = stream::unfold((events, watched), move |(mut events, watched)| {
+        let minting_queue = minting_queue.clone();
+        let mint_id = mint_id.clone();
+        async move {
+            let latest = watched?;
+            loop {
+                // A final mint only changes again if a reorg orphans its block, which no event announces
+                // in advance, so the block's depth is checked between events
+                let event = if latest.status.is_final() {
+                    match timeout(SETTLE_CHECK_INTERVAL, events.recv()).await {
+                        Ok(event) => event,
+                        Err(_) if minting_queue.is_settled(&latest) => return None,
+                        Err(_) => continue,
+                    }
+                } else {
+                    events.recv().await
+                };
+                let (minting_status, orphaned_block) = match event {
+                    Ok(event) if event.minting_status.mint_id == mint_id => (event.minting_status, event.orphaned_block),
+                    Ok(_) => continue,
+                    // Missed events may include ours; resend whatever the queue has now
+                    Err(RecvError::Lagged(_)) => match minting_queue.get_mint_status(&mint_id).await {
+                        Ok(Some(minting_status)) => (minting_status, None),
+                        _ => continue,
+                    },
+                    Err(RecvError::Closed) => return None,
+                };
+                let watched = (!minting_queue.is_settled(&minting_status)).then(|| minting_status.clone());
+                return Some((mint_event(minting_status, orphaned_block), (events, watched)));
+            }
+        }
+    });
+
+    initial.chain(updates)
+}
+
+/// Multiplexed socket where clients subscribe to mint IDs or wallets
+pub async fn ws_handler(
+    State(minting_queue): State<MintingQueue>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, minting_queue))
+}
+
+#[derive(Debug, Default)]
+struct Subscriptions {
+    mint_ids: HashSet<String>,
+    wallets: HashSet<String>,
+}
+
+impl Subscriptions {
+    fn matches(&self, event: &MintEvent) -> bool {
+        self.mint_ids.contains(&event.minting_status.mint_id)
+            || self.wallets.contains(&event.wallet_address.to_lowercase())
+    }
+
+    fn snapshot(&self) -> ServerMessage {
+        ServerMessage::Subscriptions {
+            mint_ids: self.mint_ids.iter().cloned().collect(),
+            wallets: self.wallets.iter().cloned().collect(),
+        }
+    }
+}
+
+async fn send_message(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).map_err(axum::Error::new)?;
+    socket.send(Message::Text(text.into())).await
+}
+
+async fn handle_socket(mut socket: WebSocket, minting_queue: MintingQueue) {
+    let mut events = minting_queue.subscribe();
+    let mut subscriptions = Subscriptions::default();
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    // Pings are answered by axum; other frames carry nothing for us
+                    Some(Ok(_)) => continue,
+                };
+
+                let new_mint_ids = match serde_json::from_str::<ClientMessage>(text.as_str()) {
+                    Ok(ClientMessage::Subscribe { mint_ids, wallets }) => {
+                        subscriptions.wallets.extend(wallets.iter().map(|wallet| wallet.to_lowercase()));
+                        mint_ids
+                            .into_iter()
+                            .filter(|mint_id| subscriptions.mint_ids.insert(mint_id.clone()))
+                            .collect()
+                    }
+                    Ok(ClientMessage::Unsubscribe { mint_ids, wallets }) => {
+                        for mint_id in &mint_ids {
+                            subscriptions.mint_ids.remove(mint_id);
+                        }
+                        for wallet in &wallets {
+                            subscriptions.wallets.remove(&wallet.to_lowercase());
+                        }
+                        Vec::new()
+                    }
+                    Err(e) => {
+                        let message = ServerMessage::Error { message: format!("Invalid message: {}", e) };
+                        if send_message(&mut socket, &message).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                };
+
+                if send_message(&mut socket, &subscriptions.snapshot()).await.is_err() {
+                    break;
+                }
+
+                // Newly watched mints get their current status right away
+                for mint_id in new_mint_ids {
+                    let message = match minting_queue.get_mint_status(&mint_id).await {
+                        Ok(Some(minting_status)) => ServerMessage::MintStatus {
+                            wallet_address: None,
+                            data: MintStatusResponse::from(minting_status),
+                        },
+                        Ok(None) => ServerMessage::Error { message: format!("Mint {} not found", mint_id) },
+                        Err(e) => ServerMessage::Error { message: format!("Failed to retrieve mint {}: {}", mint_id, e) },
+                    };
+                    if send_message(&mut socket, &message).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            event = events.recv() => {
+                let message = match event {
+                    Ok(event) if subscriptions.matches(&event) => match event.orphaned_block {
+                        Some(orphaned_block) => ServerMessage::MintReorg {
+                            wallet_address: event.wallet_address,
+                            orphaned_block,
+                            data: MintStatusResponse::from(event.minting_status),
+                        },
+                        None => ServerMessage::MintStatus {
+                            wallet_address: Some(event.wallet_address),
+                            data: MintStatusResponse::from(event.minting_status),
+                        },
+                    },
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => ServerMessage::Lagged { skipped },
+                    Err(RecvError::Closed) => break,
+                };
+                if send_message(&mut socket, &message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use tokio::sync::broadcast;
+    use crate::blockchain_sim::{
+        BlockchainSimulator, Clock, MintStatus, SimulatorConfig, TransactionDetails, TransactionRequest, DEFAULT_BLOCK_TIME,
+        DEFAULT_MAX_REORG_DEPTH, GENESIS_BLOCK,
+    };
+
+    fn event_name(event: &Event) -> &'static str {
+        let event = format!("{:?}", event);
+        if event.contains("event: mint_reorg") { "mint_reorg" } else { "mint_status" }
+    }
+
+    #[tokio::test]
+    async fn test_stream_reports_rollback_after_confirmation() {
+        let config = SimulatorConfig { seed: Some(7), required_confirmations: 1, ..SimulatorConfig::default() };
+        let simulator = Arc::new(BlockchainSimulator::new(config, Clock::manual(1_700_000_000)));
+        let pool = Arc::new(MockDatabase::new(DatabaseBackend::Postgres).into_connection());
+        let minting_queue = MintingQueue::new(pool, simulator.clone());
+        let (sender, events) = broadcast::channel(16);
+
+        let transaction = simulator.submit_transaction("0xabc", TransactionRequest::default());
+        let mint = |status: MintStatus, block_number: Option<u64>| MintingStatus {
+            mint_id: "mint_1".to_string(),
+            status,
+            transaction_details: Some(TransactionDetails { block_number, ..transaction.clone() }),
+            created_at: 1_700_000_000,
+            confirmed_at: None,
+        };
+        let publish = |minting_status: MintingStatus, orphaned_block: Option<u64>| {
+            sender.send(MintEvent { wallet_address: "0xabc".to_string(), minting_status, orphaned_block }).unwrap();
+        };
+
+        let mut stream = Box::pin(mint_status_stream(minting_queue, mint(MintStatus::Pending, None), events));
+        assert_eq!(event_name(&stream.next().await.unwrap().unwrap()), "mint_status");
+
+        // Confirmed, but a reorg can still reach the block, so the stream stays open and reports the rollback
+        simulator.clock().advance(2 * DEFAULT_BLOCK_TIME);
+        simulator.produce_blocks();
+        publish(mint(MintStatus::Confirmed, Some(GENESIS_BLOCK + 1)), None);
+        assert_eq!(event_name(&stream.next().await.unwrap().unwrap()), "mint_status");
+        publish(mint(MintStatus::Pending, None), Some(GENESIS_BLOCK + 1));
+        assert_eq!(event_name(&stream.next().await.unwrap().unwrap()), "mint_reorg");
+
+        // Confirmed again; the stream ends once the block is deeper than any reorg
+        publish(mint(MintStatus::Confirmed, Some(GENESIS_BLOCK + 2)), None);
+        assert_eq!(event_name(&stream.next().await.unwrap().unwrap()), "mint_status");
+        simulator.clock().advance(DEFAULT_MAX_REORG_DEPTH * DEFAULT_BLOCK_TIME);
+        simulator.produce_blocks();
+        assert!(!simulator.reorg_can_reach(GENESIS_BLOCK + 2));
+        assert!(timeout(3 * SETTLE_CHECK_INTERVAL, stream.next()).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use crate::nft::types::MintStatusResponse;
+
+/// Messages clients send over `/api/ws`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        #[serde(default)]
+        mint_ids: Vec<String>,
+        #[serde(default)]
+        wallets: Vec<String>,
+    },
+    Unsubscribe {
+        #[serde(default)]
+        mint_ids: Vec<String>,
+        #[serde(default)]
+        wallets: Vec<String>,
+    },
+}
+
+/// Messages pushed to clients over `/api/ws`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    MintStatus {
+        wallet_address: Option<String>,
+        data: MintStatusResponse,
+    },
+    /// A reorg orphaned the block that had included the mint, which is pending again
+    MintReorg {
+        wallet_address: String,
+        orphaned_block: u64,
+        data: MintStatusResponse,
+    },
+    Subscriptions {
+        mint_ids: Vec<String>,
+        wallets: Vec<String>,
+    },
+    Lagged {
+        skipped: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Payload of the `mint_reorg` Server-Sent Event: the rolled back status and the block that was orphaned
+#[derive(Debug, Serialize)]
+pub struct MintReorgEvent {
+    pub orphaned_block: u64,
+    #[serde(flatten)]
+    pub data: MintStatusResponse,
+}
@@ -0,0 +1,359 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    Json,
+    response::{IntoResponse, Response},
+    http::StatusCode,
+};
+use serde_json::{json, Value};
+use crate::{
+    auth::config::AuthConfig,
+    blockchain_sim::{block_hash, SharedSimulator, TransactionLog, TransactionStatus, BLOCK_GAS_LIMIT, ZERO_ADDRESS},
+    crypto::{keccak256, parse_address},
+    database::DbPool,
+    db_operations::{find_transaction_with_nft, find_transactions_in_block},
+    entities::ChainTransactionModel,
+    rpc::types::*,
+};
+
+/// keccak256("Transfer(address,address,uint256)")
+const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+/// Hash of an empty uncle list
+const EMPTY_UNCLES_HASH: &str = "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347";
+
+/// Ethereum JSON-RPC over the simulated chain; accepts single and batched requests
+pub async fn rpc_handler(
+    State(pool): State<DbPool>,
+    State(simulator): State<SharedSimulator>,
+    State(auth): State<AuthConfig>,
+    body: Bytes,
+) -> Response {
+    let rpc = SimulatedNode { pool, simulator, chain_id: auth.chain_id };
+
+    let request: Value = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            let error = RpcError::new(PARSE_ERROR, format!("Parse error: {}", e));
+            return Json(RpcResponse::new(Value::Null, Err(error))).into_response();
+        }
+    };
+
+    match request {
+        Value::Array(batch) if batch.is_empty() => {
+            let error = RpcError::new(INVALID_REQUEST, "Empty batch");
+            Json(RpcResponse::new(Value::Null, Err(error))).into_response()
+        }
+        Value::Array(batch) => {
+            let mut responses = Vec::with_capacity(batch.len());
+            for request in batch {
+                if let Some(response) = rpc.handle(request).await {
+                    responses.push(response);
+                }
+            }
+            if responses.is_empty() {
+                StatusCode::NO_CONTENT.into_response()
+            } else {
+                Json(responses).into_response()
+            }
+        }
+        request => match rpc.handle(request).await {
+            Some(response) => Json(response).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        },
+    }
+}
+
+struct SimulatedNode {
+    pool: DbPool,
+    simulator: SharedSimulator,
+    chain_id: u64,
+}
+
+impl SimulatedNode {
+    /// Answer one request; notifications (requests without an id) get no response
+    async fn handle(&self, request: Value) -> Option<RpcResponse> {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let request: RpcRequest = match serde_json::from_value(request) {
+            Ok(request) => request,
+            Err(e) => return Some(RpcResponse::new(id, Err(RpcError::new(INVALID_REQUEST, format!("Invalid request: {}", e))))),
+        };
+
+        let outcome = self.call(&request.method, &request.params).await;
+        request.id.map(|id| RpcResponse::new(id, outcome))
+    }
+
+    async fn call(&self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "eth_chainId" => Ok(json!(quantity(self.chain_id))),
+            "net_version" => Ok(json!(self.chain_id.to_string())),
+            "eth_blockNumber" => Ok(json!(quantity(self.simulator.head_block_number()))),
+            "eth_gasPrice" => Ok(json!(quantity(self.simulator.suggested_gas_price()))),
+            "eth_maxPriorityFeePerGas" => Ok(json!(quantity(self.simulator.gas_estimate().standard.max_priority_fee_per_gas))),
+            "eth_getTransactionCount" => {
+                let address = address_param(params)?;
+                // Only the current state is simulated, so every tag but `pending` reads the mined nonce
+                let nonce = match params.get(1).and_then(Value::as_str) {
+                    Some("pending") => self.simulator.pending_nonce(&address),
+                    _ => self.simulator.account_nonce(&address),
+                };
+                Ok(json!(quantity(nonce)))
+            }
+            "eth_getTransactionByHash" => self.get_transaction_by_hash(hash_param(params)?).await,
+            "eth_getTransactionReceipt" => self.get_transaction_receipt(hash_param(params)?).await,
+            "eth_getBlockByNumber" => {
+                let tag = BlockTag::parse(params.first().unwrap_or(&json!("latest")))?;
+                let full = params.get(1).and_then(Value::as_bool).unwrap_or(false);
+                self.get_block_by_number(tag, full).await
+            }
+            other => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method {} is not supported", other))),
+        }
+    }
+
+    async fn get_transaction_by_hash(&self, hash: String) -> Result<Value, RpcError> {
+        let Some((transaction, _)) = find_transaction_with_nft(&self.pool, &hash).await.map_err(internal_error)? else {
+            return Ok(Value::Null);
+        };
+        let position = self.position_in_block(&transaction).await?;
+
+        Ok(self.transaction_object(&transaction, position.map(|(index, _)| index)))
+    }
+
+    async fn get_transaction_receipt(&self, hash: String) -> Result<Value, RpcError> {
+        let Some((transaction, _)) = find_transaction_with_nft(&self.pool, &hash).await.map_err(internal_error)? else {
+            return Ok(Value::Null);
+        };
+        // Transactions still in the mempool have no receipt yet
+        let (Some(block_number), Some((index, cumulative_gas_used))) =
+            (transaction.block_number, self.position_in_block(&transaction).await?)
+        else {
+            return Ok(Value::Null);
+        };
+        let block_number = block_number as u64;
+
+        let succeeded = transaction.status != TransactionStatus::Failed.as_str();
+        // A reverted transaction emits no events
+        let logs: Vec<TransactionLog> = if succeeded {
+            serde_json::from_value(transaction.logs.clone()).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let logs: Vec<Value> = logs
+            .iter()
+            .map(|log| {
+                json!({
+                    "address": log.address,
+                    "topics": [TRANSFER_TOPIC, address_topic(&log.from), address_topic(&log.to), token_id_topic(&log.token_id)],
+                    "data": "0x",
+                    "blockNumber": quantity(block_number),
+                    "blockHash": included_block_hash(&transaction),
+                    "transactionHash": transaction.hash,
+                    "transactionIndex": quantity(index),
+                    "logIndex": quantity(log.log_index as u64),
+                    "removed": false,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "transactionHash": transaction.hash,
+            "transactionIndex": quantity(index),
+            "blockHash": included_block_hash(&transaction),
+            "blockNumber": quantity(block_number),
+            "from": transaction.from_address,
+            "to": transaction.to_address,
+            "cumulativeGasUsed": quantity(cumulative_gas_used),
+            "gasUsed": quantity(transaction.gas_used as u64),
+            "effectiveGasPrice": quantity(transaction.gas_price),
+            "contractAddress": null,
+            "logs": logs,
+            "logsBloom": empty_bloom(),
+            "status": if succeeded { "0x1" } else { "0x0" },
+            "type": transaction_type(&transaction),
+        }))
+    }
+
+    async fn get_block_by_number(&self, tag: BlockTag, full: bool) -> Result<Value, RpcError> {
+        let number = match tag {
+            BlockTag::Number(number) => number,
+            BlockTag::Earliest => 0,
+            BlockTag::Latest => self.simulator.head_block_number(),
+        };
+        let Some(estimated_timestamp) = self.simulator.block_timestamp(number) else {
+            return Ok(Value::Null);
+        };
+
+        let transactions = find_transactions_in_block(&self.pool, number).await.map_err(internal_error)?;
+        // Blocks produced before a restart keep the timestamp and base fee recorded with their transactions
+        let timestamp = transactions
+            .iter()
+            .find_map(|transaction| transaction.block_timestamp)
+            .map_or(estimated_timestamp, |at| at.and_utc().timestamp() as u64);
+        let recent = self.simulator.block(number);
+        // Blocks replaced by a reorg share their number, so the hash comes from the block itself when possible
+        let hash = recent
+            .as_ref()
+            .map(|block| block.hash.clone())
+            .or_else(|| transactions.iter().find_map(|transaction| transaction.block_hash.clone()))
+            .unwrap_or_else(|| block_hash(number, 0));
+        let parent_number = number.saturating_sub(1);
+        let parent_hash = self
+            .simulator
+            .block(parent_number)
+            .map_or_else(|| block_hash(parent_number, 0), |parent| parent.hash);
+        let base_fee_per_gas = recent
+            .as_ref()
+            .map(|block| block.base_fee_per_gas)
+            .or_else(|| transactions.iter().find_map(|transaction| transaction.base_fee_per_gas));
+        // Blocks still in memory also count the gas used by background traffic
+        let gas_used = recent
+            .map(|block| block.gas_used)
+            .unwrap_or_else(|| transactions.iter().map(|transaction| transaction.gas_used as u64).sum());
+        let transactions: Vec<Value> = transactions
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| {
+                if full {
+                    self.transaction_object(transaction, Some(index as u64))
+                } else {
+                    json!(transaction.hash)
+                }
+            })
+            .collect();
+        let zero_hash = format!("0x{}", "0".repeat(64));
+
+        Ok(json!({
+            "number": quantity(number),
+            "hash": hash,
+            "parentHash": parent_hash,
+            "nonce": "0x0000000000000000",
+            "sha3Uncles": EMPTY_UNCLES_HASH,
+            "logsBloom": empty_bloom(),
+            "transactionsRoot": zero_hash,
+            "stateRoot": zero_hash,
+            "receiptsRoot": zero_hash,
+            "mixHash": zero_hash,
+            "miner": ZERO_ADDRESS,
+            "difficulty": "0x0",
+            "extraData": "0x",
+            "gasLimit": quantity(BLOCK_GAS_LIMIT),
+            "gasUsed": quantity(gas_used),
+            "baseFeePerGas": base_fee_per_gas.map(quantity),
+            "timestamp": quantity(timestamp),
+            "transactions": transactions,
+            "uncles": [],
+        }))
+    }
+
+    /// Index of an included transaction within its block and the block's gas used up to and including it
+    async fn position_in_block(&self, transaction: &ChainTransactionModel) -> Result<Option<(u64, u64)>, RpcError> {
+        let Some(block_number) = transaction.block_number else {
+            return Ok(None);
+        };
+
+        let block = find_transactions_in_block(&self.pool, block_number as u64).await.map_err(internal_error)?;
+        let mut cumulative_gas_used = 0;
+        for (index, included) in block.iter().enumerate() {
+            cumulative_gas_used += included.gas_used as u64;
+            if included.hash == transaction.hash {
+                return Ok(Some((index as u64, cumulative_gas_used)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn transaction_object(&self, transaction: &ChainTransactionModel, index: Option<u64>) -> Value {
+        let block_number = transaction.block_number.map(|block| block as u64);
+        let block_hash = block_number.map(|_| included_block_hash(transaction));
+        // Transactions are not really signed; derive a stable placeholder signature from the hash
+        let r = keccak256(transaction.hash.as_bytes());
+        let s = keccak256(&r);
+
+        let mut object = json!({
+            "hash": transaction.hash,
+            "nonce": quantity(transaction.nonce.unwrap_or_default() as u64),
+            "blockHash": block_hash,
+            "blockNumber": block_number.map(quantity),
+            "transactionIndex": index.map(quantity),
+            "from": transaction.from_address,
+            "to": transaction.to_address,
+            "value": "0x0",
+            "gas": quantity(transaction.gas_used as u64),
+            "gasPrice": quantity(transaction.gas_price),
+            "input": "0x",
+            "type": transaction_type(transaction),
+            "chainId": quantity(self.chain_id),
+            "v": quantity(self.chain_id * 2 + 35),
+            "r": topic(&r),
+            "s": topic(&s),
+        });
+        if let (Some(max_fee), Some(priority_fee)) = (transaction.max_fee_per_gas, transaction.max_priority_fee_per_gas) {
+            object["maxFeePerGas"] = json!(quantity(max_fee));
+            object["maxPriorityFeePerGas"] = json!(quantity(priority_fee));
+            // EIP-1559 signatures carry the y-parity instead of an EIP-155 `v`
+            object["v"] = json!("0x0");
+            object["yParity"] = json!("0x0");
+        }
+
+        object
+    }
+}
+
+fn internal_error(e: anyhow::Error) -> RpcError {
+    RpcError::new(INTERNAL_ERROR, e.to_string())
+}
+
+/// The lowercased 32-byte hash passed as the first parameter
+fn hash_param(params: &[Value]) -> Result<String, RpcError> {
+    let hash = params
+        .first()
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params("Expected a transaction hash"))?;
+    let digits = hash.strip_prefix("0x").unwrap_or_default();
+    if digits.len() != 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(RpcError::invalid_params(format!("Invalid transaction hash: {}", hash)));
+    }
+
+    Ok(hash.to_lowercase())
+}
+
+/// The account address passed as the first parameter
+fn address_param(params: &[Value]) -> Result<String, RpcError> {
+    let address = params
+        .first()
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params("Expected an address"))?;
+    parse_address(address).map_err(|e| RpcError::invalid_params(format!("Invalid address {}: {}", address, e)))?;
+
+    Ok(address.to_string())
+}
+
+fn address_topic(address: &str) -> String {
+    topic(&parse_address(&address.to_lowercase()).unwrap_or_default())
+}
+
+/// Numeric token IDs are encoded as uint256; other IDs are represented by their keccak hash
+fn token_id_topic(token_id: &str) -> String {
+    match token_id.parse::<u64>() {
+        Ok(number) => topic(&number.to_be_bytes()),
+        Err(_) => topic(&keccak256(token_id.as_bytes())),
+    }
+}
+
+/// `0x2` for EIP-1559 transactions, `0x0` for legacy ones sent with only a gas price
+/// Hash of the block that included a transaction; rows recorded before block hashes were kept
+/// were all included before any reorg
+fn included_block_hash(transaction: &ChainTransactionModel) -> String {
+    transaction
+        .block_hash
+        .clone()
+        .unwrap_or_else(|| block_hash(transaction.block_number.unwrap_or_default() as u64, 0))
+}
+
+fn transaction_type(transaction: &ChainTransactionModel) -> &'static str {
+    if transaction.max_fee_per_gas.is_some() { "0x2" } else { "0x0" }
+}
+
+fn empty_bloom() -> String {
+    format!("0x{}", "0".repeat(512))
+}
@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Standard JSON-RPC 2.0 error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+#[derive(Debug, Deserialize)]
+pub struct RpcRequest {
+    /// Absent for notifications, which get no response
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into() }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        RpcError::new(INVALID_PARAMS, message)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcOutcome {
+    Result(Value),
+    Error(RpcError),
+}
+
+#[derive(Debug, Serialize)]
+pub struct RpcResponse {
+    pub jsonrpc: &'static str,
+    pub id: Value,
+    #[serde(flatten)]
+    pub outcome: RpcOutcome,
+}
+
+impl RpcResponse {
+    pub fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        RpcResponse {
+            jsonrpc: "2.0",
+            id,
+            outcome: match outcome {
+                Ok(result) => RpcOutcome::Result(result),
+                Err(error) => RpcOutcome::Error(error),
+            },
+        }
+    }
+}
+
+/// Block parameter of `eth_getBlockByNumber`
+#[derive(Debug, PartialEq)]
+pub enum BlockTag {
+    Number(u64),
+    Earliest,
+    /// `latest`, `pending`, `safe` and `finalized` all resolve to the head
+    Latest,
+}
+
+impl BlockTag {
+    pub fn parse(value: &Value) -> Result<Self, RpcError> {
+        let tag = value.as_str().ok_or_else(|| RpcError::invalid_params("Block must be a hex number or tag"))?;
+        match tag {
+            "earliest" => Ok(BlockTag::Earliest),
+            "latest" | "pending" | "safe" | "finalized" => Ok(BlockTag::Latest),
+            number => parse_quantity(number).map(BlockTag::Number),
+        }
+    }
+}
+
+/// Encode a number as an Ethereum JSON-RPC quantity (`0x`-prefixed hex without leading zeros)
+pub fn quantity(value: impl std::fmt::LowerHex) -> String {
+    format!("0x{:x}", value)
+}
+
+pub fn parse_quantity(value: &str) -> Result<u64, RpcError> {
+    value
+        .strip_prefix("0x")
+        .filter(|digits| !digits.is_empty())
+        .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+        .ok_or_else(|| RpcError::invalid_params(format!("Invalid quantity: {}", value)))
+}
+
+/// Left-pad a value to a 32-byte topic
+pub fn topic(bytes: &[u8]) -> String {
+    let mut padded = [0u8; 32];
+    let len = bytes.len().min(32);
+    padded[32 - len..].copy_from_slice(&bytes[bytes.len() - len..]);
+    format!("0x{}", hex::encode(padded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_quantity_roundtrip() {
+        assert_eq!(quantity(0), "0x0");
+        assert_eq!(quantity(19_000_000), "0x121eac0");
+        assert_eq!(parse_quantity("0x121eac0").unwrap(), 19_000_000);
+        assert!(parse_quantity("0x").is_err());
+        assert!(parse_quantity("121eac0").is_err());
+    }
+
+    #[test]
+    fn test_block_tags() {
+        assert_eq!(BlockTag::parse(&json!("latest")).unwrap(), BlockTag::Latest);
+        assert_eq!(BlockTag::parse(&json!("earliest")).unwrap(), BlockTag::Earliest);
+        assert_eq!(BlockTag::parse(&json!("0x10")).unwrap(), BlockTag::Number(16));
+        assert!(BlockTag::parse(&json!(16)).is_err());
+    }
+
+    #[test]
+    fn test_response_shape() {
+        let found = serde_json::to_value(RpcResponse::new(json!(1), Ok(Value::Null))).unwrap();
+        assert_eq!(found, json!({"jsonrpc": "2.0", "id": 1, "result": null}));
+
+        let failed = serde_json::to_value(RpcResponse::new(json!("a"), Err(RpcError::new(METHOD_NOT_FOUND, "nope")))).unwrap();
+        assert_eq!(failed, json!({"jsonrpc": "2.0", "id": "a", "error": {"code": -32601, "message": "nope"}}));
+    }
+}
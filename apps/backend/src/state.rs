@@ -0,0 +1,46 @@
+use axum::extract::FromRef;
+use crate::auth::config::AuthConfig;
+use crate::blockchain_sim::SharedSimulator;
+use crate::database::DbPool;
+use crate::metadata::config::MetadataConfig;
+use crate::minting_queue::MintingQueue;
+
+/// Shared application state handed to every route
+#[derive(Clone)]
+pub struct AppState {
+    pub db: DbPool,
+    pub minting_queue: MintingQueue,
+    pub auth: AuthConfig,
+    pub simulator: SharedSimulator,
+    pub metadata: MetadataConfig,
+}
+
+impl FromRef<AppState> for DbPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for MintingQueue {
+    fn from_ref(state: &AppState) -> Self {
+        state.minting_queue.clone()
+    }
+}
+
+impl FromRef<AppState> for AuthConfig {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth.clone()
+    }
+}
+
+impl FromRef<AppState> for SharedSimulator {
+    fn from_ref(state: &AppState) -> Self {
+        state.simulator.clone()
+    }
+}
+
+impl FromRef<AppState> for MetadataConfig {
+    fn from_ref(state: &AppState) -> Self {
+        state.metadata.clone()
+    }
+}
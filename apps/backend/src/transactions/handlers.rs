@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+    response::IntoResponse,
+    http::StatusCode,
+};
+use chrono::DateTime;
+use crate::{
+    database::DbPool,
+    auth::types::ApiResponse,
+    blockchain_sim::{SharedSimulator, TransactionLog, TransactionStatus},
+    db_operations::find_transaction_with_nft,
+    transactions::types::*,
+};
+
+pub async fn get_transaction_handler(
+    State(pool): State<DbPool>,
+    State(simulator): State<SharedSimulator>,
+    Path(hash): Path<String>,
+) -> impl IntoResponse {
+    let (transaction, nft) = match find_transaction_with_nft(&pool, &hash.to_lowercase()).await {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            let response = ApiResponse::<TransactionReceipt> {
+                success: false,
+                data: None,
+                message: "Transaction not found".to_string(),
+            };
+            return (StatusCode::NOT_FOUND, Json(response));
+        }
+        Err(e) => {
+            let response = ApiResponse::<TransactionReceipt> {
+                success: false,
+                data: None,
+                message: format!("Failed to retrieve transaction: {}", e),
+            };
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(response));
+        }
+    };
+
+    let status = transaction.status.parse().unwrap_or(TransactionStatus::Pending);
+    let block_number = transaction.block_number.map(|block| block as u64);
+    // Failed and never-mined transactions emit no events
+    let logs: Vec<TransactionLog> = match status {
+        TransactionStatus::Failed | TransactionStatus::Dropped | TransactionStatus::Replaced => Vec::new(),
+        _ => serde_json::from_value(transaction.logs).unwrap_or_default(),
+    };
+    let gas_used = transaction.gas_used as u64;
+    let transaction_fee = transaction.gas_price.saturating_mul(gas_used);
+
+    let receipt = TransactionReceipt {
+        hash: transaction.hash,
+        status,
+        block_number,
+        block_hash: transaction.block_hash,
+        nonce: transaction.nonce.map(|nonce| nonce as u64),
+        replaced_by: transaction.replaced_by,
+        block_timestamp: transaction.block_timestamp.map(|at| DateTime::from_naive_utc_and_offset(at, chrono::Utc)),
+        confirmations: block_number.map_or(0, |block| simulator.confirmations(block)),
+        token: nft.map(|nft| TransferredToken {
+            nft_id: nft.id,
+            token_id: nft.token_id,
+            name: nft.name,
+            contract_address: transaction.to_address.clone(),
+        }),
+        from: transaction.from_address,
+        to: transaction.to_address,
+        gas_used,
+        effective_gas_price: transaction.gas_price,
+        max_fee_per_gas: transaction.max_fee_per_gas,
+        max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+        base_fee_per_gas: transaction.base_fee_per_gas,
+        transaction_fee,
+        transaction_fee_eth: transaction_fee.to_eth_string(),
+        logs,
+        revert_reason: transaction.revert_reason,
+    };
+
+    let response = ApiResponse {
+        success: true,
+        data: Some(receipt),
+        message: "Transaction retrieved successfully".to_string(),
+    };
+    (StatusCode::OK, Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+    use crate::blockchain_sim::{BlockchainSimulator, Clock, SimulatorConfig};
+    use crate::entities::{ChainTransactionModel, NftModel};
+    use crate::wei::Wei;
+
+    #[tokio::test]
+    async fn test_receipt_fee_saturates() {
+        let now = chrono::Utc::now().naive_utc();
+        let transaction = ChainTransactionModel {
+            hash: "0x01".to_string(),
+            from_address: "0xabc".to_string(),
+            to_address: "0xdef".to_string(),
+            nft_id: None,
+            status: TransactionStatus::Confirmed.as_str().to_string(),
+            block_number: None,
+            block_timestamp: None,
+            gas_used: 176_319,
+            gas_price: Wei::MAX,
+            logs: serde_json::json!([]),
+            revert_reason: None,
+            created_at: now,
+            updated_at: now,
+            max_fee_per_gas: Some(Wei::MAX),
+            max_priority_fee_per_gas: Some(Wei::MAX),
+            base_fee_per_gas: None,
+            block_hash: None,
+            nonce: Some(0),
+            replaced_by: None,
+        };
+        let db = MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([vec![(transaction, None::<NftModel>)]]);
+        let simulator = Arc::new(BlockchainSimulator::new(SimulatorConfig::default(), Clock::manual(1_700_000_000)));
+
+        let response = get_transaction_handler(State(Arc::new(db.into_connection())), State(simulator), Path("0x01".to_string()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let receipt = &serde_json::from_slice::<serde_json::Value>(&body).unwrap()["data"];
+
+        // The fee can't exceed what a U256 holds, and comes back whole rather than overflowing
+        let max = Wei::MAX.to_string();
+        assert_eq!(receipt["effective_gas_price"], max);
+        assert_eq!(receipt["max_fee_per_gas"], max);
+        assert_eq!(receipt["transaction_fee"], max);
+        assert_eq!(receipt["transaction_fee_eth"], Wei::MAX.to_eth_string());
+        assert_eq!(serde_json::from_value::<Wei>(receipt["transaction_fee"].clone()).unwrap(), Wei::MAX);
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use crate::blockchain_sim::{TransactionLog, TransactionStatus};
+use crate::wei::Wei;
+
+/// The NFT a transaction transferred
+#[derive(Debug, Serialize)]
+pub struct TransferredToken {
+    pub nft_id: String,
+    pub token_id: String,
+    pub name: String,
+    pub contract_address: String,
+}
+
+/// Receipt for a simulated transaction, as shown on the `/tx/[hash]` page
+#[derive(Debug, Serialize)]
+pub struct TransactionReceipt {
+    pub hash: String,
+    pub status: TransactionStatus,
+    /// `None` while the transaction is still in the mempool
+    pub block_number: Option<u64>,
+    /// Identifies the including block, whose number a reorg may have given to another block
+    pub block_hash: Option<String>,
+    /// `None` for transactions sent before nonces were simulated
+    pub nonce: Option<u64>,
+    /// Transaction with the same nonce that took this one's place before it was mined
+    pub replaced_by: Option<String>,
+    pub block_timestamp: Option<DateTime<Utc>>,
+    pub confirmations: u32,
+    pub from: String,
+    pub to: String,
+    pub gas_used: u64,
+    /// Price paid per unit of gas, in wei
+    pub effective_gas_price: Wei,
+    /// EIP-1559 fee caps, in wei; `None` for legacy transactions
+    pub max_fee_per_gas: Option<Wei>,
+    pub max_priority_fee_per_gas: Option<Wei>,
+    /// Base fee of the including block, in wei
+    pub base_fee_per_gas: Option<Wei>,
+    /// `gas_used * effective_gas_price`, in wei
+    pub transaction_fee: Wei,
+    /// The same fee in ETH, exact to the wei
+    pub transaction_fee_eth: String,
+    pub token: Option<TransferredToken>,
+    pub logs: Vec<TransactionLog>,
+    /// Why a mined transaction failed
+    pub revert_reason: Option<String>,
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+use std::ops::Div;
+use std::str::FromStr;
+use anyhow::{anyhow, Result};
+use primitive_types::U256;
+use sea_orm::sea_query::{ArrayType, ColumnType, Nullable, ValueType, ValueTypeErr};
+use sea_orm::{ColIdx, QueryResult, TryGetError, TryGetable, Value};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Decimal places between wei and gwei
+pub const GWEI_DECIMALS: usize = 9;
+/// Decimal places between wei and ether
+pub const ETH_DECIMALS: usize = 18;
+
+/// An exact amount of ether in wei, wide enough for any on-chain value.
+///
+/// Serialized as a decimal string so JSON clients never round it, and stored as decimal text.
+/// Arithmetic saturates rather than overflowing, as fees come from clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Wei(U256);
+
+impl Wei {
+    pub const ZERO: Wei = Wei::new(0);
+    pub const MAX: Wei = Wei(U256::MAX);
+
+    pub const fn new(wei: u64) -> Self {
+        Wei(U256([wei, 0, 0, 0]))
+    }
+
+    pub fn from_gwei(gwei: u64) -> Self {
+        Wei(U256::from(gwei) * U256::exp10(GWEI_DECIMALS))
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn saturating_add(self, other: Wei) -> Wei {
+        Wei(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Wei) -> Wei {
+        Wei(self.0.saturating_sub(other.0))
+    }
+
+    pub fn saturating_mul(self, factor: u64) -> Wei {
+        Wei(self.0.saturating_mul(U256::from(factor)))
+    }
+
+    /// `self * numerator / denominator`, rounding up
+    pub fn mul_div_ceil(self, numerator: u64, denominator: u64) -> Wei {
+        let (quotient, remainder) = self.0.saturating_mul(U256::from(numerator)).div_mod(U256::from(denominator));
+        Wei(if remainder.is_zero() { quotient } else { quotient.saturating_add(U256::one()) })
+    }
+
+    /// Exact amount in gwei, e.g. `"11.5"`
+    pub fn to_gwei_string(self) -> String {
+        self.format_units(GWEI_DECIMALS)
+    }
+
+    /// Exact amount in ether, e.g. `"0.0020276685"`
+    pub fn to_eth_string(self) -> String {
+        self.format_units(ETH_DECIMALS)
+    }
+
+    fn format_units(self, decimals: usize) -> String {
+        let digits = format!("{:0>width$}", self.0.to_string(), width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, fraction)
+        }
+    }
+}
+
+impl From<u64> for Wei {
+    fn from(wei: u64) -> Self {
+        Wei::new(wei)
+    }
+}
+
+impl Div<u64> for Wei {
+    type Output = Wei;
+
+    fn div(self, divisor: u64) -> Wei {
+        Wei(self.0 / U256::from(divisor))
+    }
+}
+
+/// Decimal wei
+impl fmt::Display for Wei {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for Wei {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+/// Decimal wei, or a `0x` hex quantity
+impl FromStr for Wei {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parsed = match s.strip_prefix("0x") {
+            Some(hex) if !hex.is_empty() => U256::from_str_radix(hex, 16).map_err(|_| ()),
+            Some(_) => Err(()),
+            None if !s.is_empty() && s.bytes().all(|byte| byte.is_ascii_digit()) => U256::from_dec_str(s).map_err(|_| ()),
+            None => Err(()),
+        };
+        parsed.map(Wei).map_err(|_| anyhow!("Invalid wei amount: {}", s))
+    }
+}
+
+impl Serialize for Wei {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Accepts plain JSON integers as well, as clients sent before amounts were strings
+impl<'de> Deserialize<'de> for Wei {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct WeiVisitor;
+
+        impl de::Visitor<'_> for WeiVisitor {
+            type Value = Wei;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a wei amount as a decimal string or an unsigned integer")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Wei, E> {
+                Ok(Wei::from(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Wei, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(WeiVisitor)
+    }
+}
+
+impl From<Wei> for Value {
+    fn from(wei: Wei) -> Self {
+        Value::String(Some(Box::new(wei.to_string())))
+    }
+}
+
+impl TryGetable for Wei {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        let text = String::try_get_by(res, index)?;
+        text.parse()
+            .map_err(|error: anyhow::Error| TryGetError::DbErr(sea_orm::DbErr::Type(error.to_string())))
+    }
+}
+
+impl ValueType for Wei {
+    fn try_from(value: Value) -> Result<Self, ValueTypeErr> {
+        match value {
+            Value::String(Some(text)) => text.parse().map_err(|_| ValueTypeErr),
+            _ => Err(ValueTypeErr),
+        }
+    }
+
+    fn type_name() -> String {
+        "Wei".to_string()
+    }
+
+    fn array_type() -> ArrayType {
+        ArrayType::String
+    }
+
+    fn column_type() -> ColumnType {
+        ColumnType::Text
+    }
+}
+
+impl Nullable for Wei {
+    fn null() -> Value {
+        Value::String(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_formatting() {
+        let fee = Wei::from(11_500_000_000).saturating_mul(176_319);
+        assert_eq!(fee.to_string(), "2027668500000000");
+        assert_eq!(fee.to_eth_string(), "0.0020276685");
+        assert_eq!(Wei::from(11_500_000_000).to_gwei_string(), "11.5");
+        assert_eq!(Wei::from_gwei(3).to_eth_string(), "0.000000003");
+        assert_eq!(Wei::ZERO.to_eth_string(), "0");
+
+        // Far beyond u64 without losing a wei
+        let large = Wei::from(u64::MAX).saturating_mul(u64::MAX);
+        assert_eq!(large.to_string(), "340282366920938463426481119284349108225");
+        assert_eq!(large.to_eth_string(), "340282366920938463426.481119284349108225");
+    }
+
+    #[test]
+    fn test_serde_as_decimal_string() {
+        let wei: Wei = "340282366920938463426481119284349108225".parse().unwrap();
+        let json = serde_json::to_string(&wei).unwrap();
+        assert_eq!(json, "\"340282366920938463426481119284349108225\"");
+        assert_eq!(serde_json::from_str::<Wei>(&json).unwrap(), wei);
+        assert_eq!(serde_json::from_str::<Wei>("30000000000").unwrap(), Wei::from_gwei(30));
+        assert_eq!(serde_json::from_str::<Wei>("\"0x1e\"").unwrap(), Wei::from(30));
+        assert!(serde_json::from_str::<Wei>("\"1.5\"").is_err());
+        assert!(serde_json::from_str::<Wei>("-1").is_err());
+    }
+
+    #[test]
+    fn test_rounding_up() {
+        assert_eq!(Wei::from(1_000).mul_div_ceil(110, 100), Wei::from(1_100));
+        assert_eq!(Wei::from(1).mul_div_ceil(110, 100), Wei::from(2));
+    }
+}
@@ -4,6 +4,7 @@ import { motion } from 'framer-motion';
 import { useAccount } from 'wagmi';
 import { ConnectButton } from '@repo/web3';
 import { apiClient, AdminStats, AnalyticsData } from '../../lib/api-client';
+import { useSession } from '../../hooks/useSession';
 import Navigation from '../../components/Navigation';
 import { formatNumber, formatPrice } from '../../lib/utils';
 import { 
@@ -45,10 +46,19 @@ const AdminDashboard = () => {
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
 
+  const { ensureSession } = useSession();
+
   useEffect(() => {
     const fetchData = async () => {
+      if (!isConnected || !address) {
+        setError('Connect an admin wallet to view the dashboard');
+        setLoading(false);
+        return;
+      }
       try {
         setLoading(true);
+        setError(null);
+        await ensureSession();
         const [statsData, analyticsData] = await Promise.all([
           apiClient.getAdminStats(),
           apiClient.getAdminAnalytics()
@@ -64,7 +74,7 @@ const AdminDashboard = () => {
     };
 
     fetchData();
-  }, []);
+  }, [isConnected, address, ensureSession]);
 
   if (loading) {
     return (
@@ -3,6 +3,7 @@ import React, { useState, useEffect } from 'react';
 import { useAccount } from 'wagmi';
 import { ConnectButton } from '@repo/web3';
 import { apiClient, NFTResponse } from '../../lib/api-client';
+import { formatAttributeValue } from '../../lib/utils';
 import Image from 'next/image';
 import Navigation from '../../components/Navigation';
 import { NFTDetailsModal } from '../../components/NFTCard';
@@ -70,7 +71,7 @@ const NFTCard = ({ nft, onMint }: { nft: NFTResponse; onMint: (id: string) => vo
               {nft.attributes.slice(0, 4).map((attr, idx) => (
                 <div key={idx} className="bg-white/5 backdrop-blur-sm rounded-lg p-2.5 border border-white/10">
                   <span className="text-slate-400 text-xs font-medium uppercase tracking-wide">{attr.trait_type}</span>
-                  <p className="text-white text-sm font-semibold mt-0.5 truncate">{attr.value}</p>
+                  <p className="text-white text-sm font-semibold mt-0.5 truncate">{formatAttributeValue(attr)}</p>
                 </div>
               ))}
             </div>
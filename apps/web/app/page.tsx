@@ -5,6 +5,7 @@ import React, { useState, useEffect } from 'react';
 import { useAccount, useConnect, useDisconnect } from 'wagmi';
 import { injected } from 'wagmi/connectors';
 import Navigation from '../components/Navigation';
+import { apiClient } from '../lib/api-client';
 
 const NFTLandingPage = () => {
   const [isVisible, setIsVisible] = useState(false);
@@ -89,7 +90,7 @@ const NFTLandingPage = () => {
             </span>
           </div>
           <button 
-            onClick={() => disconnect()}
+            onClick={() => { apiClient.signOut(); disconnect(); }}
             className="bg-red-500/20 hover:bg-red-500/30 border border-red-500/30 text-red-400 hover:text-red-300 font-medium py-2.5 px-5 rounded-xl transition-all duration-300"
           >
             Disconnect
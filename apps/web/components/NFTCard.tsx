@@ -3,7 +3,7 @@ import React from 'react';
 import Image from 'next/image';
 import { motion, AnimatePresence } from 'framer-motion';
 import { NFTResponse } from '../lib/api-client';
-import { cn, formatAddress, getRarityColor, getRarityPercentage } from '../lib/utils';
+import { cn, formatAddress, formatAttributeValue, getRarityColor, getRarityPercentage } from '../lib/utils';
 import { Eye, Heart, Share2, ExternalLink, X, Copy, CheckCircle, Clock, Calendar, Hash, Zap, Blocks } from 'lucide-react';
 
 interface NFTCardProps {
@@ -35,7 +35,7 @@ export const NFTDetailsModal: React.FC<NFTDetailsModalProps> = ({ nft, isOpen, o
     }
   };
 
-  const rarity = nft?.attributes?.find(attr => attr.trait_type === 'Rarity')?.value || 'Common';
+  const rarity = String(nft?.attributes?.find(attr => attr.trait_type === 'Rarity')?.value || 'Common');
   const rarityColor = getRarityColor(rarity);
   const rarityPercentage = getRarityPercentage(rarity);
 
@@ -231,7 +231,7 @@ export const NFTDetailsModal: React.FC<NFTDetailsModalProps> = ({ nft, isOpen, o
                           <div className="text-sm text-slate-400 font-medium uppercase tracking-wide mb-1">
                             {attr.trait_type}
                           </div>
-                          <div className="text-white font-semibold">{attr.value}</div>
+                          <div className="text-white font-semibold">{formatAttributeValue(attr)}</div>
                         </div>
                       ))}
                     </div>
@@ -266,7 +266,7 @@ const NFTCard: React.FC<NFTCardProps> = ({
   className,
   showActions = true
 }) => {
-  const rarity = nft.attributes?.find(attr => attr.trait_type === 'Rarity')?.value || 'Common';
+  const rarity = String(nft.attributes?.find(attr => attr.trait_type === 'Rarity')?.value || 'Common');
   const rarityColor = getRarityColor(rarity);
   const rarityPercentage = getRarityPercentage(rarity);
 
@@ -396,7 +396,7 @@ const NFTCard: React.FC<NFTCardProps> = ({
               {nft.attributes.slice(0, 4).map((attr, idx) => (
                 <div key={idx} className="bg-white/5 backdrop-blur-sm rounded-lg p-2.5 border border-white/10">
                   <span className="text-slate-400 text-xs font-medium uppercase tracking-wide">{attr.trait_type}</span>
-                  <p className="text-white text-sm font-semibold mt-0.5 truncate">{attr.value}</p>
+                  <p className="text-white text-sm font-semibold mt-0.5 truncate">{formatAttributeValue(attr)}</p>
                 </div>
               ))}
             </div>
@@ -1,6 +1,7 @@
 import { useState, useCallback } from 'react';
 import { useAccount } from 'wagmi';
-import { apiClient, NFTMetadata, MintResponse, MintStatusResponse } from '../lib/api-client';
+import { apiClient, ApiError, NFTMetadata, MintResponse, MintStatusResponse } from '../lib/api-client';
+import { useSession } from './useSession';
 
 export interface UseMintingReturn {
   isMinting: boolean;
@@ -11,8 +12,31 @@ export interface UseMintingReturn {
   resetMinting: () => void;
 }
 
+const MINT_ATTEMPTS = 3;
+const MINT_RETRY_DELAY_MS = 1000;
+
+// Only a lost request or a server error may go through on a retry; any other answer would just repeat
+const isRetryable = (error: unknown): boolean =>
+  error instanceof ApiError && (error.status === 0 || error.status >= 500);
+
+// Backs off 1s, then 2s, between attempts
+const mintWithRetries = async (request: NFTMetadata, idempotencyKey: string): Promise<MintResponse> => {
+  for (let attempt = 1; ; attempt++) {
+    try {
+      return await apiClient.mintNFT(request, idempotencyKey);
+    } catch (error) {
+      if (attempt >= MINT_ATTEMPTS || !isRetryable(error)) {
+        throw error;
+      }
+      console.warn(`Mint request failed, retrying (attempt ${attempt + 1} of ${MINT_ATTEMPTS}):`, error);
+      await new Promise(resolve => setTimeout(resolve, MINT_RETRY_DELAY_MS * 2 ** (attempt - 1)));
+    }
+  }
+};
+
 export const useMinting = (): UseMintingReturn => {
   const { address, isConnected } = useAccount();
+  const { ensureSession } = useSession();
   const [isMinting, setIsMinting] = useState(false);
   const [mintStatus, setMintStatus] = useState('');
   const [mintResult, setMintResult] = useState<MintResponse | null>(null);
@@ -26,6 +50,11 @@ export const useMinting = (): UseMintingReturn => {
     setMintStatus('Preparing transaction...');
 
     try {
+      // Minting needs a signed-in session; the wallet signs a sign-in message the first time
+      setMintStatus('Please sign in with your wallet...');
+      await ensureSession();
+      setMintStatus('Preparing transaction...');
+
       // Step 1: Prepare transaction (simulate blockchain interaction)
       await new Promise(resolve => setTimeout(resolve, 2000));
       setMintStatus('Please confirm in your wallet...');
@@ -34,11 +63,10 @@ export const useMinting = (): UseMintingReturn => {
       await new Promise(resolve => setTimeout(resolve, 3000));
       setMintStatus('Transaction submitted...');
 
-      // Step 3: Call backend API
-      const result = await apiClient.mintNFT({
-        ...metadata,
-        owner_wallet: address,
-      });
+      // Step 3: Call backend API; retries reuse the key so they can't mint twice
+      const idempotencyKey = crypto.randomUUID();
+      const request = { ...metadata, owner_wallet: address };
+      const result = await mintWithRetries(request, idempotencyKey);
 
       setMintResult(result);
       setMintStatus('Transaction confirmed!');
@@ -51,7 +79,7 @@ export const useMinting = (): UseMintingReturn => {
     } finally {
       setIsMinting(false);
     }
-  }, [isConnected, address]);
+  }, [isConnected, address, ensureSession]);
 
   const checkMintStatus = useCallback(async (mintId: string): Promise<MintStatusResponse> => {
     return await apiClient.getMintStatus(mintId);
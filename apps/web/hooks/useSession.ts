@@ -0,0 +1,27 @@
+import { useCallback } from 'react';
+import { useAccount, useSignMessage } from 'wagmi';
+import { apiClient } from '../lib/api-client';
+
+export interface UseSessionReturn {
+  /** Sign in with the connected wallet unless it already has a session */
+  ensureSession: () => Promise<void>;
+  signOut: () => Promise<void>;
+}
+
+export const useSession = (): UseSessionReturn => {
+  const { address } = useAccount();
+  const { signMessageAsync } = useSignMessage();
+
+  const ensureSession = useCallback(async () => {
+    if (!address) {
+      throw new Error('Wallet not connected');
+    }
+    if (!apiClient.hasSession(address)) {
+      await apiClient.signIn(address, message => signMessageAsync({ message }));
+    }
+  }, [address, signMessageAsync]);
+
+  const signOut = useCallback(() => apiClient.signOut(), []);
+
+  return { ensureSession, signOut };
+};
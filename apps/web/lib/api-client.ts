@@ -1,14 +1,24 @@
+import { createSiweMessage, SIWE_CHAIN_ID } from './siwe';
+
 const API_BASE_URL = 'http://localhost:8000/api';
+const SESSION_STORAGE_KEY = 'mintverse.session';
+// A mint that hasn't answered by then is given up on, and retried with the same idempotency key
+const MINT_TIMEOUT_MS = 30_000;
+
+export interface NFTAttribute {
+  trait_type: string;
+  /** Dates are unix seconds */
+  value: string | number | boolean;
+  display_type?: 'number' | 'boost_number' | 'boost_percentage' | 'date';
+  max_value?: number;
+}
 
 export interface NFTMetadata {
   name: string;
   description?: string;
   image_url: string;
   owner_wallet: string;
-  attributes?: Array<{
-    trait_type: string;
-    value: string;
-  }>;
+  attributes?: NFTAttribute[];
   collection_name?: string;
 }
 
@@ -21,15 +31,13 @@ export interface NFTResponse {
   minted_at: string;
   transaction_hash?: string;
   owner_id: string;
-  attributes?: Array<{
-    trait_type: string;
-    value: string;
-  }>;
+  attributes?: NFTAttribute[];
   collection_name?: string;
   mint_status?: string;
   block_number?: number;
   gas_used?: number;
-  gas_price?: number;
+  /** Decimal wei */
+  gas_price?: string;
 }
 
 export interface MintResponse {
@@ -39,7 +47,8 @@ export interface MintResponse {
   transaction_hash?: string;
   block_number?: number;
   gas_used?: number;
-  gas_price?: number;
+  /** Decimal wei */
+  gas_price?: string;
   mint_status: string;
   message: string;
 }
@@ -50,7 +59,8 @@ export interface MintStatusResponse {
   transaction_hash?: string;
   block_number?: number;
   gas_used?: number;
-  gas_price?: number;
+  /** Decimal wei */
+  gas_price?: string;
   confirmations?: number;
   created_at: number;
   confirmed_at?: number;
@@ -69,6 +79,22 @@ export interface ApiResponse<T> {
   message: string;
 }
 
+/** A request the backend answered with an error status, or that never got an answer (status 0) */
+export class ApiError extends Error {
+  constructor(public status: number, message: string) {
+    super(message);
+    this.name = 'ApiError';
+  }
+}
+
+/** A signed-in wallet's bearer token, from POST /auth/verify */
+export interface Session {
+  token: string;
+  expires_at: string;
+  user_id: string;
+  wallet_address: string;
+}
+
 // Admin types
 export interface AdminStats {
   total_users: number;
@@ -78,13 +104,15 @@ export interface AdminStats {
   minting_trends: Array<{
     date: string;
     count: number;
-    volume: number;
+    /** Decimal wei */
+    volume: string;
   }>;
   popular_collections: Array<{
     id: string;
     name: string;
     nft_count: number;
-    total_volume: number;
+    /** Decimal wei */
+    total_volume: string;
   }>;
   user_engagement: {
     active_users_24h: number;
@@ -143,8 +171,9 @@ export interface Collection {
   creator_wallet: string;
   nft_count: number;
   unique_owners: number;
-  total_volume: number;
-  floor_price?: number;
+  /** Decimal wei */
+  total_volume: string;
+  floor_price?: string;
   created_at: string;
   is_featured: boolean;
 }
@@ -159,9 +188,14 @@ export interface CreateCollectionRequest {
 
 class ApiClient {
   private baseUrl: string;
+  private session: Session | null = null;
 
   constructor(baseUrl: string = API_BASE_URL) {
     this.baseUrl = baseUrl;
+    if (typeof window !== 'undefined') {
+      const stored = window.localStorage.getItem(SESSION_STORAGE_KEY);
+      this.session = stored ? JSON.parse(stored) : null;
+    }
   }
 
   private async request<T>(
@@ -173,19 +207,80 @@ class ApiClient {
     const defaultOptions: RequestInit = {
       headers: {
         'Content-Type': 'application/json',
+        ...(this.session ? { Authorization: `Bearer ${this.session.token}` } : {}),
         ...options.headers,
       },
     };
 
-    const response = await fetch(url, { ...defaultOptions, ...options });
+    let response: Response;
+    try {
+      response = await fetch(url, { ...options, headers: defaultOptions.headers });
+    } catch (error) {
+      throw new ApiError(0, `API request failed: ${error instanceof Error ? error.message : error}`);
+    }
     
     if (!response.ok) {
-      throw new Error(`API request failed: ${response.status} ${response.statusText}`);
+      // The session expired or was signed out elsewhere; the next write signs in again
+      if (response.status === 401) {
+        this.setSession(null);
+      }
+      const body = await response.json().catch(() => null);
+      throw new ApiError(response.status, body?.message ?? `API request failed: ${response.status} ${response.statusText}`);
     }
 
     return response.json();
   }
 
+  private setSession(session: Session | null) {
+    this.session = session;
+    if (typeof window !== 'undefined') {
+      if (session) {
+        window.localStorage.setItem(SESSION_STORAGE_KEY, JSON.stringify(session));
+      } else {
+        window.localStorage.removeItem(SESSION_STORAGE_KEY);
+      }
+    }
+  }
+
+  /** Whether `walletAddress` holds an unexpired session */
+  hasSession(walletAddress: string): boolean {
+    return (
+      this.session !== null &&
+      this.session.wallet_address.toLowerCase() === walletAddress.toLowerCase() &&
+      new Date(this.session.expires_at).getTime() > Date.now()
+    );
+  }
+
+  // Sign-In With Ethereum: sign a message carrying a fresh nonce, then trade it for a bearer token
+  async signIn(walletAddress: string, signMessage: (message: string) => Promise<string>): Promise<Session> {
+    const nonce = await this.request<{ nonce: string; expires_at: string }>('/auth/nonce');
+    const message = createSiweMessage({
+      domain: window.location.host,
+      address: walletAddress,
+      uri: window.location.origin,
+      chainId: SIWE_CHAIN_ID,
+      nonce: nonce.data!.nonce,
+      issuedAt: new Date(),
+      expirationTime: new Date(nonce.data!.expires_at),
+      statement: 'Sign in to Mintverse',
+    });
+    const signature = await signMessage(message);
+
+    const response = await this.request<Session>('/auth/verify', {
+      method: 'POST',
+      body: JSON.stringify({ message, signature }),
+    });
+    this.setSession(response.data!);
+    return response.data!;
+  }
+
+  async signOut(): Promise<void> {
+    if (this.session) {
+      await this.request('/auth/logout', { method: 'POST' }).catch(() => undefined);
+    }
+    this.setSession(null);
+  }
+
   // Health check
   async healthCheck(): Promise<{ status: string; database: string }> {
     const response = await fetch(`${this.baseUrl}/health`);
@@ -205,10 +300,13 @@ class ApiClient {
     return response.data!;
   }
 
-  async mintNFT(metadata: NFTMetadata): Promise<MintResponse> {
+  // Retrying with the same idempotency key returns the original mint instead of minting again
+  async mintNFT(metadata: NFTMetadata, idempotencyKey?: string): Promise<MintResponse> {
     const response = await this.request<MintResponse>('/nfts/mint', {
       method: 'POST',
       body: JSON.stringify(metadata),
+      headers: idempotencyKey ? { 'Idempotency-Key': idempotencyKey } : {},
+      signal: AbortSignal.timeout(MINT_TIMEOUT_MS),
     });
     return response.data!;
   }
@@ -0,0 +1,37 @@
+// Chain the backend's simulated chain reports (its CHAIN_ID); sign-in messages must name it
+export const SIWE_CHAIN_ID = 1337;
+
+export interface SiweMessageFields {
+  /** host[:port] the backend expects sign-ins for (its SIWE_DOMAIN) */
+  domain: string;
+  address: string;
+  uri: string;
+  chainId: number;
+  nonce: string;
+  issuedAt: Date;
+  expirationTime?: Date;
+  statement?: string;
+}
+
+/** EIP-4361 message text, in the exact layout the backend parses */
+export const createSiweMessage = (fields: SiweMessageFields): string => {
+  const lines = [
+    `${fields.domain} wants you to sign in with your Ethereum account:`,
+    fields.address,
+    '',
+  ];
+  if (fields.statement) {
+    lines.push(fields.statement, '');
+  }
+  lines.push(
+    `URI: ${fields.uri}`,
+    'Version: 1',
+    `Chain ID: ${fields.chainId}`,
+    `Nonce: ${fields.nonce}`,
+    `Issued At: ${fields.issuedAt.toISOString()}`,
+  );
+  if (fields.expirationTime) {
+    lines.push(`Expiration Time: ${fields.expirationTime.toISOString()}`);
+  }
+  return lines.join('\n');
+};
@@ -1,5 +1,6 @@
 import { type ClassValue, clsx } from "clsx"
 import { twMerge } from "tailwind-merge"
+import type { NFTAttribute } from "./api-client"
 
 export function cn(...inputs: ClassValue[]) {
   return twMerge(clsx(inputs))
@@ -24,6 +25,20 @@ export function formatPrice(price: number): string {
   return `${price.toFixed(2)} ETH`
 }
 
+export function formatAttributeValue(attribute: NFTAttribute): string {
+  const { value, display_type, max_value } = attribute
+  if (display_type === 'date' && typeof value === 'number') {
+    return new Date(value * 1000).toLocaleDateString()
+  }
+  if (display_type === 'boost_percentage') {
+    return `+${value}%`
+  }
+  if (max_value !== undefined) {
+    return `${value} / ${max_value}`
+  }
+  return String(value)
+}
+
 export function getRarityColor(rarity: string): string {
   switch (rarity.toLowerCase()) {
     case 'legendary':
@@ -89,7 +104,7 @@ export function generateDemoNFTs(count: number) {
     mint_status: "Confirmed" as const,
     block_number: 12345678 + i,
     gas_used: 210000 + (i * 1000),
-    gas_price: 20000000000 + (i * 1000000)
+    gas_price: String(20000000000 + (i * 1000000))
   }))
 }
 